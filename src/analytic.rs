@@ -0,0 +1,40 @@
+use std::f64::consts::PI;
+
+use crate::constants::PERMITTIVITY;
+use crate::vector::Vec3;
+
+/// Computes the electric field, in volts per meter, at a point due to a point charge, in
+/// coulombs, at the source position in a vacuum.
+///
+/// Simulated fields can be compared against this far from the boundaries of the mesh to
+/// validate the deposition, solve and gather end to end. The field is infinite at the source.
+pub fn analytic_point_charge_field(q: f64, source: Vec3, at: Vec3) -> Vec3 {
+    let separation = at - source;
+    let distance = separation.magnitude();
+
+    separation * (q / (4.0 * PI * PERMITTIVITY * distance.powi(3)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::constants::ELEMENTARY_CHARGE;
+
+    #[test]
+    fn test_analytic_point_charge_field() {
+        let source = Vec3::new(0.1, -0.2, 0.3);
+        let direction = Vec3::new(2.0, 3.0, -6.0) / 7.0;
+        let near = analytic_point_charge_field(ELEMENTARY_CHARGE, source, source + direction);
+        let far = analytic_point_charge_field(ELEMENTARY_CHARGE, source, source + direction * 2.0);
+
+        // A proton produces a field of about 1.44e-9 V/m at one meter.
+        assert!((near.magnitude() - 1.44e-9).abs() < 1e-11);
+
+        // The magnitude falls off as the inverse square of the distance.
+        assert!((near.magnitude() / far.magnitude() - 4.0).abs() < 1e-12);
+
+        // The field points radially away from a positive source.
+        assert!((near.normalize() - direction).magnitude() < 1e-12);
+    }
+}
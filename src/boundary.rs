@@ -0,0 +1,278 @@
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Identifies an axis of a box mesh.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// The x-axis.
+    X,
+    /// The y-axis.
+    Y,
+    /// The z-axis.
+    Z,
+}
+
+impl Axis {
+    /// Returns the index of the axis within a logical coordinate or set of dimensions.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the faces at the minimum and maximum coordinates of the axis.
+    pub fn faces(self) -> (Face, Face) {
+        match self {
+            Axis::X => (Face::XMin, Face::XMax),
+            Axis::Y => (Face::YMin, Face::YMax),
+            Axis::Z => (Face::ZMin, Face::ZMax),
+        }
+    }
+}
+
+/// Identifies a face of a box mesh.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Face {
+    /// The face at the minimum x-coordinate.
+    XMin,
+    /// The face at the maximum x-coordinate.
+    XMax,
+    /// The face at the minimum y-coordinate.
+    YMin,
+    /// The face at the maximum y-coordinate.
+    YMax,
+    /// The face at the minimum z-coordinate.
+    ZMin,
+    /// The face at the maximum z-coordinate.
+    ZMax,
+}
+
+impl Face {
+    /// Lists every face of a box mesh.
+    pub const ALL: [Face; 6] = [
+        Face::XMin,
+        Face::XMax,
+        Face::YMin,
+        Face::YMax,
+        Face::ZMin,
+        Face::ZMax,
+    ];
+
+    /// Returns the index of the face for per-face storage.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the axis normal to the face.
+    pub fn axis(self) -> Axis {
+        match self {
+            Face::XMin | Face::XMax => Axis::X,
+            Face::YMin | Face::YMax => Axis::Y,
+            Face::ZMin | Face::ZMax => Axis::Z,
+        }
+    }
+
+    /// Returns whether the face lies at the minimum coordinate of its axis.
+    pub fn is_min(self) -> bool {
+        matches!(self, Face::XMin | Face::YMin | Face::ZMin)
+    }
+
+    /// Returns the face on the opposite side of the mesh.
+    pub fn opposite(self) -> Face {
+        let (min, max) = self.axis().faces();
+
+        if self.is_min() {
+            max
+        } else {
+            min
+        }
+    }
+}
+
+/// Represents a boundary condition on the potential at a face of the mesh.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", content = "value")
+)]
+pub enum BoundaryCondition {
+    /// Fixes the potential on the face to the provided value.
+    Dirichlet(f64),
+    /// Fixes the normal gradient of the potential on the face to zero.
+    Neumann,
+    /// Drives the potential on the face as `amplitude * sin(2π * frequency * t + phase)`.
+    Sinusoidal {
+        amplitude: f64,
+        frequency: f64,
+        phase: f64,
+    },
+    /// Connects the face to the opposite face, which must also be periodic, so that the
+    /// potential wraps around the mesh.
+    Periodic,
+    /// Leaves the potential on the face to be set externally, such as by the neighboring
+    /// block of a multi-block mesh.
+    Interface,
+}
+
+impl BoundaryCondition {
+    /// Returns the potential imposed on the face at the provided time, or `None` if the
+    /// condition constrains the gradient rather than the value of the potential.
+    pub fn potential(self, time: f64) -> Option<f64> {
+        match self {
+            BoundaryCondition::Dirichlet(value) => Some(value),
+            BoundaryCondition::Neumann
+            | BoundaryCondition::Periodic
+            | BoundaryCondition::Interface => None,
+            BoundaryCondition::Sinusoidal {
+                amplitude,
+                frequency,
+                phase,
+            } => Some(amplitude * (2.0 * PI * frequency * time + phase).sin()),
+        }
+    }
+}
+
+impl Default for BoundaryCondition {
+    fn default() -> Self {
+        BoundaryCondition::Dirichlet(0.0)
+    }
+}
+
+/// Represents the ways in which a set of boundary conditions can be inconsistent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoundaryError {
+    /// A face is periodic while the opposite face has a different condition.
+    UnpairedPeriodic {
+        face: Face,
+        opposite: BoundaryCondition,
+    },
+}
+
+impl fmt::Display for BoundaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoundaryError::UnpairedPeriodic { face, opposite } => write!(
+                f,
+                "Face {:?} is periodic, but the opposite face {:?} has condition {:?}.",
+                face,
+                face.opposite(),
+                opposite
+            ),
+        }
+    }
+}
+
+impl Error for BoundaryError {}
+
+/// Composes the boundary conditions on every face of a box mesh.
+///
+/// Faces default to a grounded Dirichlet condition, and conditions are chained per face, as
+/// in `BoundaryConditions::new().dirichlet(Face::XMin, 1.0).periodic_pair(Axis::Y)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BoundaryConditions {
+    /// The condition on each face, indexed by `Face::index`.
+    conditions: [BoundaryCondition; 6],
+}
+
+impl BoundaryConditions {
+    /// Creates a set of boundary conditions with every face grounded.
+    pub fn new() -> Self {
+        BoundaryConditions::default()
+    }
+
+    /// Sets the condition on a face.
+    pub fn condition(mut self, face: Face, condition: BoundaryCondition) -> Self {
+        self.conditions[face.index()] = condition;
+        self
+    }
+
+    /// Fixes the potential on a face to the provided value.
+    pub fn dirichlet(self, face: Face, value: f64) -> Self {
+        self.condition(face, BoundaryCondition::Dirichlet(value))
+    }
+
+    /// Fixes the normal gradient of the potential on a face to zero.
+    pub fn neumann(self, face: Face) -> Self {
+        self.condition(face, BoundaryCondition::Neumann)
+    }
+
+    /// Makes both faces of an axis periodic.
+    pub fn periodic_pair(self, axis: Axis) -> Self {
+        let (min, max) = axis.faces();
+
+        self.condition(min, BoundaryCondition::Periodic)
+            .condition(max, BoundaryCondition::Periodic)
+    }
+
+    /// Returns the condition on each face, indexed by `Face::index`, after checking that
+    /// every periodic face is paired with a periodic opposite face.
+    pub fn build(self) -> Result<[BoundaryCondition; 6], BoundaryError> {
+        for face in Face::ALL {
+            let opposite = self.conditions[face.opposite().index()];
+
+            if self.conditions[face.index()] == BoundaryCondition::Periodic
+                && opposite != BoundaryCondition::Periodic
+            {
+                return Err(BoundaryError::UnpairedPeriodic { face, opposite });
+            }
+        }
+
+        Ok(self.conditions)
+    }
+}
+
+/// Represents how particles are treated upon reaching a face of the mesh.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoundaryMode {
+    /// Particles are specularly reflected back into the domain.
+    #[default]
+    Reflecting,
+    /// Particles are removed from the simulation and their charge is collected by the face.
+    Absorbing,
+    /// Particles re-enter the mesh through the opposite face with unchanged velocity.
+    Periodic,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundary_conditions_builder() {
+        let conditions = BoundaryConditions::new()
+            .dirichlet(Face::XMin, 5.0)
+            .periodic_pair(Axis::Y)
+            .neumann(Face::ZMax)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            conditions[Face::XMin.index()],
+            BoundaryCondition::Dirichlet(5.0)
+        );
+        assert_eq!(
+            conditions[Face::XMax.index()],
+            BoundaryCondition::Dirichlet(0.0)
+        );
+        assert_eq!(conditions[Face::YMin.index()], BoundaryCondition::Periodic);
+        assert_eq!(conditions[Face::YMax.index()], BoundaryCondition::Periodic);
+        assert_eq!(conditions[Face::ZMax.index()], BoundaryCondition::Neumann);
+    }
+
+    #[test]
+    fn test_unpaired_periodic() {
+        let result = BoundaryConditions::new()
+            .periodic_pair(Axis::X)
+            .dirichlet(Face::XMax, 1.0)
+            .build();
+
+        assert_eq!(
+            result,
+            Err(BoundaryError::UnpairedPeriodic {
+                face: Face::XMin,
+                opposite: BoundaryCondition::Dirichlet(1.0),
+            })
+        );
+    }
+}
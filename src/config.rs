@@ -0,0 +1,232 @@
+#[cfg(feature = "serde")]
+use std::fs;
+#[cfg(feature = "serde")]
+use std::io::{Error, ErrorKind, Result};
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+use crate::boundary::{BoundaryCondition, BoundaryMode, Face};
+use crate::field::Weighting;
+use crate::solver::PotentialSolver;
+use crate::species::{Integrator, Species};
+use crate::state::SimulationState;
+
+/// Describes the mesh of a simulation.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeshConfig {
+    /// The corner of the mesh with the minimum coordinates.
+    pub origin: [f64; 3],
+    /// The corner of the mesh with the maximum coordinates.
+    pub max_bound: [f64; 3],
+    /// The number of nodes along each axis.
+    pub dimensions: [usize; 3],
+    /// The method used to solve the potential, which defaults to successive over-relaxation
+    /// for configurations written before it could be chosen.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub potential_solver: PotentialSolver,
+    /// The relaxation parameter of the potential solver.
+    pub relaxation_parameter: f64,
+    /// Whether the mean potential is pinned to zero after each solve.
+    pub pin_mean_potential: bool,
+    /// The number of binomial filter passes applied to the electric field.
+    pub electric_field_filter_passes: usize,
+    /// The uniform charge density added to that of the species.
+    pub background_charge_density: f64,
+    /// The fraction of normal kinetic energy retained by particles reflected at each face,
+    /// indexed by `Face::index`.
+    pub reflection_energy_coefficients: [f64; 6],
+    /// The treatment of particles reaching each face, indexed by `Face::index`.
+    pub particle_boundaries: [BoundaryMode; 6],
+    /// The boundary condition on the potential at each face, indexed by `Face::index`.
+    pub boundary_conditions: [BoundaryCondition; 6],
+}
+
+/// Describes a species of a simulation.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeciesConfig {
+    /// The name of the species.
+    pub name: String,
+    /// The mass of the particles, in kilograms.
+    pub mass: f64,
+    /// The charge of the particles, in coulombs.
+    pub charge: f64,
+    /// Whether the particles move.
+    pub mobile: bool,
+    /// The number of macroparticles.
+    pub num_macroparticles: usize,
+    /// The scheme for weighting particles onto the mesh.
+    pub weighting: Weighting,
+    /// The scheme for integrating the motion of the particles.
+    pub integrator: Integrator,
+}
+
+/// Describes the configuration a simulation was run with, including every value left at its
+/// default, so that a results directory records how it was produced.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimulationConfig {
+    /// The seed of the random number generator, if the run is reproducible.
+    pub seed: Option<u64>,
+    /// The timestep, in seconds.
+    pub timestep: f64,
+    /// The maximum number of iterations for each potential solve.
+    pub max_solver_iterations: usize,
+    /// The residual below which each potential solve is considered converged.
+    pub tolerance: f64,
+    /// The largest fractional change in total energy allowed over a step, if checked.
+    pub max_energy_drift: Option<f64>,
+    /// The mesh.
+    pub mesh: MeshConfig,
+    /// The species, in the order they are simulated.
+    pub species: Vec<SpeciesConfig>,
+}
+
+impl SimulationConfig {
+    /// Creates the configuration of a simulation state, resolved from its mesh and species,
+    /// along with the seed its particles were loaded with, if any.
+    pub fn from_state(state: &SimulationState, seed: Option<u64>) -> Self {
+        let mesh = state.mesh();
+        let dimensions = mesh.dimensions();
+        let (origin, max_bound) = (mesh.origin(), mesh.max_bound());
+
+        SimulationConfig {
+            seed,
+            timestep: mesh.timestep(),
+            max_solver_iterations: state.max_solver_iterations(),
+            tolerance: state.tolerance(),
+            max_energy_drift: state.max_energy_drift(),
+            mesh: MeshConfig {
+                origin: [origin.x, origin.y, origin.z],
+                max_bound: [max_bound.x, max_bound.y, max_bound.z],
+                dimensions: [dimensions.x, dimensions.y, dimensions.z],
+                potential_solver: mesh.potential_solver(),
+                relaxation_parameter: mesh.relaxation_parameter(),
+                pin_mean_potential: mesh.pin_mean_potential(),
+                electric_field_filter_passes: mesh.electric_field_filter_passes(),
+                background_charge_density: mesh.background_charge_density(),
+                reflection_energy_coefficients: Face::ALL
+                    .map(|face| mesh.reflection_energy_coefficient(face)),
+                particle_boundaries: Face::ALL.map(|face| mesh.particle_boundary(face)),
+                boundary_conditions: Face::ALL.map(|face| mesh.boundary_condition(face)),
+            },
+            species: state.species().iter().map(SpeciesConfig::from).collect(),
+        }
+    }
+
+    /// Writes the configuration to a TOML file, such as `run_config.toml` within the results
+    /// directory of a run.
+    #[cfg(feature = "serde")]
+    pub fn write_effective<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let contents =
+            toml::to_string(self).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+        fs::write(path, contents)
+    }
+
+    /// Reads a configuration from a TOML file written by `write_effective`.
+    #[cfg(feature = "serde")]
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        toml::from_str(&contents).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+}
+
+impl From<&Species> for SpeciesConfig {
+    fn from(species: &Species) -> Self {
+        SpeciesConfig {
+            name: species.name(),
+            mass: species.mass(),
+            charge: species.charge(),
+            mobile: species.is_mobile(),
+            num_macroparticles: species.particles().len(),
+            weighting: species.weighting(),
+            integrator: species.integrator(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    use crate::boundary::BoundaryConditions;
+    use crate::constants::{ELECTRON_MASS, ELEMENTARY_CHARGE};
+    use crate::mesh::{BoxMesh, Dimensions};
+    use crate::vector::Vec3;
+
+    #[test]
+    fn test_write_effective() {
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(11, 11, 11),
+            1e-10,
+        );
+        mesh.set_boundary_conditions(
+            BoundaryConditions::new()
+                .dirichlet(Face::XMin, 5.0)
+                .neumann(Face::ZMax)
+                .condition(
+                    Face::YMin,
+                    BoundaryCondition::Sinusoidal {
+                        amplitude: 2.0,
+                        frequency: 1e6,
+                        phase: 0.5,
+                    },
+                )
+                .build()
+                .unwrap(),
+        );
+        mesh.set_particle_boundary(Face::XMax, BoundaryMode::Absorbing);
+
+        let mut electrons = Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        electrons.set_integrator(Integrator::Midpoint);
+        electrons.load_particles_box_qs(
+            Vec3::new(0.02, 0.02, 0.02),
+            Vec3::new(0.08, 0.08, 0.08),
+            1e11,
+            (5, 5, 5),
+            &mesh,
+        );
+
+        let mut state = SimulationState::new(mesh, vec![electrons], 4000, 1e-3).unwrap();
+        for _ in 0..3 {
+            state.step().unwrap();
+        }
+
+        let config = SimulationConfig::from_state(&state, Some(7));
+        assert_eq!(config.mesh.dimensions, [11, 11, 11]);
+        assert_eq!(config.species[0].num_macroparticles, 125);
+
+        let path = std::env::temp_dir().join(format!("run_config_{}.toml", std::process::id()));
+        config.write_effective(&path).unwrap();
+        let read = SimulationConfig::read(&path).unwrap();
+
+        assert_eq!(read, config);
+
+        // Reading a configuration written before the potential solver could be chosen.
+        let written = std::fs::read_to_string(&path).unwrap();
+        let contents: String = written
+            .lines()
+            .filter(|line| !line.starts_with("potential_solver"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert!(contents.len() < written.len());
+        std::fs::write(&path, contents).unwrap();
+        let read = SimulationConfig::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            read.mesh.potential_solver,
+            PotentialSolver::SuccessiveOverRelaxation
+        );
+    }
+}
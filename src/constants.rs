@@ -9,3 +9,9 @@ pub const ELECTRON_MASS: f64 = 9.10938215e-31;
 
 /// The permittivity of free space or the dieletric permittivity of the vacuum.
 pub const PERMITTIVITY: f64 = 8.85418782e-12;
+
+/// The permeability of free space or the magnetic permeability of the vacuum.
+pub const PERMEABILITY: f64 = 1.25663706212e-6;
+
+/// The speed of light in a vacuum.
+pub const SPEED_OF_LIGHT: f64 = 299792458.0;
@@ -0,0 +1,156 @@
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+/// Represents an energy-dependent collision cross section.
+#[derive(Clone, Debug)]
+pub struct CrossSection {
+    /// The tabulated energies in electron volts, in increasing order.
+    energies: Vec<f64>,
+    /// The cross sections in square meters at the tabulated energies.
+    sigmas: Vec<f64>,
+}
+
+impl CrossSection {
+    /// Creates a cross section from energies, in electron volts, and cross sections, in
+    /// square meters.
+    pub fn new(energies: Vec<f64>, sigmas: Vec<f64>) -> Result<Self> {
+        if energies.is_empty() || energies.len() != sigmas.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Cross section table must contain one cross section per energy.",
+            ));
+        }
+
+        if energies
+            .iter()
+            .chain(&sigmas)
+            .any(|value| !value.is_finite())
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Cross section energies and cross sections must be finite.",
+            ));
+        }
+
+        if energies.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Cross section energies must be strictly increasing.",
+            ));
+        }
+
+        Ok(CrossSection { energies, sigmas })
+    }
+
+    /// Loads a cross section from a file with two columns: energy in electron volts and
+    /// cross section in square meters.
+    ///
+    /// Columns may be separated by whitespace or commas. Blank lines and lines starting with
+    /// `#` are ignored.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        let mut energies = Vec::new();
+        let mut sigmas = Vec::new();
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let values: Vec<f64> = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|token| !token.is_empty())
+                .map(|token| token.parse::<f64>())
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|error| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Line {}: {}", line_number + 1, error),
+                    )
+                })?;
+
+            if values.len() != 2 {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Line {}: expected two columns.", line_number + 1),
+                ));
+            }
+
+            energies.push(values[0]);
+            sigmas.push(values[1]);
+        }
+
+        CrossSection::new(energies, sigmas)
+    }
+
+    /// Returns the cross section at an energy in electron volts.
+    ///
+    /// Values are linearly interpolated between tabulated energies and clamped to the first
+    /// or last tabulated value outside of the table. A NaN energy has no cross section, so 0
+    /// is returned.
+    pub fn value(&self, energy: f64) -> f64 {
+        if energy.is_nan() {
+            return 0.0;
+        }
+
+        let last = self.energies.len() - 1;
+
+        if energy <= self.energies[0] {
+            return self.sigmas[0];
+        }
+
+        if energy >= self.energies[last] {
+            return self.sigmas[last];
+        }
+
+        // Finding the first tabulated energy above the provided energy.
+        let upper = self.energies.partition_point(|&e| e <= energy);
+        let lower = upper - 1;
+
+        let fraction =
+            (energy - self.energies[lower]) / (self.energies[upper] - self.energies[lower]);
+
+        self.sigmas[lower] + fraction * (self.sigmas[upper] - self.sigmas[lower])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_file() {
+        let path = std::env::temp_dir().join(format!("cross_section_{}.dat", std::process::id()));
+        fs::write(
+            &path,
+            "# energy (eV), sigma (m^2)\n1.0, 1e-20\n10.0 2e-20\n\n100.0,4e-20\n",
+        )
+        .unwrap();
+
+        let cross_section = CrossSection::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // Interpolating between tabulated energies.
+        assert!((cross_section.value(5.5) - 1.5e-20).abs() < 1e-32);
+        assert!((cross_section.value(55.0) - 3e-20).abs() < 1e-32);
+        assert_eq!(cross_section.value(10.0), 2e-20);
+
+        // Clamping outside of the tabulated energies.
+        assert_eq!(cross_section.value(0.1), 1e-20);
+        assert_eq!(cross_section.value(1000.0), 4e-20);
+        assert_eq!(cross_section.value(f64::INFINITY), 4e-20);
+        assert_eq!(cross_section.value(f64::NAN), 0.0);
+    }
+
+    #[test]
+    fn test_invalid_table() {
+        assert!(CrossSection::new(vec![2.0, 1.0], vec![1e-20, 2e-20]).is_err());
+        assert!(CrossSection::new(vec![1.0], vec![]).is_err());
+        assert!(CrossSection::new(vec![1.0, f64::NAN], vec![1e-20, 2e-20]).is_err());
+        assert!(CrossSection::new(vec![1.0, f64::INFINITY], vec![1e-20, 2e-20]).is_err());
+    }
+}
@@ -0,0 +1,483 @@
+use std::f64::consts::PI;
+
+use crate::boundary::BoundaryCondition;
+use crate::constants::PERMITTIVITY;
+use crate::field::Field;
+use crate::mesh::Dimensions;
+use crate::solver::{self, SolverError, SolverReport};
+use crate::vector::Vec3;
+
+/// Identifies a face of a cylindrical mesh on which a boundary condition is imposed.
+///
+/// The axis of the cylinder isn't a face, since the potential there follows from symmetry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CylindricalFace {
+    /// The face at the minimum axial coordinate.
+    ZMin,
+    /// The face at the maximum axial coordinate.
+    ZMax,
+    /// The curved face at the outer radius.
+    Outer,
+}
+
+impl CylindricalFace {
+    /// Lists every face of a cylindrical mesh.
+    pub const ALL: [CylindricalFace; 3] = [
+        CylindricalFace::ZMin,
+        CylindricalFace::ZMax,
+        CylindricalFace::Outer,
+    ];
+
+    /// Returns the index of the face for per-face storage.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Represents an axisymmetric mesh in radial and axial (r-z) coordinates.
+///
+/// Fields are stored with the radial index along the first axis and the axial index along
+/// the second, with a single node along the third. Vector fields hold their radial component
+/// in `x` and their axial component in `y`.
+pub struct CylindricalMesh {
+    /// The outer radius of the mesh.
+    radius: f64,
+    /// The minimum axial coordinate of the mesh.
+    z_min: f64,
+    /// The maximum axial coordinate of the mesh.
+    z_max: f64,
+    /// The number of nodes along the radial and axial directions.
+    dimensions: Dimensions,
+    /// The radial and axial spacing between nodes.
+    cell_spacings: [f64; 2],
+    /// The volume of the annulus surrounding each node.
+    node_volumes: Field<f64>,
+    /// The change in time for a mesh iteration.
+    timestep: f64,
+    /// The current simulation time.
+    time: f64,
+    /// The electric potential on the mesh.
+    potential: Field<f64>,
+    /// The charge density on the mesh.
+    charge_density: Field<f64>,
+    /// The radial and axial electric field on the mesh.
+    electric_field: Field<Vec3>,
+    /// The boundary condition on the potential at each face, indexed by face.
+    boundary_conditions: [BoundaryCondition; 3],
+    /// The relaxation parameter used by the SOR potential solver.
+    relaxation_parameter: f64,
+}
+
+impl CylindricalMesh {
+    /// Creates a cylindrical mesh extending from the axis to the provided radius and between
+    /// the provided axial coordinates.
+    ///
+    /// At least 2 radial and 3 axial nodes are required for the solver to have nodes to
+    /// update, which is checked.
+    pub fn new(
+        radius: f64,
+        z_min: f64,
+        z_max: f64,
+        num_radial_nodes: usize,
+        num_axial_nodes: usize,
+        timestep: f64,
+    ) -> Self {
+        assert!(
+            num_radial_nodes >= 2 && num_axial_nodes >= 3,
+            "Cylindrical meshes need at least 2 radial and 3 axial nodes, but got {} x {}.",
+            num_radial_nodes,
+            num_axial_nodes
+        );
+
+        let dimensions = Dimensions::new(num_radial_nodes, num_axial_nodes, 1);
+
+        let mut mesh = CylindricalMesh {
+            radius,
+            z_min,
+            z_max,
+            dimensions,
+            cell_spacings: [
+                radius / (num_radial_nodes - 1) as f64,
+                (z_max - z_min) / (num_axial_nodes - 1) as f64,
+            ],
+            node_volumes: Field::<f64>::new(dimensions),
+            timestep,
+            time: 0.0,
+            potential: Field::<f64>::new(dimensions),
+            charge_density: Field::<f64>::new(dimensions),
+            electric_field: Field::<Vec3>::new(dimensions),
+            boundary_conditions: [BoundaryCondition::default(); 3],
+            relaxation_parameter: 1.4,
+        };
+
+        mesh.compute_node_volumes();
+
+        mesh
+    }
+
+    /// Returns the outer radius of the mesh.
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Returns the minimum and maximum axial coordinates of the mesh.
+    pub fn axial_bounds(&self) -> (f64, f64) {
+        (self.z_min, self.z_max)
+    }
+
+    /// Returns the number of nodes along the radial and axial directions.
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// Returns the radial and axial spacing between nodes.
+    pub fn cell_spacings(&self) -> [f64; 2] {
+        self.cell_spacings
+    }
+
+    /// Returns the volume of the annulus surrounding each node.
+    pub fn node_volumes(&self) -> &Field<f64> {
+        &self.node_volumes
+    }
+
+    /// Returns the change in time for a mesh iteration.
+    pub fn timestep(&self) -> f64 {
+        self.timestep
+    }
+
+    /// Returns the current simulation time.
+    pub fn current_time(&self) -> f64 {
+        self.time
+    }
+
+    /// Advances the simulation time by a timestep.
+    pub fn advance_time(&mut self) {
+        self.time += self.timestep;
+    }
+
+    /// Returns the electric potential on the mesh.
+    pub fn potential(&self) -> &Field<f64> {
+        &self.potential
+    }
+
+    /// Returns the charge density on the mesh.
+    pub fn charge_density(&self) -> &Field<f64> {
+        &self.charge_density
+    }
+
+    /// Returns the charge density on the mesh for modification.
+    pub fn charge_density_mut(&mut self) -> &mut Field<f64> {
+        &mut self.charge_density
+    }
+
+    /// Returns the radial (`x`) and axial (`y`) electric field on the mesh.
+    pub fn electric_field(&self) -> &Field<Vec3> {
+        &self.electric_field
+    }
+
+    /// Returns the boundary condition on the potential at a face of the mesh.
+    pub fn boundary_condition(&self, face: CylindricalFace) -> BoundaryCondition {
+        self.boundary_conditions[face.index()]
+    }
+
+    /// Sets the boundary condition on the potential at a face of the mesh.
+    pub fn set_boundary_condition(
+        &mut self,
+        face: CylindricalFace,
+        boundary_condition: BoundaryCondition,
+    ) {
+        self.boundary_conditions[face.index()] = boundary_condition;
+    }
+
+    /// Returns the relaxation parameter used by the SOR potential solver.
+    pub fn relaxation_parameter(&self) -> f64 {
+        self.relaxation_parameter
+    }
+
+    /// Sets the relaxation parameter used by the SOR potential solver, which defaults to 1.4.
+    ///
+    /// Panics for parameters outside of the interval (0, 2), in which SOR converges.
+    pub fn set_relaxation_parameter(&mut self, relaxation_parameter: f64) {
+        solver::validate_relaxation_parameter(relaxation_parameter)
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        self.relaxation_parameter = relaxation_parameter;
+    }
+
+    /// Converts a radial and axial position into a logical coordinate on the mesh.
+    pub fn position_to_logical_coordinate(&self, r: f64, z: f64) -> Vec3 {
+        Vec3::new(
+            r / self.cell_spacings[0],
+            (z - self.z_min) / self.cell_spacings[1],
+            0.0,
+        )
+    }
+
+    /// Computes the volume of the annulus surrounding each node, which is halved at the axial
+    /// faces and truncated at the axis and outer radius.
+    fn compute_node_volumes(&mut self) {
+        let [dr, dz] = self.cell_spacings;
+
+        for i in 0..self.dimensions.x {
+            let r = i as f64 * dr;
+            let inner = (r - 0.5 * dr).max(0.0);
+            let outer = (r + 0.5 * dr).min(self.radius);
+            let annulus_area = PI * (outer * outer - inner * inner);
+
+            for j in 0..self.dimensions.y {
+                let mut volume = annulus_area * dz;
+
+                if j == 0 || j == self.dimensions.y - 1 {
+                    volume *= 0.5;
+                }
+
+                self.node_volumes[[i, j, 0]] = volume;
+            }
+        }
+    }
+
+    /// Solves the potential field using the axisymmetric Poisson equation.
+    ///
+    /// When provided, `residual_history` is extended with the iteration and residual of each
+    /// convergence check. The report also holds the largest change of the potential over the
+    /// final sweep.
+    pub fn solve_potential(
+        &mut self,
+        max_solver_iterations: usize,
+        tolerance: f64,
+        residual_history: Option<&mut Vec<(usize, f64)>>,
+    ) -> Result<SolverReport, SolverError> {
+        solver::validate_relaxation_parameter(self.relaxation_parameter)?;
+
+        self.apply_potential_boundaries();
+
+        solver::solve_sor(
+            self,
+            max_solver_iterations,
+            tolerance,
+            residual_history,
+            |mesh| {
+                let max_potential_change = mesh.sweep_potential();
+                mesh.apply_potential_boundaries();
+
+                max_potential_change
+            },
+            CylindricalMesh::potential_residue,
+        )
+    }
+
+    /// Returns the coefficients of the discretized Poisson equation at a radial index, as the
+    /// weights of the inner and outer radial neighbors and of each axial neighbor.
+    ///
+    /// On the axis, the radial term `(1/r) ∂/∂r (r ∂φ/∂r)` becomes `2 ∂²φ/∂r²` by symmetry,
+    /// with the ghost node inside the axis mirroring the outer neighbor.
+    fn stencil(&self, i: usize) -> (f64, f64, f64) {
+        let dr2 = 1.0 / (self.cell_spacings[0] * self.cell_spacings[0]);
+        let dz2 = 1.0 / (self.cell_spacings[1] * self.cell_spacings[1]);
+
+        if i == 0 {
+            return (0.0, 4.0 * dr2, dz2);
+        }
+
+        let i = i as f64;
+
+        ((i - 0.5) / i * dr2, (i + 0.5) / i * dr2, dz2)
+    }
+
+    /// Applies a single Gauss-Seidel sweep with successive over-relaxation to the potential
+    /// on the axis and interior nodes, returning the largest absolute change of any node.
+    fn sweep_potential(&mut self) -> f64 {
+        let mut max_change: f64 = 0.0;
+
+        for i in 0..self.dimensions.x - 1 {
+            let (inner, outer, axial) = self.stencil(i);
+            let denominator = inner + outer + 2.0 * axial;
+
+            for j in 1..self.dimensions.y - 1 {
+                let phi = &self.potential;
+                let inner_phi = if i == 0 { 0.0 } else { phi[[i - 1, j, 0]] };
+
+                let new_phi = (self.charge_density[[i, j, 0]] / PERMITTIVITY
+                    + inner * inner_phi
+                    + outer * phi[[i + 1, j, 0]]
+                    + axial * (phi[[i, j - 1, 0]] + phi[[i, j + 1, 0]]))
+                    / denominator;
+
+                let current_phi = phi[[i, j, 0]];
+
+                // Successive over-relaxation.
+                let change = self.relaxation_parameter * (new_phi - current_phi);
+                self.potential[[i, j, 0]] = current_phi + change;
+                max_change = max_change.max(change.abs());
+            }
+        }
+
+        max_change
+    }
+
+    /// Applies the boundary conditions to the potential on the faces of the mesh.
+    fn apply_potential_boundaries(&mut self) {
+        let (num_radial_nodes, num_axial_nodes) = (self.dimensions.x, self.dimensions.y);
+
+        for face in CylindricalFace::ALL {
+            let boundary_condition = self.boundary_conditions[face.index()];
+
+            // Listing each boundary node along with its neighbor inside the mesh.
+            let nodes: Vec<([usize; 3], [usize; 3])> = match face {
+                CylindricalFace::ZMin => (0..num_radial_nodes)
+                    .map(|i| ([i, 0, 0], [i, 1, 0]))
+                    .collect(),
+                CylindricalFace::ZMax => (0..num_radial_nodes)
+                    .map(|i| ([i, num_axial_nodes - 1, 0], [i, num_axial_nodes - 2, 0]))
+                    .collect(),
+                CylindricalFace::Outer => (0..num_axial_nodes)
+                    .map(|j| ([num_radial_nodes - 1, j, 0], [num_radial_nodes - 2, j, 0]))
+                    .collect(),
+            };
+
+            for (index, neighbor_index) in nodes {
+                self.potential[index] = match boundary_condition.potential(self.time) {
+                    Some(value) => value,
+                    None => self.potential[neighbor_index],
+                };
+            }
+        }
+    }
+
+    /// Computes the L2 norm of the residue of the discretized Poisson equation.
+    fn potential_residue(&self) -> f64 {
+        let phi = &self.potential;
+        let mut sum = 0.0;
+
+        for i in 0..self.dimensions.x - 1 {
+            let (inner, outer, axial) = self.stencil(i);
+            let denominator = inner + outer + 2.0 * axial;
+
+            for j in 1..self.dimensions.y - 1 {
+                let inner_phi = if i == 0 { 0.0 } else { phi[[i - 1, j, 0]] };
+
+                let r = -phi[[i, j, 0]] * denominator
+                    + self.charge_density[[i, j, 0]] / PERMITTIVITY
+                    + inner * inner_phi
+                    + outer * phi[[i + 1, j, 0]]
+                    + axial * (phi[[i, j - 1, 0]] + phi[[i, j + 1, 0]]);
+                sum += r * r;
+            }
+        }
+
+        (sum / (self.dimensions.x * self.dimensions.y) as f64).sqrt()
+    }
+
+    /// Computes the radial and axial electric field.
+    pub fn compute_electric_field(&mut self) {
+        let [dr, dz] = self.cell_spacings;
+        let (num_radial_nodes, num_axial_nodes) = (self.dimensions.x, self.dimensions.y);
+        let phi = &self.potential;
+
+        for i in 0..num_radial_nodes {
+            for j in 0..num_axial_nodes {
+                let ef = &mut self.electric_field[[i, j, 0]];
+
+                // Computing the radial component, which vanishes on the axis by symmetry.
+                if i == 0 {
+                    ef.x = 0.0;
+                } else if i == num_radial_nodes - 1 {
+                    ef.x = -(phi[[i - 2, j, 0]] - 4.0 * phi[[i - 1, j, 0]] + 3.0 * phi[[i, j, 0]])
+                        / (2.0 * dr);
+                } else {
+                    ef.x = -(phi[[i + 1, j, 0]] - phi[[i - 1, j, 0]]) / (2.0 * dr);
+                }
+
+                // Computing the axial component.
+                if j == 0 {
+                    ef.y = -(-3.0 * phi[[i, j, 0]] + 4.0 * phi[[i, j + 1, 0]] - phi[[i, j + 2, 0]])
+                        / (2.0 * dz);
+                } else if j == num_axial_nodes - 1 {
+                    ef.y = -(phi[[i, j - 2, 0]] - 4.0 * phi[[i, j - 1, 0]] + 3.0 * phi[[i, j, 0]])
+                        / (2.0 * dz);
+                } else {
+                    ef.y = -(phi[[i, j + 1, 0]] - phi[[i, j - 1, 0]]) / (2.0 * dz);
+                }
+
+                ef.z = 0.0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_volumes() {
+        let mesh = CylindricalMesh::new(0.1, -0.05, 0.15, 11, 9, 1e-10);
+
+        // The node volumes should sum to the volume of the cylinder.
+        let expected = PI * 0.1 * 0.1 * 0.2;
+        assert!((mesh.node_volumes().sum() / expected - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cylindrical meshes need at least 2 radial and 3 axial nodes")]
+    fn test_new_single_radial_node() {
+        CylindricalMesh::new(0.1, -0.05, 0.15, 1, 9, 1e-10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cylindrical meshes need at least 2 radial and 3 axial nodes")]
+    fn test_new_two_axial_nodes() {
+        CylindricalMesh::new(0.1, -0.05, 0.15, 11, 2, 1e-10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Relaxation parameter 0 is outside of the interval (0, 2)")]
+    fn test_set_relaxation_parameter_out_of_range() {
+        let mut mesh = CylindricalMesh::new(0.1, -0.05, 0.15, 11, 9, 1e-10);
+        mesh.set_relaxation_parameter(0.0);
+    }
+
+    #[test]
+    fn test_uniformly_charged_cylinder() {
+        let radius = 0.1;
+        let charged_radius = 0.05;
+        let rho = 1e-9;
+
+        // Modelling an infinitely long cylinder with zero axial gradient at the ends.
+        let mut mesh = CylindricalMesh::new(radius, 0.0, 0.05, 41, 6, 1e-10);
+        mesh.set_boundary_condition(CylindricalFace::ZMin, BoundaryCondition::Neumann);
+        mesh.set_boundary_condition(CylindricalFace::ZMax, BoundaryCondition::Neumann);
+        mesh.set_relaxation_parameter(1.8);
+
+        let dr = mesh.cell_spacings()[0];
+        for i in 0..mesh.dimensions().x {
+            if i as f64 * dr < charged_radius - 1e-12 {
+                for j in 0..mesh.dimensions().y {
+                    mesh.charge_density_mut()[[i, j, 0]] = rho;
+                }
+            }
+        }
+
+        mesh.solve_potential(20000, 1e-6, None).unwrap();
+
+        // Solving Poisson's equation in cylindrical coordinates, with the charged region
+        // extending half way to the nodes at its edge.
+        let a = charged_radius - 0.5 * dr;
+        let analytic = |r: f64| {
+            if r < a {
+                rho / (4.0 * PERMITTIVITY) * (a * a - r * r)
+                    + rho * a * a / (2.0 * PERMITTIVITY) * (radius / a).ln()
+            } else {
+                rho * a * a / (2.0 * PERMITTIVITY) * (radius / r).ln()
+            }
+        };
+
+        let peak = analytic(0.0);
+        for i in 0..mesh.dimensions().x {
+            let r = i as f64 * dr;
+            let error = (mesh.potential()[[i, 2, 0]] - analytic(r)).abs();
+
+            assert!(error < 0.01 * peak, "Error {} at r = {}.", error, r);
+        }
+    }
+}
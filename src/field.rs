@@ -1,5 +1,5 @@
 use std::fmt;
-use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, Sub};
+use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, Sub, SubAssign};
 
 use ndarray::{Array3, ScalarOperand};
 use num_traits::identities::Zero;
@@ -7,6 +7,19 @@ use num_traits::identities::Zero;
 use crate::mesh::Dimensions;
 use crate::vector::Vec3;
 
+/// Represents a scheme for weighting values at points onto the nodes of a mesh.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weighting {
+    /// Assigns the whole value to the nearest node.
+    NearestGridPoint,
+    /// Distributes the value linearly over the nodes of the enclosing cell.
+    #[default]
+    CloudInCell,
+    /// Distributes the value quadratically over the three nearest nodes along each axis.
+    TriangularShapedCloud,
+}
+
 /// Represents a field.
 #[derive(Clone, Debug)]
 pub struct Field<T: Copy + Clone + Zero + Mul<f64> + AddAssign<<T as Mul<f64>>::Output>> {
@@ -19,7 +32,18 @@ impl<
     > Field<T>
 {
     /// Creates a new field with the provided dimensions.
+    ///
+    /// Panics if any dimension is zero. Note that meshes on which potential is solved need at
+    /// least three nodes along each axis.
     pub fn new(dimensions: Dimensions) -> Self {
+        assert!(
+            dimensions.x >= 1 && dimensions.y >= 1 && dimensions.z >= 1,
+            "Field dimensions must be at least 1 along every axis, but got {} x {} x {}.",
+            dimensions.x,
+            dimensions.y,
+            dimensions.z
+        );
+
         let shape: (usize, usize, usize) = dimensions.into();
         let data = Array3::<T>::zeros(shape);
 
@@ -29,6 +53,21 @@ impl<
         }
     }
 
+    /// Returns the number of nodes along each axis of the field.
+    pub fn shape(&self) -> (usize, usize, usize) {
+        self.shape
+    }
+
+    /// Returns the value at a node, or `None` if the indices lie outside of the field.
+    pub fn get(&self, i: usize, j: usize, k: usize) -> Option<&T> {
+        self.data.get([i, j, k])
+    }
+
+    /// Returns the value at a node mutably, or `None` if the indices lie outside of the field.
+    pub fn get_mut(&mut self, i: usize, j: usize, k: usize) -> Option<&mut T> {
+        self.data.get_mut([i, j, k])
+    }
+
     /// Clears the field to its zero value.
     pub fn clear(&mut self) {
         for i in 0..self.shape.0 {
@@ -40,6 +79,40 @@ impl<
         }
     }
 
+    /// Smooths the field by applying the provided number of passes of a 1-2-1 binomial
+    /// filter along each axis.
+    ///
+    /// Each node keeps half of its value and passes a quarter to each neighbor, except that
+    /// nodes on the edges of the field keep the quarter meant for a missing neighbor, so that
+    /// the sum over the nodes is preserved.
+    pub fn smooth_binomial(&mut self, passes: usize) {
+        let shape = [self.shape.0, self.shape.1, self.shape.2];
+
+        for _ in 0..passes {
+            for axis in 0..3 {
+                let source = self.data.clone();
+
+                for i in 0..shape[0] {
+                    for j in 0..shape[1] {
+                        for k in 0..shape[2] {
+                            let index = [i, j, k];
+
+                            let mut lower = index;
+                            lower[axis] = index[axis].saturating_sub(1);
+                            let mut upper = index;
+                            upper[axis] = (index[axis] + 1).min(shape[axis] - 1);
+
+                            let mut value = source[index] * 0.5;
+                            value += source[lower] * 0.25;
+                            value += source[upper] * 0.25;
+                            self.data[index] = value;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     /// Interpolates field values at points between mesh nodes.
     pub fn gather(&self, logical_coordinate: Vec3) -> T {
         let lc = logical_coordinate;
@@ -54,9 +127,33 @@ impl<
             return T::zero();
         }
 
-        let i = lc.x as usize;
-        let j = lc.y as usize;
-        let k = lc.z as usize;
+        self.interpolate(lc)
+    }
+
+    /// Interpolates field values at points between mesh nodes like `gather`, but also at points
+    /// on the faces furthest from the origin, which `gather` treats as outside of the mesh.
+    pub fn gather_closed(&self, logical_coordinate: Vec3) -> T {
+        let lc = logical_coordinate;
+
+        if lc.x < 0.0
+            || lc.x > (self.shape.0 - 1) as f64
+            || lc.y < 0.0
+            || lc.y > (self.shape.1 - 1) as f64
+            || lc.z < 0.0
+            || lc.z > (self.shape.2 - 1) as f64
+        {
+            return T::zero();
+        }
+
+        self.interpolate(lc)
+    }
+
+    /// Trilinearly interpolates the field values of the cell containing a point within the
+    /// mesh, taking points on the faces furthest from the origin to lie in the last cell.
+    fn interpolate(&self, lc: Vec3) -> T {
+        let i = (lc.x as usize).min(self.shape.0 - 2);
+        let j = (lc.y as usize).min(self.shape.1 - 2);
+        let k = (lc.z as usize).min(self.shape.2 - 2);
 
         let di = lc.x - i as f64;
         let dj = lc.y - j as f64;
@@ -74,6 +171,66 @@ impl<
         value
     }
 
+    /// Interpolates field values at points between mesh nodes, returning `None` when the
+    /// point lies outside of the mesh.
+    pub fn try_gather(&self, logical_coordinate: Vec3) -> Option<T> {
+        let lc = logical_coordinate;
+
+        if lc.x < 0.0
+            || lc.x >= (self.shape.0 - 1) as f64
+            || lc.y < 0.0
+            || lc.y >= (self.shape.1 - 1) as f64
+            || lc.z < 0.0
+            || lc.z >= (self.shape.2 - 1) as f64
+        {
+            return None;
+        }
+
+        Some(self.gather(lc))
+    }
+
+    /// Disperses a value at a point to those surrounding mesh nodes which lie within the
+    /// mesh, returning the fraction of the value that was deposited.
+    pub fn scatter_partial(&mut self, logical_coordinate: Vec3, value: T) -> f64 {
+        self.scatter_weighted(logical_coordinate, value, Weighting::CloudInCell)
+    }
+
+    /// Disperses a value at a point to nearby mesh nodes using the provided weighting
+    /// scheme, returning the fraction of the value that was deposited.
+    ///
+    /// Nodes outside of the mesh are skipped, except that triangular-shaped cloud weights
+    /// beyond a boundary node are folded back onto it, so that points within the mesh are
+    /// always fully deposited.
+    pub fn scatter_weighted(
+        &mut self,
+        logical_coordinate: Vec3,
+        value: T,
+        weighting: Weighting,
+    ) -> f64 {
+        let lc = logical_coordinate;
+
+        let weights_i = axis_weights(lc.x, self.shape.0, weighting);
+        let weights_j = axis_weights(lc.y, self.shape.1, weighting);
+        let weights_k = axis_weights(lc.z, self.shape.2, weighting);
+
+        let mut deposited_fraction = 0.0;
+
+        for &(i, weight_i) in &weights_i {
+            for &(j, weight_j) in &weights_j {
+                for &(k, weight_k) in &weights_k {
+                    let weight = weight_i * weight_j * weight_k;
+
+                    if weight > 0.0 {
+                        self.data[[i, j, k]] += value * weight;
+                        deposited_fraction += weight;
+                    }
+                }
+            }
+        }
+
+        deposited_fraction
+    }
+
     /// Disperses a value at a point to the surrounding mesh nodes.
     pub fn scatter(&mut self, logical_coordinate: Vec3, value: T) {
         let lc = logical_coordinate;
@@ -107,6 +264,130 @@ impl<
     }
 }
 
+/// Computes the nodes along one axis receiving a share of a value at a logical coordinate,
+/// along with their weights. Unused entries have zero weight.
+fn axis_weights(x: f64, num_nodes: usize, weighting: Weighting) -> [(usize, f64); 3] {
+    let mut weights = [(0, 0.0); 3];
+    let last = num_nodes as f64 - 1.0;
+    let in_mesh = |node: f64| node >= 0.0 && node <= last;
+
+    match weighting {
+        Weighting::NearestGridPoint => {
+            let node = x.round();
+
+            if in_mesh(node) {
+                weights[0] = (node as usize, 1.0);
+            }
+        }
+        Weighting::CloudInCell => {
+            let base = x.floor();
+            let offset = x - base;
+
+            if in_mesh(base) {
+                weights[0] = (base as usize, 1.0 - offset);
+            }
+
+            if in_mesh(base + 1.0) {
+                weights[1] = ((base + 1.0) as usize, offset);
+            }
+        }
+        Weighting::TriangularShapedCloud => {
+            let center = x.round();
+
+            if !in_mesh(center) {
+                return weights;
+            }
+
+            let offset = x - center;
+            let mut lower = 0.5 * (0.5 - offset) * (0.5 - offset);
+            let mut upper = 0.5 * (0.5 + offset) * (0.5 + offset);
+            let mut middle = 0.75 - offset * offset;
+
+            // Folding weights beyond the mesh back onto the boundary node.
+            if center == 0.0 {
+                middle += lower;
+                lower = 0.0;
+            }
+
+            if center == last {
+                middle += upper;
+                upper = 0.0;
+            }
+
+            let center = center as usize;
+            weights[1] = (center, middle);
+
+            if lower > 0.0 {
+                weights[0] = (center - 1, lower);
+            }
+
+            if upper > 0.0 {
+                weights[2] = (center + 1, upper);
+            }
+        }
+    }
+
+    weights
+}
+
+impl Field<f64> {
+    /// Returns the sum of the values at every node.
+    pub fn sum(&self) -> f64 {
+        self.data.sum()
+    }
+
+    /// Returns the average of the values at every node.
+    pub fn mean(&self) -> f64 {
+        self.sum() / self.data.len() as f64
+    }
+
+    /// Returns the maximum value of the field and the indices of the node where it occurs.
+    pub fn max_with_index(&self) -> (f64, (usize, usize, usize)) {
+        let mut max_value = f64::NEG_INFINITY;
+        let mut max_index = (0, 0, 0);
+
+        for ((i, j, k), &value) in self.data.indexed_iter() {
+            if value > max_value {
+                max_value = value;
+                max_index = (i, j, k);
+            }
+        }
+
+        (max_value, max_index)
+    }
+
+    /// Returns whether any value of the field is NaN or infinite.
+    pub fn has_non_finite(&self) -> bool {
+        self.data.iter().any(|value| !value.is_finite())
+    }
+
+    /// Returns the root mean square of the differences from another field of the same shape.
+    pub fn l2_difference(&self, other: &Field<f64>) -> f64 {
+        assert_eq!(
+            self.shape, other.shape,
+            "Cannot compare fields of different shapes."
+        );
+
+        let sum_squares: f64 = self
+            .data
+            .iter()
+            .zip(other.data.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum();
+
+        (sum_squares / self.data.len() as f64).sqrt()
+    }
+}
+
+impl Field<Vec3> {
+    /// Returns whether any component of any value of the field is NaN or infinite.
+    pub fn has_non_finite(&self) -> bool {
+        self.data
+            .iter()
+            .any(|value| !(value.x.is_finite() && value.y.is_finite() && value.z.is_finite()))
+    }
+}
+
 impl<T: Copy + Clone + Zero + Mul<f64> + AddAssign<<T as Mul<f64>>::Output> + fmt::Display>
     fmt::Display for Field<T>
 {
@@ -134,6 +415,19 @@ impl<T: Copy + Clone + Zero + Mul<f64> + AddAssign<<T as Mul<f64>>::Output>> Add
     }
 }
 
+impl<T: Copy + Clone + Zero + Mul<f64> + SubAssign + AddAssign<<T as Mul<f64>>::Output>> SubAssign
+    for Field<T>
+{
+    fn sub_assign(&mut self, other: Self) {
+        assert_eq!(
+            self.shape, other.shape,
+            "Cannot subtract fields of different shapes."
+        );
+
+        self.data -= &other.data;
+    }
+}
+
 impl<
         T: Copy + Clone + Zero + Mul<f64> + Div + Div<Output = T> + AddAssign<<T as Mul<f64>>::Output>,
     > Div for Field<T>
@@ -243,3 +537,222 @@ impl<
         }
     }
 }
+
+/// Represents a flag at each node of a mesh, such as the nodes occupied by a solid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mask {
+    data: Array3<bool>,
+    shape: (usize, usize, usize),
+}
+
+impl Mask {
+    /// Creates a new mask with the provided dimensions, with no node flagged.
+    pub fn new(dimensions: Dimensions) -> Self {
+        let shape: (usize, usize, usize) = dimensions.into();
+
+        Mask {
+            data: Array3::from_elem(shape, false),
+            shape: shape,
+        }
+    }
+
+    /// Returns the number of nodes along each axis of the mask.
+    pub fn shape(&self) -> (usize, usize, usize) {
+        self.shape
+    }
+
+    /// Returns the number of flagged nodes.
+    pub fn count(&self) -> usize {
+        self.data.iter().filter(|&&flagged| flagged).count()
+    }
+}
+
+impl Index<[usize; 3]> for Mask {
+    type Output = bool;
+
+    fn index(&self, indices: [usize; 3]) -> &Self::Output {
+        &self.data[indices]
+    }
+}
+
+impl IndexMut<[usize; 3]> for Mask {
+    fn index_mut(&mut self, indices: [usize; 3]) -> &mut Self::Output {
+        &mut self.data[indices]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Field dimensions must be at least 1 along every axis")]
+    fn test_zero_dimension() {
+        Field::<f64>::new(Dimensions::new(4, 0, 4));
+    }
+
+    #[test]
+    fn test_get() {
+        let mut field = Field::<f64>::new(Dimensions::new(2, 3, 4));
+        field[[1, 2, 3]] = 5.0;
+
+        assert_eq!(field.get(1, 2, 3), Some(&5.0));
+        assert_eq!(field.get(2, 0, 0), None);
+        assert_eq!(field.get(0, 3, 0), None);
+        assert_eq!(field.get(0, 0, 4), None);
+
+        *field.get_mut(0, 1, 2).unwrap() = 7.0;
+        assert_eq!(field[[0, 1, 2]], 7.0);
+        assert!(field.get_mut(2, 0, 0).is_none());
+        assert!(field.get_mut(0, 3, 0).is_none());
+        assert!(field.get_mut(0, 0, 4).is_none());
+    }
+
+    #[test]
+    fn test_sub_assign() {
+        let mut field = Field::<f64>::new(Dimensions::new(3, 4, 5)) + 2.5;
+        field[[1, 2, 3]] = -7.0;
+
+        field -= field.clone();
+        assert!(field.data.iter().all(|&value| value == 0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sub_assign_shape_mismatch() {
+        let mut field = Field::<f64>::new(Dimensions::new(3, 4, 5));
+        field -= Field::<f64>::new(Dimensions::new(3, 4, 1));
+    }
+
+    #[test]
+    fn test_l2_difference() {
+        let mut field = Field::<f64>::new(Dimensions::new(3, 4, 5)) + 1.5;
+        field[[1, 2, 3]] = -7.0;
+
+        assert_eq!(field.l2_difference(&field), 0.0);
+        assert_eq!(field.l2_difference(&(field.clone() + 0.25)), 0.25);
+    }
+
+    #[test]
+    fn test_has_non_finite() {
+        let mut scalars = Field::<f64>::new(Dimensions::new(3, 3, 3));
+        assert!(!scalars.has_non_finite());
+        scalars[[1, 2, 0]] = f64::NEG_INFINITY;
+        assert!(scalars.has_non_finite());
+
+        let mut vectors = Field::<Vec3>::new(Dimensions::new(3, 3, 3));
+        assert!(!vectors.has_non_finite());
+        vectors[[2, 0, 1]].z = f64::NAN;
+        assert!(vectors.has_non_finite());
+    }
+
+    #[test]
+    fn test_gather() {
+        let dimensions = Dimensions::new(4, 5, 6);
+        let mut scalars = Field::<f64>::new(dimensions);
+        let mut vectors = Field::<Vec3>::new(dimensions);
+
+        // Trilinear interpolation reproduces a trilinear function exactly.
+        let function = |x: f64, y: f64, z: f64| 1.0 + 2.0 * x - 3.0 * y + 0.5 * z + x * y * z;
+        for i in 0..4 {
+            for j in 0..5 {
+                for k in 0..6 {
+                    let (x, y, z) = (i as f64, j as f64, k as f64);
+                    scalars[[i, j, k]] = function(x, y, z);
+                    vectors[[i, j, k]] = Vec3::new(function(x, y, z), -x, y * z);
+                }
+            }
+        }
+
+        let lc = Vec3::new(1.25, 2.5, 4.75);
+        let expected = function(lc.x, lc.y, lc.z);
+        assert!((scalars.gather(lc) - expected).abs() < 1e-12);
+
+        let vector = vectors.gather(lc);
+        assert!((vector.x - expected).abs() < 1e-12);
+        assert!((vector.y + lc.x).abs() < 1e-12);
+        assert!((vector.z - lc.y * lc.z).abs() < 1e-12);
+
+        // Points outside of the mesh gather nothing.
+        assert_eq!(scalars.gather(Vec3::new(-0.1, 2.0, 3.0)), 0.0);
+        assert_eq!(scalars.gather(Vec3::new(1.0, 2.0, 5.0)), 0.0);
+        assert_eq!(vectors.gather(Vec3::new(3.0, 2.0, 3.0)).x, 0.0);
+    }
+
+    #[test]
+    fn test_try_gather() {
+        let mut field = Field::<f64>::new(Dimensions::new(5, 5, 5));
+        field[[1, 2, 3]] = 4.0;
+
+        assert_eq!(field.try_gather(Vec3::new(1.0, 2.0, 3.0)), Some(4.0));
+        assert_eq!(field.try_gather(Vec3::new(1.5, 2.0, 3.0)), Some(2.0));
+        assert_eq!(field.try_gather(Vec3::new(-0.5, 2.0, 3.0)), None);
+        assert_eq!(field.try_gather(Vec3::new(1.0, 4.0, 3.0)), None);
+    }
+
+    #[test]
+    fn test_gather_closed() {
+        let mut field = Field::<f64>::new(Dimensions::new(4, 5, 6));
+        field[[3, 4, 5]] = 2.0;
+        field[[3, 3, 5]] = 4.0;
+
+        // Points on the faces furthest from the origin are gathered rather than dropped.
+        assert_eq!(field.gather(Vec3::new(3.0, 4.0, 5.0)), 0.0);
+        assert_eq!(field.gather_closed(Vec3::new(3.0, 4.0, 5.0)), 2.0);
+        assert_eq!(field.gather_closed(Vec3::new(3.0, 3.5, 5.0)), 3.0);
+        assert_eq!(field.gather_closed(Vec3::new(3.1, 3.5, 5.0)), 0.0);
+        assert_eq!(field.gather_closed(Vec3::new(-0.1, 3.5, 5.0)), 0.0);
+    }
+
+    #[test]
+    fn test_scatter_partial() {
+        let mut field = Field::<f64>::new(Dimensions::new(5, 5, 5));
+
+        // Entirely within the mesh, including a point on the maximum face.
+        assert!((field.scatter_partial(Vec3::new(1.5, 2.25, 3.0), 1.0) - 1.0).abs() < 1e-12);
+        assert!((field.scatter_partial(Vec3::new(4.0, 2.0, 3.0), 1.0) - 1.0).abs() < 1e-12);
+        assert!((field.sum() - 2.0).abs() < 1e-12);
+
+        // Straddling the minimum x face, with a quarter of a cell lying outside.
+        field.clear();
+        let fraction = field.scatter_partial(Vec3::new(-0.25, 2.5, 1.0), 2.0);
+
+        assert!((fraction - 0.75).abs() < 1e-12);
+        assert!((field.sum() - 1.5).abs() < 1e-12);
+        assert!((field[[0, 2, 1]] - 0.75).abs() < 1e-12);
+        assert!((field[[0, 3, 1]] - 0.75).abs() < 1e-12);
+
+        // Lying entirely outside of the mesh.
+        field.clear();
+        assert_eq!(field.scatter_partial(Vec3::new(-1.5, 2.0, 3.0), 1.0), 0.0);
+        assert_eq!(field.sum(), 0.0);
+    }
+
+    #[test]
+    fn test_scatter_weighted() {
+        let mut field = Field::<f64>::new(Dimensions::new(5, 5, 5));
+
+        // Assigning everything to the nearest node.
+        field.scatter_weighted(Vec3::new(1.4, 2.6, 3.0), 1.0, Weighting::NearestGridPoint);
+        assert_eq!(field[[1, 3, 3]], 1.0);
+        assert_eq!(field.sum(), 1.0);
+
+        // Spreading over the three nearest nodes, folding back at the boundary.
+        for lc in [Vec3::new(2.2, 1.9, 3.0), Vec3::new(0.1, 4.0, 0.4)] {
+            field.clear();
+            let fraction = field.scatter_weighted(lc, 1.0, Weighting::TriangularShapedCloud);
+
+            assert!((fraction - 1.0).abs() < 1e-12);
+            assert!((field.sum() - 1.0).abs() < 1e-12);
+        }
+
+        field.clear();
+        field.scatter_weighted(
+            Vec3::new(2.0, 2.0, 2.0),
+            1.0,
+            Weighting::TriangularShapedCloud,
+        );
+        assert!((field[[2, 2, 2]] - 0.75_f64.powi(3)).abs() < 1e-12);
+        assert!((field[[1, 2, 2]] - 0.125 * 0.75 * 0.75).abs() < 1e-12);
+    }
+}
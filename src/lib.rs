@@ -0,0 +1,19 @@
+pub mod analytic;
+pub mod boundary;
+pub mod config;
+pub mod constants;
+pub mod cross_section;
+pub mod cylindrical_mesh;
+pub mod field;
+pub mod mesh;
+pub mod multi_block_mesh;
+pub mod output;
+pub mod particle;
+pub mod plasma;
+pub mod sampling;
+pub mod solver;
+pub mod species;
+pub mod state;
+pub mod vector;
+
+pub mod simulations;
@@ -1,19 +1,11 @@
-mod constants;
-mod field;
-mod mesh;
-mod output;
-mod particle;
-mod species;
-mod vector;
-
-mod simulations;
-
 use std::env;
 use std::io::{Error, ErrorKind};
 use std::time::Instant;
 
-use crate::simulations::grounded_box;
-use crate::simulations::single_particle;
+use plasma_simulation::simulations::convergence_study;
+use plasma_simulation::simulations::grounded_box;
+use plasma_simulation::simulations::neutral_box;
+use plasma_simulation::simulations::single_particle;
 
 fn main() -> std::io::Result<()> {
     const NUM_MESH_NODES: usize = 21;
@@ -47,6 +39,20 @@ fn main() -> std::io::Result<()> {
         let elapsed_time = now.elapsed();
         println!("Simulation took {} seconds.", elapsed_time.as_secs());
         println!("Grounded box multi-particle simulation complete.");
+    } else if argument == "neutral-box" {
+        println!("Running neutral plasma box simulation...");
+        let now = Instant::now();
+        neutral_box::simulate(NUM_MESH_NODES)?;
+        let elapsed_time = now.elapsed();
+        println!("Simulation took {} seconds.", elapsed_time.as_secs());
+        println!("Neutral plasma box simulation complete.");
+    } else if argument == "convergence-study" {
+        println!("Running potential solver convergence study...");
+        let now = Instant::now();
+        convergence_study::simulate()?;
+        let elapsed_time = now.elapsed();
+        println!("Simulation took {} seconds.", elapsed_time.as_secs());
+        println!("Potential solver convergence study complete.");
     }
 
     Ok(())
@@ -56,5 +62,5 @@ fn print_usage() {
     println!("Rust Plasma Physics Simulation Examples");
     println!("USAGE:\n\tplasma-simulation {{OPTIONS | SIMULATION}}");
     println!("OPTIONS:\n\t-h, --help\tPrint help information");
-    println!("SIMULATION:\n\tsingle-particle\n\tgrounded-box");
+    println!("SIMULATION:\n\tsingle-particle\n\tgrounded-box\n\tneutral-box\n\tconvergence-study");
 }
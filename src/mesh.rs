@@ -1,8 +1,23 @@
+use std::cell::OnceCell;
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind, Result as IoResult};
+use std::path::Path;
+
+use crate::boundary::{Axis, BoundaryCondition, BoundaryMode, Face};
 use crate::constants::PERMITTIVITY;
-use crate::field::Field;
+#[cfg(feature = "em")]
+use crate::constants::SPEED_OF_LIGHT;
+use crate::field::{Field, Mask};
+use crate::output::decode_base64;
+use crate::solver::{self, PotentialSolver, SolverError, SolverReport};
 use crate::species::Species;
 use crate::vector::Vec3;
 
+/// The largest number of nodes of a mesh loaded from a VTK image data file, which guards
+/// against malformed extents asking for more memory than any simulation here would use.
+const MAX_VTI_NODES: usize = 1 << 26;
+
 /// Represents the dimensions of a simulation mesh.
 #[derive(Clone, Copy, Debug)]
 pub struct Dimensions {
@@ -40,27 +55,77 @@ pub struct BoxMesh {
     cell_spacings: [f64; 3],
     /// Specifies the centroid of the mesh.
     centroid: Vec3,
+    /// Specifies the positions of the mesh nodes, computed on first access.
+    node_positions: OnceCell<Field<Vec3>>,
     /// Specifies the volumes of nodes for number density calculations.
     node_volumes: Field<f64>,
     /// Specifies the timestep for simulation.
     timestep: f64,
+    /// Specifies the current simulation time.
+    time: f64,
     /// Specifies the potential on the mesh.
     potential: Field<f64>,
     /// Specifies the charge density on the mesh.
     charge_density: Field<f64>,
+    /// Specifies a uniform charge density added to that of the species.
+    background_charge_density: f64,
     /// Specifies the electric field on the mesh.
     electric_field: Field<Vec3>,
+    /// Specifies the nodes occupied by solids, which the solver pins to the solid potential.
+    solid_mask: Option<Mask>,
+    /// Specifies the potential of the solids.
+    solid_potential: f64,
+    /// Specifies the boundary conditions on the potential at each face.
+    boundary_conditions: [BoundaryCondition; 6],
+    /// Specifies whether the solver pins the domain-average potential to zero.
+    pin_mean_potential: bool,
+    /// Specifies the method used to solve the potential.
+    potential_solver: PotentialSolver,
+    /// Specifies the successive over-relaxation parameter of the potential solver.
+    relaxation_parameter: f64,
+    /// Specifies the number of binomial filter passes applied to the electric field.
+    electric_field_filter_passes: usize,
+    /// Specifies how particles are treated upon reaching each face.
+    particle_boundaries: [BoundaryMode; 6],
+    /// Specifies the fraction of normal kinetic energy retained by particles reflected at
+    /// each face.
+    reflection_energy_coefficients: [f64; 6],
+    /// Specifies the nodes updated by the potential solver along each axis, together with
+    /// their previous and next neighbors, kept in step with the boundary conditions.
+    stencil_nodes: [Vec<(usize, usize, usize)>; 3],
+    /// Specifies the magnetic field on the mesh nodes.
+    #[cfg(feature = "em")]
+    magnetic_field: Field<Vec3>,
+    /// Specifies the electric field on the edges of the Yee cells.
+    #[cfg(feature = "em")]
+    yee_electric_field: Field<Vec3>,
+    /// Specifies the magnetic field on the faces of the Yee cells.
+    #[cfg(feature = "em")]
+    yee_magnetic_field: Field<Vec3>,
 }
 
 impl BoxMesh {
     /// Creates a box mesh.
+    ///
+    /// The dimensions must be at least 2 along each axis, so that the cell spacings are
+    /// defined, which is checked. Solving the potential further needs at least 3, so that the
+    /// solver has interior nodes and the electric field has neighbors for its one-sided
+    /// differences.
     pub fn new(origin: Vec3, max_bound: Vec3, dimensions: Dimensions, timestep: f64) -> Self {
+        assert!(
+            dimensions.x >= 2 && dimensions.y >= 2 && dimensions.z >= 2,
+            "Mesh dimensions must be at least 2 along every axis, but got {} x {} x {}.",
+            dimensions.x,
+            dimensions.y,
+            dimensions.z
+        );
+
         let centroid = (origin + max_bound) * 0.5;
 
         let cell_spacings = [
-            (max_bound.x - origin.x) / dimensions.x as f64,
-            (max_bound.y - origin.y) / dimensions.y as f64,
-            (max_bound.z - origin.z) / dimensions.z as f64,
+            (max_bound.x - origin.x) / (dimensions.x - 1) as f64,
+            (max_bound.y - origin.y) / (dimensions.y - 1) as f64,
+            (max_bound.z - origin.z) / (dimensions.z - 1) as f64,
         ];
 
         let mut mesh = BoxMesh {
@@ -69,14 +134,34 @@ impl BoxMesh {
             dimensions: dimensions,
             cell_spacings: cell_spacings,
             centroid: centroid,
+            node_positions: OnceCell::new(),
             node_volumes: Field::<f64>::new(dimensions),
             timestep: timestep,
+            time: 0.0,
             potential: Field::<f64>::new(dimensions),
             charge_density: Field::<f64>::new(dimensions),
+            background_charge_density: 0.0,
             electric_field: Field::<Vec3>::new(dimensions),
+            solid_mask: None,
+            solid_potential: 0.0,
+            boundary_conditions: [BoundaryCondition::default(); 6],
+            pin_mean_potential: false,
+            potential_solver: PotentialSolver::default(),
+            relaxation_parameter: 1.4,
+            electric_field_filter_passes: 0,
+            particle_boundaries: [BoundaryMode::default(); 6],
+            reflection_energy_coefficients: [1.0; 6],
+            stencil_nodes: Default::default(),
+            #[cfg(feature = "em")]
+            magnetic_field: Field::<Vec3>::new(dimensions),
+            #[cfg(feature = "em")]
+            yee_electric_field: Field::<Vec3>::new(dimensions),
+            #[cfg(feature = "em")]
+            yee_magnetic_field: Field::<Vec3>::new(dimensions),
         };
 
         mesh.compute_node_volumes();
+        mesh.compute_stencil_nodes();
 
         mesh
     }
@@ -91,6 +176,18 @@ impl BoxMesh {
         self.max_bound
     }
 
+    /// Returns the length of the box along each axis.
+    pub fn extent(&self) -> Vec3 {
+        self.max_bound - self.origin
+    }
+
+    /// Returns the volume of the box.
+    pub fn domain_volume(&self) -> f64 {
+        let extent = self.extent();
+
+        extent.x * extent.y * extent.z
+    }
+
     /// Returns the spacings between mesh nodes in each dimension.
     pub fn cell_spacings(&self) -> [f64; 3] {
         self.cell_spacings
@@ -106,6 +203,18 @@ impl BoxMesh {
         self.dimensions
     }
 
+    /// Returns the positions of the mesh nodes, which are computed and cached on first access
+    /// so that meshes which never query them don't pay for the extra field.
+    pub fn node_positions(&self) -> &Field<Vec3> {
+        self.node_positions
+            .get_or_init(|| self.compute_node_positions())
+    }
+
+    /// Returns the cached position of the mesh node at the provided indices.
+    pub fn node_position(&self, i: usize, j: usize, k: usize) -> &Vec3 {
+        &self.node_positions()[[i, j, k]]
+    }
+
     /// Returns node volumes.
     pub fn node_volumes(&self) -> Field<f64> {
         self.node_volumes.clone()
@@ -121,16 +230,244 @@ impl BoxMesh {
         &self.charge_density
     }
 
+    /// Returns the potential on the mesh for modification within the crate.
+    pub(crate) fn potential_mut(&mut self) -> &mut Field<f64> {
+        &mut self.potential
+    }
+
+    /// Sets the charge density on the mesh directly, bypassing the particles, such as to solve
+    /// for the potential of a prescribed charge distribution.
+    ///
+    /// The field must have the dimensions of the mesh. It is replaced by the next call to
+    /// `compute_charge_density`.
+    pub fn set_charge_density(&mut self, charge_density: Field<f64>) {
+        assert_eq!(
+            charge_density.shape(),
+            self.charge_density.shape(),
+            "Charge density shape doesn't match the mesh dimensions."
+        );
+
+        self.charge_density = charge_density;
+    }
+
+    /// Returns the charge density on the mesh for modification within the crate.
+    pub(crate) fn charge_density_mut(&mut self) -> &mut Field<f64> {
+        &mut self.charge_density
+    }
+
     /// Returns the electric field on the mesh.
     pub fn electric_field(&self) -> &Field<Vec3> {
         &self.electric_field
     }
 
+    /// Returns the boundary condition on the potential at a face.
+    pub fn boundary_condition(&self, face: Face) -> BoundaryCondition {
+        self.boundary_conditions[face.index()]
+    }
+
+    /// Sets the boundary condition on the potential at a face.
+    pub fn set_boundary_condition(&mut self, face: Face, boundary_condition: BoundaryCondition) {
+        self.boundary_conditions[face.index()] = boundary_condition;
+        self.compute_stencil_nodes();
+    }
+
+    /// Sets the boundary conditions on the potential at every face, such as those composed
+    /// with `BoundaryConditions`.
+    pub fn set_boundary_conditions(&mut self, boundary_conditions: [BoundaryCondition; 6]) {
+        self.boundary_conditions = boundary_conditions;
+        self.compute_stencil_nodes();
+    }
+
+    /// Returns whether the solver pins the domain-average potential to zero.
+    pub fn pin_mean_potential(&self) -> bool {
+        self.pin_mean_potential
+    }
+
+    /// Sets whether the solver pins the domain-average potential to zero.
+    ///
+    /// With Neumann conditions on every face the potential is only determined up to a
+    /// constant, so pinning the mean makes the problem of a floating domain well-posed.
+    pub fn set_pin_mean_potential(&mut self, pin_mean_potential: bool) {
+        self.pin_mean_potential = pin_mean_potential;
+    }
+
+    /// Returns how particles are treated upon reaching a face.
+    pub fn particle_boundary(&self, face: Face) -> BoundaryMode {
+        self.particle_boundaries[face.index()]
+    }
+
+    /// Sets how particles are treated upon reaching a face.
+    ///
+    /// Periodic particle boundaries should be set on both faces of an axis, as with
+    /// `set_periodic`, since a particle leaving through a periodic face re-enters through the
+    /// opposite face.
+    pub fn set_particle_boundary(&mut self, face: Face, boundary_mode: BoundaryMode) {
+        self.particle_boundaries[face.index()] = boundary_mode;
+    }
+
+    /// Makes both faces of an axis periodic for both the potential and the particles, so that
+    /// the mesh wraps around along the axis.
+    pub fn set_periodic(&mut self, axis: Axis) {
+        let (min, max) = axis.faces();
+
+        for face in [min, max] {
+            self.boundary_conditions[face.index()] = BoundaryCondition::Periodic;
+            self.particle_boundaries[face.index()] = BoundaryMode::Periodic;
+        }
+
+        self.compute_stencil_nodes();
+    }
+
+    /// Returns whether particles wrap around both faces of an axis.
+    pub(crate) fn particles_periodic(&self, axis: Axis) -> bool {
+        let (min, max) = axis.faces();
+
+        self.particle_boundaries[min.index()] == BoundaryMode::Periodic
+            && self.particle_boundaries[max.index()] == BoundaryMode::Periodic
+    }
+
+    /// Averages the values on the first and last nodes of each axis along which particles wrap
+    /// around.
+    ///
+    /// These nodes coincide, but each only receives deposits from the cell on its own side of
+    /// the mesh, so a density deposited on half a node volume is combined with its other half.
+    pub(crate) fn fold_periodic_nodes(&self, field: &mut Field<f64>) {
+        let dimensions = [self.dimensions.x, self.dimensions.y, self.dimensions.z];
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            if !self.particles_periodic(axis) {
+                continue;
+            }
+
+            let axis = axis.index();
+            let u_axis = (axis + 1) % 3;
+            let v_axis = (axis + 2) % 3;
+
+            for u in 0..dimensions[u_axis] {
+                for v in 0..dimensions[v_axis] {
+                    let mut first = [0; 3];
+                    first[u_axis] = u;
+                    first[v_axis] = v;
+                    let mut last = first;
+                    last[axis] = dimensions[axis] - 1;
+
+                    let average = 0.5 * (field[first] + field[last]);
+                    field[first] = average;
+                    field[last] = average;
+                }
+            }
+        }
+    }
+
+    /// Returns the fraction of the kinetic energy normal to a face retained by particles
+    /// reflected at the face.
+    pub fn reflection_energy_coefficient(&self, face: Face) -> f64 {
+        self.reflection_energy_coefficients[face.index()]
+    }
+
+    /// Sets the fraction of the kinetic energy normal to a face retained by particles
+    /// reflected at the face, from zero for particles stopped at the wall to one for perfectly
+    /// elastic reflection, which is the default.
+    pub fn set_reflection_energy_coefficient(&mut self, face: Face, coefficient: f64) {
+        assert!(
+            (0.0..=1.0).contains(&coefficient),
+            "Reflection energy coefficient must lie within [0, 1], but got {}.",
+            coefficient
+        );
+
+        self.reflection_energy_coefficients[face.index()] = coefficient;
+    }
+
+    /// Returns the method used to solve the potential.
+    pub fn potential_solver(&self) -> PotentialSolver {
+        self.potential_solver
+    }
+
+    /// Sets the method used by `solve_potential`, which defaults to successive
+    /// over-relaxation.
+    pub fn set_potential_solver(&mut self, potential_solver: PotentialSolver) {
+        self.potential_solver = potential_solver;
+    }
+
+    /// Returns the successive over-relaxation parameter of the potential solver.
+    pub fn relaxation_parameter(&self) -> f64 {
+        self.relaxation_parameter
+    }
+
+    /// Sets the successive over-relaxation parameter of the potential solver, which defaults to
+    /// 1.4.
+    ///
+    /// SOR only converges for parameters within (0, 2), and the optimal value within that
+    /// interval depends on the mesh, see `solver::optimal_relaxation_parameter`. Panics for
+    /// parameters outside of that interval.
+    pub fn set_relaxation_parameter(&mut self, relaxation_parameter: f64) {
+        solver::validate_relaxation_parameter(relaxation_parameter)
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        self.relaxation_parameter = relaxation_parameter;
+    }
+
+    /// Returns the nodes occupied by solids, if any.
+    pub fn solid_mask(&self) -> Option<&Mask> {
+        self.solid_mask.as_ref()
+    }
+
+    /// Returns the potential the solids are pinned to.
+    pub fn solid_potential(&self) -> f64 {
+        self.solid_potential
+    }
+
+    /// Marks the flagged nodes as solid, so that the potential solver pins them to the provided
+    /// potential rather than updating them.
+    ///
+    /// This carves electrodes of arbitrary shape into the domain. The mask may flag nodes on
+    /// the faces of the mesh, in which case it takes precedence over their boundary conditions.
+    pub fn set_solid_mask(&mut self, mask: Mask, potential: f64) {
+        assert_eq!(
+            mask.shape(),
+            self.potential.shape(),
+            "Solid mask shape doesn't match the mesh dimensions."
+        );
+
+        self.solid_mask = Some(mask);
+        self.solid_potential = potential;
+    }
+
+    /// Removes any solids, so that the solver updates every interior node again.
+    pub fn clear_solid_mask(&mut self) {
+        self.solid_mask = None;
+    }
+
+    /// Returns the number of binomial filter passes applied to the electric field.
+    pub fn electric_field_filter_passes(&self) -> usize {
+        self.electric_field_filter_passes
+    }
+
+    /// Sets the number of binomial filter passes applied to the electric field each time it
+    /// is computed.
+    ///
+    /// Smoothing the field gathered by particles reduces grid heating. Each pass filters
+    /// every component along each axis, see `Field::smooth_binomial`. No filtering is applied
+    /// by default.
+    pub fn set_electric_field_filter_passes(&mut self, passes: usize) {
+        self.electric_field_filter_passes = passes;
+    }
+
     /// Returns the change in time for a mesh iteration.
     pub fn timestep(&self) -> f64 {
         self.timestep
     }
 
+    /// Returns the current simulation time.
+    pub fn current_time(&self) -> f64 {
+        self.time
+    }
+
+    /// Advances the simulation time by a timestep.
+    pub fn advance_time(&mut self) {
+        self.time += self.timestep;
+    }
+
     /// Converts a position to a logical coordinate.
     pub fn position_to_logical_coordinate(&self, position: Vec3) -> Vec3 {
         let mut logical_coordinate = position - self.origin;
@@ -140,8 +477,108 @@ impl BoxMesh {
         logical_coordinate
     }
 
-    /// Computes charge density on the mesh.
-    pub fn compute_charge_density(&mut self, species: &Vec<Species>) {
+    /// Returns the number of cells in the mesh.
+    pub fn num_cells(&self) -> usize {
+        (self.dimensions.x - 1) * (self.dimensions.y - 1) * (self.dimensions.z - 1)
+    }
+
+    /// Returns the linear index of the cell containing a position, with x varying fastest.
+    ///
+    /// Positions outside of the mesh are assigned to the nearest cell.
+    pub fn cell_index(&self, position: Vec3) -> usize {
+        let lc = self.position_to_logical_coordinate(position);
+        let num_cells = [
+            self.dimensions.x - 1,
+            self.dimensions.y - 1,
+            self.dimensions.z - 1,
+        ];
+
+        let mut cell = [0; 3];
+        for axis in 0..3 {
+            cell[axis] = (lc[axis].max(0.0) as usize).min(num_cells[axis] - 1);
+        }
+
+        cell[0] + num_cells[0] * (cell[1] + num_cells[1] * cell[2])
+    }
+
+    /// Integrates a scalar field over the domain using the node volumes.
+    pub fn integrate(&self, field: &Field<f64>) -> f64 {
+        let dimensions = self.dimensions;
+        let mut integral = 0.0;
+
+        for i in 0..dimensions.x {
+            for j in 0..dimensions.y {
+                for k in 0..dimensions.z {
+                    integral += field[[i, j, k]] * self.node_volumes[[i, j, k]];
+                }
+            }
+        }
+
+        integral
+    }
+
+    /// Returns the total charge on the mesh.
+    pub fn total_charge(&self) -> f64 {
+        self.integrate(&self.charge_density)
+    }
+
+    /// Returns the time taken for a wave travelling at the provided speed, such as the ion
+    /// acoustic speed, to cross the longest extent of the mesh.
+    pub fn sound_crossing_time(&self, sound_speed: f64) -> f64 {
+        let extent = self.extent();
+
+        extent.x.max(extent.y).max(extent.z) / sound_speed
+    }
+
+    /// Estimates the thickness of the sheath at a wall of the mesh.
+    ///
+    /// Walking inward from the wall along the center line of the mesh, the thickness is the
+    /// distance to the first node at which the potential is within 1% of the wall-to-bulk
+    /// potential drop of the bulk value, taken at the center of the mesh.
+    pub fn sheath_thickness(&self, wall: Face) -> f64 {
+        let axis = wall.axis().index();
+        let dimensions = [self.dimensions.x, self.dimensions.y, self.dimensions.z];
+        let center = [dimensions[0] / 2, dimensions[1] / 2, dimensions[2] / 2];
+
+        let node_at = |step: usize| {
+            let mut index = center;
+            index[axis] = if wall.is_min() {
+                step
+            } else {
+                dimensions[axis] - 1 - step
+            };
+            index
+        };
+
+        let bulk = self.potential[center];
+        let drop = (bulk - self.potential[node_at(0)]).abs();
+
+        let steps = (0..=center[axis])
+            .find(|&step| (self.potential[node_at(step)] - bulk).abs() <= 0.01 * drop)
+            .unwrap_or(center[axis]);
+
+        steps as f64 * self.cell_spacings[axis]
+    }
+
+    /// Returns the energy stored in the electric field, integrated over the node volumes.
+    pub fn field_energy(&self) -> f64 {
+        let dimensions = self.dimensions;
+        let mut energy = 0.0;
+
+        for i in 0..dimensions.x {
+            for j in 0..dimensions.y {
+                for k in 0..dimensions.z {
+                    let ef = self.electric_field[[i, j, k]];
+                    energy += ef.dot(ef) * self.node_volumes[[i, j, k]];
+                }
+            }
+        }
+
+        0.5 * PERMITTIVITY * energy
+    }
+
+    /// Computes charge density on the mesh, including any uniform background.
+    pub fn compute_charge_density(&mut self, species: &[Species]) {
         self.charge_density.clear();
 
         for s in species {
@@ -149,8 +586,111 @@ impl BoxMesh {
                 continue;
             }
 
-            self.charge_density += s.number_density() * s.charge();
+            self.charge_density += s.charge_density();
+        }
+
+        if self.background_charge_density != 0.0 {
+            self.charge_density = self.charge_density.clone() + self.background_charge_density;
+        }
+    }
+
+    /// Returns the uniform charge density added to that of the species.
+    pub fn background_charge_density(&self) -> f64 {
+        self.background_charge_density
+    }
+
+    /// Sets a fixed uniform background charge density which exactly neutralizes the charge
+    /// deposited by the provided species over the domain.
+    ///
+    /// This gives a well-defined equilibrium for tests with a single species, such as
+    /// electron oscillations in a grounded box. The number densities of the species must
+    /// have been computed, and the background is included from the next call to
+    /// `compute_charge_density`.
+    pub fn set_neutralizing_background(&mut self, species: &[Species]) {
+        let total_charge: f64 = species
+            .iter()
+            .map(|s| self.integrate(&self.charge_density_of(s)))
+            .sum();
+
+        self.background_charge_density = -total_charge / self.domain_volume();
+    }
+
+    /// Returns the contribution of a single species to the charge density, without modifying
+    /// the charge density on the mesh.
+    ///
+    /// This is the number density of the species scaled by its charge, accounting for the
+    /// charge state of each particle.
+    pub fn charge_density_of(&self, species: &Species) -> Field<f64> {
+        let charge_density = species.charge_density();
+
+        assert_eq!(
+            charge_density.shape(),
+            self.charge_density.shape(),
+            "Species density shape doesn't match the mesh dimensions."
+        );
+
+        charge_density
+    }
+
+    /// Returns the absolute charge density integrated over the domain, normalized by the total
+    /// charge of the positively charged species and any positive background charge.
+    ///
+    /// This is zero for a perfectly neutral plasma and one when only ions are present, so a
+    /// rising imbalance flags numerical heating or particles leaking through the boundaries.
+    /// The charge density must have been computed from the provided species. Without any
+    /// positive charge, such as once every ion has been absorbed, this returns infinity.
+    pub fn net_charge_imbalance(&self, species: &[Species]) -> f64 {
+        let mut ion_charge: f64 = species
+            .iter()
+            .filter(|s| s.charge() > 0.0)
+            .map(|s| self.integrate(&self.charge_density_of(s)))
+            .sum();
+
+        if self.background_charge_density > 0.0 {
+            ion_charge += self.background_charge_density * self.domain_volume();
+        }
+
+        if ion_charge <= 0.0 {
+            return f64::INFINITY;
+        }
+
+        let mut absolute_charge = 0.0;
+        for i in 0..self.dimensions.x {
+            for j in 0..self.dimensions.y {
+                for k in 0..self.dimensions.z {
+                    absolute_charge +=
+                        self.charge_density[[i, j, k]].abs() * self.node_volumes[[i, j, k]];
+                }
+            }
+        }
+
+        absolute_charge / ion_charge
+    }
+
+    /// Computes the position of a mesh node from the origin and cell spacings.
+    pub fn compute_node_position(&self, i: usize, j: usize, k: usize) -> Vec3 {
+        self.origin
+            + Vec3::new(
+                i as f64 * self.cell_spacings[0],
+                j as f64 * self.cell_spacings[1],
+                k as f64 * self.cell_spacings[2],
+            )
+    }
+
+    /// Computes node positions.
+    fn compute_node_positions(&self) -> Field<Vec3> {
+        let dimensions = self.dimensions;
+        let mut node_positions = Field::<Vec3>::new(dimensions);
+
+        for i in 0..dimensions.x {
+            for j in 0..dimensions.y {
+                for k in 0..dimensions.z {
+                    node_positions[[i, j, k]] = self.compute_node_position(i, j, k);
+                }
+            }
         }
+
+        node_positions
     }
 
     /// Computes node volumes.
@@ -180,124 +720,2272 @@ impl BoxMesh {
         }
     }
 
-    /// Solves the potential field.
-    pub fn solve_potential(&mut self, max_solver_iterations: usize, tolerance: f64) -> bool {
-        let dx2 = 1.0 / (self.cell_spacings[0] * self.cell_spacings[0]);
-        let dy2 = 1.0 / (self.cell_spacings[1] * self.cell_spacings[1]);
-        let dz2 = 1.0 / (self.cell_spacings[2] * self.cell_spacings[2]);
+    /// Saves the potential to a CSV file which can be restored with `load_potential`.
+    ///
+    /// The first line holds the mesh dimensions, followed by one potential value per line
+    /// with the x-index varying fastest.
+    pub fn save_potential<P: AsRef<Path>>(&self, path: P) -> IoResult<()> {
+        let (ni, nj, nk) = self.potential.shape();
+        let mut contents = format!("{},{},{}\n", ni, nj, nk);
 
-        let dimensions = &self.dimensions;
-        let phi = &mut self.potential;
-        let rho = &self.charge_density;
+        for k in 0..nk {
+            for j in 0..nj {
+                for i in 0..ni {
+                    contents.push_str(&format!("{:e}\n", self.potential[[i, j, k]]));
+                }
+            }
+        }
 
-        let mut residue_l2_norm;
-        let mut converged = false;
+        fs::write(path, contents)
+    }
 
-        let gauss_seidel_denominator = 2.0 * dx2 + 2.0 * dy2 + 2.0 * dz2;
-        let volume = (dimensions.x * dimensions.y * dimensions.z) as f64;
+    /// Loads the potential from a CSV file written by `save_potential`, such as a prior
+    /// solution or an analytic guess from which to warm-start the solver.
+    ///
+    /// Blank lines and lines starting with `#` are ignored. Fails without modifying the
+    /// potential if the dimensions in the file don't match those of the mesh.
+    pub fn load_potential<P: AsRef<Path>>(&mut self, path: P) -> IoResult<()> {
+        let contents = fs::read_to_string(path)?;
 
-        // Iterating through mesh to solve potential.
-        for iteration in 0..max_solver_iterations {
-            for i in 1..dimensions.x - 1 {
-                for j in 1..dimensions.y - 1 {
-                    for k in 1..dimensions.z - 1 {
-                        // Applying the Gauss-Seidel method.
-                        let new_phi = ((rho[[i, j, k]] / PERMITTIVITY)
-                            + dx2 * (phi[[i - 1, j, k]] + phi[[i + 1, j, k]])
-                            + dy2 * (phi[[i, j - 1, k]] + phi[[i, j + 1, k]])
-                            + dz2 * (phi[[i, j, k - 1]] + phi[[i, j, k + 1]]))
-                            / gauss_seidel_denominator;
+        let mut lines = contents
+            .lines()
+            .enumerate()
+            .map(|(line_number, line)| (line_number + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
 
-                        let current_phi = phi[[i, j, k]];
+        let invalid = |line_number: usize, message: String| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Line {}: {}", line_number, message),
+            )
+        };
 
-                        // Successive over-relaxation.
-                        phi[[i, j, k]] = current_phi + 1.4 * (new_phi - current_phi);
-                    }
-                }
-            }
+        let (line_number, header) = lines
+            .next()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Potential file is empty."))?;
+        let shape: Vec<usize> = header
+            .split(',')
+            .map(|token| token.trim().parse::<usize>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|error| invalid(line_number, error.to_string()))?;
 
-            // Checking for convergence.
-            if iteration != 0 && iteration % 25 == 0 {
-                let mut sum = 0.0;
+        let expected_shape = self.potential.shape();
+        if shape != [expected_shape.0, expected_shape.1, expected_shape.2] {
+            return Err(invalid(
+                line_number,
+                format!(
+                    "dimensions {:?} don't match mesh dimensions {:?}.",
+                    shape, expected_shape
+                ),
+            ));
+        }
 
-                for i in 1..dimensions.x - 1 {
-                    for j in 1..dimensions.y - 1 {
-                        for k in 1..dimensions.z - 1 {
-                            let r = -phi[[i, j, k]] * gauss_seidel_denominator
-                                + (rho[[i, j, k]] / PERMITTIVITY)
-                                + dx2 * (phi[[i - 1, j, k]] + phi[[i + 1, j, k]])
-                                + dy2 * (phi[[i, j - 1, k]] + phi[[i, j + 1, k]])
-                                + dz2 * (phi[[i, j, k - 1]] + phi[[i, j, k + 1]]);
-                            sum += r * r;
-                        }
-                    }
-                }
+        let num_values: usize = shape.iter().product();
+        let mut values = Vec::with_capacity(num_values);
+        for (line_number, line) in lines {
+            let value = line
+                .parse::<f64>()
+                .map_err(|error| invalid(line_number, error.to_string()))?;
+            values.push(value);
+        }
+
+        if values.len() != num_values {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Expected {} potential values but found {}.",
+                    num_values,
+                    values.len()
+                ),
+            ));
+        }
 
-                residue_l2_norm = (sum / volume).sqrt();
-                if residue_l2_norm < tolerance {
-                    converged = true;
-                    break;
+        let (ni, nj, nk) = expected_shape;
+        let mut values = values.into_iter();
+        for k in 0..nk {
+            for j in 0..nj {
+                for i in 0..ni {
+                    self.potential[[i, j, k]] = values.next().unwrap();
                 }
             }
         }
 
-        converged
+        Ok(())
     }
 
-    /// Computes the electric field.
-    pub fn compute_electric_field(&mut self) {
-        let dx = self.cell_spacings[0];
-        let dy = self.cell_spacings[1];
-        let dz = self.cell_spacings[2];
+    /// Loads a mesh from a VTK image data file written by `output::write_vti`, restoring the
+    /// geometry along with the potential, charge density and electric field.
+    ///
+    /// Only the field state is restored. Particles aren't stored in the file, so species must
+    /// be reloaded separately, and neither is the timestep, which must be provided. Data
+    /// arrays left out of the file are left at zero, and the boundary conditions and solver
+    /// settings are the defaults of a new mesh.
+    ///
+    /// The extent must span at least 2 nodes along each axis, as `new` requires, and at most
+    /// `MAX_VTI_NODES` nodes in total, otherwise an `InvalidData` error is returned.
+    pub fn load_from_vti<P: AsRef<Path>>(path: P, timestep: f64) -> IoResult<BoxMesh> {
+        let contents = fs::read_to_string(path)?;
 
-        let dimensions = &self.dimensions;
-        let phi = &mut self.potential;
+        let invalid = |message: String| Error::new(ErrorKind::InvalidData, message);
 
-        for i in 0..dimensions.x {
-            for j in 0..dimensions.y {
-                for k in 0..dimensions.z {
-                    let ef = &mut self.electric_field[[i, j, k]];
+        let image_data_start = contents
+            .find("<ImageData ")
+            .ok_or_else(|| invalid(String::from("Missing ImageData element.")))?;
+        let image_data = &contents[image_data_start..];
+        let image_data = &image_data[..image_data.find('>').unwrap_or(image_data.len())];
 
-                    // Computing the x-component.
-                    if i == 0 {
-                        ef.x = -(-3.0 * phi[[i, j, k]] + 4.0 * phi[[i + 1, j, k]]
-                            - phi[[i + 2, j, k]])
-                            / (2.0 * dx);
-                    } else if i == dimensions.x - 1 {
-                        ef.x = -(phi[[i - 2, j, k]] - 4.0 * phi[[i - 1, j, k]]
-                            + 3.0 * phi[[i, j, k]])
-                            / (2.0 * dx);
-                    } else {
-                        ef.x = -(phi[[i + 1, j, k]] - phi[[i - 1, j, k]]) / (2.0 * dx);
-                    }
+        let origin = parse_vti_values(vti_attribute(image_data, "Origin")?)?;
+        let spacing = parse_vti_values(vti_attribute(image_data, "Spacing")?)?;
+        let extent = parse_vti_values(vti_attribute(image_data, "WholeExtent")?)?;
 
-                    // Computing the y-component.
-                    if j == 0 {
-                        ef.y = -(-3.0 * phi[[i, j, k]] + 4.0 * phi[[i, j + 1, k]]
-                            - phi[[i, j + 2, k]])
-                            / (2.0 * dy);
-                    } else if j == dimensions.y - 1 {
-                        ef.y = -(phi[[i, j - 2, k]] - 4.0 * phi[[i, j - 1, k]]
-                            + 3.0 * phi[[i, j, k]])
-                            / (2.0 * dy);
-                    } else {
-                        ef.y = -(phi[[i, j + 1, k]] - phi[[i, j - 1, k]]) / (2.0 * dy);
-                    }
+        if origin.len() != 3 || spacing.len() != 3 || extent.len() != 6 {
+            return Err(invalid(String::from(
+                "ImageData origin, spacing or extent has the wrong number of values.",
+            )));
+        }
 
-                    // Computing the z-component.
-                    if k == 0 {
-                        ef.z = -(-3.0 * phi[[i, j, k]] + 4.0 * phi[[i, j, k + 1]]
-                            - phi[[i, j, k + 2]])
-                            / (2.0 * dz);
-                    } else if k == dimensions.z - 1 {
-                        ef.z = -(phi[[i, j, k - 2]] - 4.0 * phi[[i, j, k - 1]]
-                            + 3.0 * phi[[i, j, k]])
-                            / (2.0 * dz);
-                    } else {
-                        ef.z = -(phi[[i, j, k + 1]] - phi[[i, j, k - 1]]) / (2.0 * dz);
-                    }
-                }
+        // Checking the extent before allocating the mesh, as the node counts are only valid
+        // for non-negative integer bounds enclosing at least one cell.
+        let mut nodes = [0.0; 3];
+        for (axis, bounds) in extent.chunks(2).enumerate() {
+            let (low, high) = (bounds[0], bounds[1]);
+
+            if !(low.is_finite() && high.is_finite())
+                || low < 0.0
+                || low.fract() != 0.0
+                || high.fract() != 0.0
+                || high <= low
+            {
+                return Err(invalid(format!(
+                    "ImageData extent from {} to {} along axis {} isn't a valid range of nodes.",
+                    low, high, axis
+                )));
+            }
+
+            nodes[axis] = high - low + 1.0;
+        }
+
+        if nodes[0] * nodes[1] * nodes[2] > MAX_VTI_NODES as f64 {
+            return Err(invalid(format!(
+                "ImageData extent holds {} x {} x {} nodes, exceeding {} in total.",
+                nodes[0], nodes[1], nodes[2], MAX_VTI_NODES
+            )));
+        }
+
+        let dimensions = Dimensions::new(nodes[0] as usize, nodes[1] as usize, nodes[2] as usize);
+        let origin = Vec3::new(origin[0], origin[1], origin[2]);
+        let max_bound = origin
+            + Vec3::new(
+                spacing[0] * (dimensions.x - 1) as f64,
+                spacing[1] * (dimensions.y - 1) as f64,
+                spacing[2] * (dimensions.z - 1) as f64,
+            );
+
+        let mut mesh = BoxMesh::new(origin, max_bound, dimensions, timestep);
+        let num_nodes = dimensions.x * dimensions.y * dimensions.z;
+
+        // Data arrays are written in VTK point ordering, with the x-index varying fastest.
+        let indices = (0..dimensions.z).flat_map(|k| {
+            (0..dimensions.y).flat_map(move |j| (0..dimensions.x).map(move |i| [i, j, k]))
+        });
+
+        if let Some(values) = vti_data_array(&contents, "phi", num_nodes)? {
+            for (index, value) in indices.clone().zip(values) {
+                mesh.potential[index] = value;
+            }
+        }
+
+        if let Some(values) = vti_data_array(&contents, "rho", num_nodes)? {
+            for (index, value) in indices.clone().zip(values) {
+                mesh.charge_density[index] = value;
+            }
+        }
+
+        if let Some(values) = vti_data_array(&contents, "ef", 3 * num_nodes)? {
+            for (index, value) in indices.zip(values.chunks(3)) {
+                mesh.electric_field[index] = Vec3::new(value[0], value[1], value[2]);
             }
         }
+
+        Ok(mesh)
+    }
+
+    /// Solves the potential field with the selected potential solver.
+    ///
+    /// When provided, `residual_history` is extended with the iteration and residual of each
+    /// convergence check. For successive over-relaxation, the report also holds the largest
+    /// change of the potential over the final sweep, which gives a sense of the remaining
+    /// error in volts.
+    pub fn solve_potential(
+        &mut self,
+        max_solver_iterations: usize,
+        tolerance: f64,
+        residual_history: Option<&mut Vec<(usize, f64)>>,
+    ) -> Result<SolverReport, SolverError> {
+        if self.potential_solver == PotentialSolver::ConjugateGradient {
+            return self.solve_potential_cg(max_solver_iterations, tolerance, residual_history);
+        }
+
+        solver::validate_relaxation_parameter(self.relaxation_parameter)?;
+
+        self.apply_potential_boundaries();
+
+        solver::solve_sor(
+            self,
+            max_solver_iterations,
+            tolerance,
+            residual_history,
+            BoxMesh::iterate_potential,
+            BoxMesh::potential_residue,
+        )
+    }
+
+    /// Solves the potential field with the conjugate gradient method, regardless of the
+    /// selected potential solver.
+    ///
+    /// The discretized Poisson equation uses the same 7-point stencil as successive
+    /// over-relaxation and its residual is measured the same way, so both solvers can be
+    /// compared for the same tolerance. Every face must either have a prescribed potential or
+    /// belong to a periodic axis, since Neumann faces would make the system non-symmetric.
+    /// The residual is checked at every iteration, as it comes at no extra cost.
+    ///
+    /// A mesh periodic along every axis without solid nodes only determines the potential up
+    /// to a constant, so it is rejected with `UnsupportedBoundary` unless the mean potential
+    /// is pinned. Even then, it only converges for a domain without net charge.
+    pub fn solve_potential_cg(
+        &mut self,
+        max_solver_iterations: usize,
+        tolerance: f64,
+        mut residual_history: Option<&mut Vec<(usize, f64)>>,
+    ) -> Result<SolverReport, SolverError> {
+        for face in Face::ALL {
+            let prescribed = self.boundary_conditions[face.index()]
+                .potential(self.time)
+                .is_some();
+
+            if !(prescribed || self.is_periodic(face.axis())) {
+                return Err(SolverError::UnsupportedBoundary(face));
+            }
+        }
+
+        let fully_periodic = [Axis::X, Axis::Y, Axis::Z]
+            .iter()
+            .all(|&axis| self.is_periodic(axis));
+        let has_solids = matches!(&self.solid_mask, Some(mask) if mask.count() > 0);
+
+        if fully_periodic && !has_solids && !self.pin_mean_potential {
+            return Err(SolverError::UnsupportedBoundary(Face::XMin));
+        }
+
+        self.apply_potential_boundaries();
+
+        let dx2 = 1.0 / (self.cell_spacings[0] * self.cell_spacings[0]);
+        let dy2 = 1.0 / (self.cell_spacings[1] * self.cell_spacings[1]);
+        let dz2 = 1.0 / (self.cell_spacings[2] * self.cell_spacings[2]);
+        let denominator = 2.0 * dx2 + 2.0 * dy2 + 2.0 * dz2;
+        let volume = (self.dimensions.x * self.dimensions.y * self.dimensions.z) as f64;
+
+        // Listing the unknown nodes along with their neighbors, leaving out solid nodes.
+        let [x_nodes, y_nodes, z_nodes] = &self.stencil_nodes;
+        let solid_mask = self.solid_mask.as_ref();
+        let mut nodes = Vec::new();
+
+        for &(i, im, ip) in x_nodes {
+            for &(j, jm, jp) in y_nodes {
+                for &(k, km, kp) in z_nodes {
+                    if !matches!(solid_mask, Some(mask) if mask[[i, j, k]]) {
+                        nodes.push(((i, j, k), (im, ip, jm, jp, km, kp)));
+                    }
+                }
+            }
+        }
+
+        // Applies the negated stencil to a field which vanishes on the fixed nodes.
+        let apply_stencil = |p: &Field<f64>, ap: &mut Field<f64>| {
+            for &((i, j, k), (im, ip, jm, jp, km, kp)) in &nodes {
+                ap[[i, j, k]] = denominator * p[[i, j, k]]
+                    - dx2 * (p[[im, j, k]] + p[[ip, j, k]])
+                    - dy2 * (p[[i, jm, k]] + p[[i, jp, k]])
+                    - dz2 * (p[[i, j, km]] + p[[i, j, kp]]);
+            }
+        };
+        let dot = |a: &Field<f64>, b: &Field<f64>| {
+            nodes
+                .iter()
+                .map(|&((i, j, k), _)| a[[i, j, k]] * b[[i, j, k]])
+                .sum::<f64>()
+        };
+
+        // Computing the initial residual, with the fixed nodes acting as sources.
+        let mut r = Field::<f64>::new(self.dimensions);
+        apply_stencil(&self.potential, &mut r);
+        for &((i, j, k), _) in &nodes {
+            r[[i, j, k]] = self.charge_density[[i, j, k]] / PERMITTIVITY - r[[i, j, k]];
+        }
+
+        let mut p = r.clone();
+        let mut ap = Field::<f64>::new(self.dimensions);
+        let mut r_dot_r = dot(&r, &r);
+
+        let mut residual = (r_dot_r / volume).sqrt();
+        if !residual.is_finite() {
+            return Err(SolverError::NonFiniteResidual);
+        }
+
+        for iteration in 0..max_solver_iterations {
+            // An exactly satisfied system leaves no search direction, so it is done even when
+            // the tolerance is zero.
+            if residual < tolerance || r_dot_r == 0.0 {
+                self.finish_potential_solve();
+
+                return Ok(SolverReport {
+                    iterations: iteration,
+                    residual,
+                    max_potential_change: None,
+                });
+            }
+
+            apply_stencil(&p, &mut ap);
+            let alpha = r_dot_r / dot(&p, &ap);
+
+            for &((i, j, k), _) in &nodes {
+                self.potential[[i, j, k]] += alpha * p[[i, j, k]];
+                r[[i, j, k]] -= alpha * ap[[i, j, k]];
+            }
+
+            let previous_r_dot_r = r_dot_r;
+            r_dot_r = dot(&r, &r);
+            residual = (r_dot_r / volume).sqrt();
+
+            if let Some(history) = residual_history.as_mut() {
+                history.push((iteration, residual));
+            }
+
+            if !residual.is_finite() {
+                return Err(SolverError::NonFiniteResidual);
+            }
+
+            let beta = r_dot_r / previous_r_dot_r;
+            for &((i, j, k), _) in &nodes {
+                p[[i, j, k]] = r[[i, j, k]] + beta * p[[i, j, k]];
+            }
+        }
+
+        self.finish_potential_solve();
+
+        if residual < tolerance || r_dot_r == 0.0 {
+            return Ok(SolverReport {
+                iterations: max_solver_iterations,
+                residual,
+                max_potential_change: None,
+            });
+        }
+
+        Err(SolverError::NotConverged {
+            iterations: max_solver_iterations,
+            residual,
+        })
+    }
+
+    /// Applies the mean pin and boundary conditions after the conjugate gradient method has
+    /// updated the unknown nodes.
+    fn finish_potential_solve(&mut self) {
+        if self.pin_mean_potential {
+            let mean = self.potential.mean();
+            self.potential = self.potential.clone() - mean;
+        }
+
+        self.apply_potential_boundaries();
+    }
+
+    /// Estimates the spectral radius of the Jacobi iteration matrix for the potential solve by
+    /// applying the provided number of power iterations.
+    ///
+    /// The closer the spectral radius is to one, the slower the solver converges. It also
+    /// determines the optimal relaxation parameter, see `solver::optimal_relaxation_parameter`.
+    ///
+    /// The estimate assumes a box of the same dimensions with a prescribed potential on every
+    /// face and no solid nodes, ignoring the boundary conditions and solid mask of the mesh.
+    /// Neumann and periodic faces converge more slowly than this, while solid nodes speed up
+    /// convergence, so the estimate and the relaxation parameter derived from it only hold for
+    /// such a grounded box.
+    pub fn estimate_spectral_radius(&self, samples: usize) -> f64 {
+        let dx2 = 1.0 / (self.cell_spacings[0] * self.cell_spacings[0]);
+        let dy2 = 1.0 / (self.cell_spacings[1] * self.cell_spacings[1]);
+        let dz2 = 1.0 / (self.cell_spacings[2] * self.cell_spacings[2]);
+        let denominator = 2.0 * dx2 + 2.0 * dy2 + 2.0 * dz2;
+
+        let dimensions = self.dimensions;
+
+        // Starting from a uniform interior vector, which overlaps the dominant eigenvector.
+        let mut x = Field::<f64>::new(dimensions);
+        for i in 1..dimensions.x - 1 {
+            for j in 1..dimensions.y - 1 {
+                for k in 1..dimensions.z - 1 {
+                    x[[i, j, k]] = 1.0;
+                }
+            }
+        }
+
+        let norm = |field: &Field<f64>| {
+            let mut sum = 0.0;
+            for i in 1..dimensions.x - 1 {
+                for j in 1..dimensions.y - 1 {
+                    for k in 1..dimensions.z - 1 {
+                        sum += field[[i, j, k]] * field[[i, j, k]];
+                    }
+                }
+            }
+            sum.sqrt()
+        };
+
+        let mut estimate = 0.0;
+        let mut y = Field::<f64>::new(dimensions);
+
+        for _ in 0..samples {
+            // Applying the Jacobi iteration matrix, with zero values on the boundaries.
+            for i in 1..dimensions.x - 1 {
+                for j in 1..dimensions.y - 1 {
+                    for k in 1..dimensions.z - 1 {
+                        y[[i, j, k]] = (dx2 * (x[[i - 1, j, k]] + x[[i + 1, j, k]])
+                            + dy2 * (x[[i, j - 1, k]] + x[[i, j + 1, k]])
+                            + dz2 * (x[[i, j, k - 1]] + x[[i, j, k + 1]]))
+                            / denominator;
+                    }
+                }
+            }
+
+            let x_norm = norm(&x);
+            let y_norm = norm(&y);
+
+            if x_norm == 0.0 || y_norm == 0.0 {
+                return 0.0;
+            }
+
+            estimate = y_norm / x_norm;
+            x = y.clone() * (1.0 / y_norm);
+        }
+
+        estimate
+    }
+
+    /// Returns an estimate of the number of floating-point operations needed to apply the
+    /// provided number of Gauss-Seidel sweeps to the potential of the mesh.
+    ///
+    /// Each node updated by a sweep costs a fixed number of operations for the stencil, the
+    /// over-relaxation and tracking the largest change. Boundary conditions and convergence
+    /// checks are neglected, as they scale with the faces or happen only every so often.
+    pub fn solve_flop_estimate(&self, iterations: usize) -> u64 {
+        const OPERATIONS_PER_NODE: u64 = 16;
+
+        let [x_nodes, y_nodes, z_nodes] = &self.stencil_nodes;
+        let num_nodes = (x_nodes.len() * y_nodes.len() * z_nodes.len()) as u64;
+
+        iterations as u64 * num_nodes * OPERATIONS_PER_NODE
+    }
+
+    /// Applies a fixed number of relaxation sweeps to the potential, without checking for
+    /// convergence.
+    ///
+    /// When the charge density changes little between timesteps, a few sweeps starting from
+    /// the previous potential keep it close to the converged solution at a fraction of the
+    /// cost of a full solve. Returns the largest absolute change of the potential over the
+    /// final sweep.
+    pub fn relax_potential(&mut self, sweeps: usize) -> f64 {
+        self.apply_potential_boundaries();
+
+        let mut max_potential_change = 0.0;
+        for _ in 0..sweeps {
+            max_potential_change = self.iterate_potential();
+        }
+
+        max_potential_change
+    }
+
+    /// Applies a single solver iteration, followed by the mean pin and boundary conditions.
+    ///
+    /// Returns the largest absolute change of the potential over the sweep.
+    fn iterate_potential(&mut self) -> f64 {
+        let max_potential_change = self.sweep_potential();
+
+        if self.pin_mean_potential {
+            let mean = self.potential.mean();
+            self.potential = self.potential.clone() - mean;
+        }
+
+        self.apply_potential_boundaries();
+
+        max_potential_change
+    }
+
+    /// Applies a single Gauss-Seidel sweep with successive over-relaxation to the potential,
+    /// returning the largest absolute change of any node.
+    fn sweep_potential(&mut self) -> f64 {
+        let dx2 = 1.0 / (self.cell_spacings[0] * self.cell_spacings[0]);
+        let dy2 = 1.0 / (self.cell_spacings[1] * self.cell_spacings[1]);
+        let dz2 = 1.0 / (self.cell_spacings[2] * self.cell_spacings[2]);
+
+        let gauss_seidel_denominator = 2.0 * dx2 + 2.0 * dy2 + 2.0 * dz2;
+        let relaxation_parameter = self.relaxation_parameter;
+
+        let [x_nodes, y_nodes, z_nodes] = &self.stencil_nodes;
+        let phi = &mut self.potential;
+        let rho = &self.charge_density;
+        let solid_mask = self.solid_mask.as_ref();
+
+        let mut max_change: f64 = 0.0;
+
+        for &(i, im, ip) in x_nodes {
+            for &(j, jm, jp) in y_nodes {
+                for &(k, km, kp) in z_nodes {
+                    // Leaving solid nodes at their pinned potential.
+                    if matches!(solid_mask, Some(mask) if mask[[i, j, k]]) {
+                        continue;
+                    }
+
+                    // Applying the Gauss-Seidel method.
+                    let new_phi = ((rho[[i, j, k]] / PERMITTIVITY)
+                        + dx2 * (phi[[im, j, k]] + phi[[ip, j, k]])
+                        + dy2 * (phi[[i, jm, k]] + phi[[i, jp, k]])
+                        + dz2 * (phi[[i, j, km]] + phi[[i, j, kp]]))
+                        / gauss_seidel_denominator;
+
+                    let current_phi = phi[[i, j, k]];
+
+                    // Successive over-relaxation.
+                    let change = relaxation_parameter * (new_phi - current_phi);
+                    phi[[i, j, k]] = current_phi + change;
+                    max_change = max_change.max(change.abs());
+                }
+            }
+        }
+
+        max_change
+    }
+
+    /// Returns whether both faces of an axis have periodic boundary conditions.
+    fn is_periodic(&self, axis: Axis) -> bool {
+        let (min, max) = axis.faces();
+
+        self.boundary_conditions[min.index()] == BoundaryCondition::Periodic
+            && self.boundary_conditions[max.index()] == BoundaryCondition::Periodic
+    }
+
+    /// Lists the nodes updated by the potential solver along each axis, together with their
+    /// previous and next neighbors, so that the solver doesn't rebuild them on every sweep.
+    ///
+    /// Along a periodic axis the last node coincides with the first, so the first node is
+    /// updated as well, with its previous neighbor wrapping around the mesh.
+    fn compute_stencil_nodes(&mut self) {
+        let dimensions = [self.dimensions.x, self.dimensions.y, self.dimensions.z];
+
+        self.stencil_nodes = [Axis::X, Axis::Y, Axis::Z].map(|axis| {
+            let num_nodes = dimensions[axis.index()];
+
+            if self.is_periodic(axis) {
+                let num_unique_nodes = num_nodes - 1;
+                (0..num_unique_nodes)
+                    .map(|i| {
+                        (
+                            i,
+                            (i + num_unique_nodes - 1) % num_unique_nodes,
+                            (i + 1) % num_unique_nodes,
+                        )
+                    })
+                    .collect()
+            } else {
+                (1..num_nodes - 1).map(|i| (i, i - 1, i + 1)).collect()
+            }
+        });
+    }
+
+    /// Applies the boundary conditions to the potential on the faces of the mesh, followed by
+    /// pinning any solid nodes.
+    ///
+    /// A periodic face whose opposite face isn't periodic is treated as a Neumann face.
+    fn apply_potential_boundaries(&mut self) {
+        let dimensions = [self.dimensions.x, self.dimensions.y, self.dimensions.z];
+        let periodic = [Axis::X, Axis::Y, Axis::Z].map(|axis| self.is_periodic(axis));
+
+        for face in Face::ALL {
+            let axis = face.axis().index();
+            let (node, neighbor) = if face.is_min() {
+                (0, 1)
+            } else {
+                (dimensions[axis] - 1, dimensions[axis] - 2)
+            };
+
+            // Determining the axes tangential to the face.
+            let u_axis = (axis + 1) % 3;
+            let v_axis = (axis + 2) % 3;
+
+            for u in 0..dimensions[u_axis] {
+                for v in 0..dimensions[v_axis] {
+                    let mut index = [0; 3];
+                    index[axis] = node;
+                    index[u_axis] = u;
+                    index[v_axis] = v;
+
+                    let boundary_condition = self.boundary_conditions[face.index()];
+
+                    // Copying the first node of a periodic axis onto the coinciding last node.
+                    if periodic[axis] {
+                        if !face.is_min() {
+                            let mut first_index = index;
+                            first_index[axis] = 0;
+                            self.potential[index] = self.potential[first_index];
+                        }
+                        continue;
+                    }
+
+                    // Leaving interface faces to be set by the neighboring block.
+                    if boundary_condition == BoundaryCondition::Interface {
+                        continue;
+                    }
+
+                    match boundary_condition.potential(self.time) {
+                        Some(value) => self.potential[index] = value,
+                        None => {
+                            let mut neighbor_index = index;
+                            neighbor_index[axis] = neighbor;
+                            self.potential[index] = self.potential[neighbor_index];
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(mask) = &self.solid_mask {
+            for i in 0..dimensions[0] {
+                for j in 0..dimensions[1] {
+                    for k in 0..dimensions[2] {
+                        if mask[[i, j, k]] {
+                            self.potential[[i, j, k]] = self.solid_potential;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Computes the L2 norm of the residue of the discretized Poisson equation.
+    pub(crate) fn potential_residue(&self) -> f64 {
+        let dx2 = 1.0 / (self.cell_spacings[0] * self.cell_spacings[0]);
+        let dy2 = 1.0 / (self.cell_spacings[1] * self.cell_spacings[1]);
+        let dz2 = 1.0 / (self.cell_spacings[2] * self.cell_spacings[2]);
+
+        let dimensions = &self.dimensions;
+        let phi = &self.potential;
+        let rho = &self.charge_density;
+
+        let gauss_seidel_denominator = 2.0 * dx2 + 2.0 * dy2 + 2.0 * dz2;
+        let volume = (dimensions.x * dimensions.y * dimensions.z) as f64;
+
+        let [x_nodes, y_nodes, z_nodes] = &self.stencil_nodes;
+        let solid_mask = self.solid_mask.as_ref();
+        let mut sum = 0.0;
+
+        for &(i, im, ip) in x_nodes {
+            for &(j, jm, jp) in y_nodes {
+                for &(k, km, kp) in z_nodes {
+                    // Solid nodes are pinned rather than solved for.
+                    if matches!(solid_mask, Some(mask) if mask[[i, j, k]]) {
+                        continue;
+                    }
+
+                    let r = -phi[[i, j, k]] * gauss_seidel_denominator
+                        + (rho[[i, j, k]] / PERMITTIVITY)
+                        + dx2 * (phi[[im, j, k]] + phi[[ip, j, k]])
+                        + dy2 * (phi[[i, jm, k]] + phi[[i, jp, k]])
+                        + dz2 * (phi[[i, j, km]] + phi[[i, j, kp]]);
+                    sum += r * r;
+                }
+            }
+        }
+
+        (sum / volume).sqrt()
+    }
+
+    /// Computes the electric field from the potential, followed by any filter passes.
+    pub fn compute_electric_field(&mut self) {
+        self.electric_field = self.electric_field_of(&self.potential);
+        self.electric_field
+            .smooth_binomial(self.electric_field_filter_passes);
+    }
+
+    /// Returns the electric field given by an arbitrary potential on the mesh, without
+    /// modifying the stored electric field.
+    pub fn electric_field_of(&self, potential: &Field<f64>) -> Field<Vec3> {
+        assert_eq!(
+            potential.shape(),
+            self.potential.shape(),
+            "Potential shape doesn't match the mesh dimensions."
+        );
+
+        let dx = self.cell_spacings[0];
+        let dy = self.cell_spacings[1];
+        let dz = self.cell_spacings[2];
+
+        let dimensions = &self.dimensions;
+        let phi = potential;
+        let mut electric_field = Field::<Vec3>::new(*dimensions);
+
+        // Along a periodic axis, the first and last nodes coincide and are differenced across
+        // the wraparound rather than one-sidedly.
+        let [x_periodic, y_periodic, z_periodic] =
+            [Axis::X, Axis::Y, Axis::Z].map(|axis| self.is_periodic(axis));
+
+        for i in 0..dimensions.x {
+            for j in 0..dimensions.y {
+                for k in 0..dimensions.z {
+                    let ef = &mut electric_field[[i, j, k]];
+
+                    // Computing the x-component.
+                    if x_periodic && (i == 0 || i == dimensions.x - 1) {
+                        ef.x = -(phi[[1, j, k]] - phi[[dimensions.x - 2, j, k]]) / (2.0 * dx);
+                    } else if i == 0 {
+                        ef.x = -(-3.0 * phi[[i, j, k]] + 4.0 * phi[[i + 1, j, k]]
+                            - phi[[i + 2, j, k]])
+                            / (2.0 * dx);
+                    } else if i == dimensions.x - 1 {
+                        ef.x = -(phi[[i - 2, j, k]] - 4.0 * phi[[i - 1, j, k]]
+                            + 3.0 * phi[[i, j, k]])
+                            / (2.0 * dx);
+                    } else {
+                        ef.x = -(phi[[i + 1, j, k]] - phi[[i - 1, j, k]]) / (2.0 * dx);
+                    }
+
+                    // Computing the y-component.
+                    if y_periodic && (j == 0 || j == dimensions.y - 1) {
+                        ef.y = -(phi[[i, 1, k]] - phi[[i, dimensions.y - 2, k]]) / (2.0 * dy);
+                    } else if j == 0 {
+                        ef.y = -(-3.0 * phi[[i, j, k]] + 4.0 * phi[[i, j + 1, k]]
+                            - phi[[i, j + 2, k]])
+                            / (2.0 * dy);
+                    } else if j == dimensions.y - 1 {
+                        ef.y = -(phi[[i, j - 2, k]] - 4.0 * phi[[i, j - 1, k]]
+                            + 3.0 * phi[[i, j, k]])
+                            / (2.0 * dy);
+                    } else {
+                        ef.y = -(phi[[i, j + 1, k]] - phi[[i, j - 1, k]]) / (2.0 * dy);
+                    }
+
+                    // Computing the z-component.
+                    if z_periodic && (k == 0 || k == dimensions.z - 1) {
+                        ef.z = -(phi[[i, j, 1]] - phi[[i, j, dimensions.z - 2]]) / (2.0 * dz);
+                    } else if k == 0 {
+                        ef.z = -(-3.0 * phi[[i, j, k]] + 4.0 * phi[[i, j, k + 1]]
+                            - phi[[i, j, k + 2]])
+                            / (2.0 * dz);
+                    } else if k == dimensions.z - 1 {
+                        ef.z = -(phi[[i, j, k - 2]] - 4.0 * phi[[i, j, k - 1]]
+                            + 3.0 * phi[[i, j, k]])
+                            / (2.0 * dz);
+                    } else {
+                        ef.z = -(phi[[i, j, k + 1]] - phi[[i, j, k - 1]]) / (2.0 * dz);
+                    }
+                }
+            }
+        }
+
+        electric_field
+    }
+}
+
+#[cfg(feature = "em")]
+impl BoxMesh {
+    /// Returns the magnetic field on the mesh.
+    pub fn magnetic_field(&self) -> &Field<Vec3> {
+        &self.magnetic_field
+    }
+
+    /// Returns the staggered electric field, whose components are stored on the edges of the
+    /// Yee cells at `(i + 1/2, j, k)`, `(i, j + 1/2, k)` and `(i, j, k + 1/2)`.
+    pub fn yee_electric_field(&self) -> &Field<Vec3> {
+        &self.yee_electric_field
+    }
+
+    /// Returns the staggered magnetic field, whose components are stored on the faces of the
+    /// Yee cells at `(i, j + 1/2, k + 1/2)`, `(i + 1/2, j, k + 1/2)` and `(i + 1/2, j + 1/2, k)`.
+    pub fn yee_magnetic_field(&self) -> &Field<Vec3> {
+        &self.yee_magnetic_field
+    }
+
+    /// Returns the staggered electric field for initialization.
+    pub fn yee_electric_field_mut(&mut self) -> &mut Field<Vec3> {
+        &mut self.yee_electric_field
+    }
+
+    /// Returns the staggered magnetic field for initialization.
+    pub fn yee_magnetic_field_mut(&mut self) -> &mut Field<Vec3> {
+        &mut self.yee_magnetic_field
+    }
+
+    /// Advances the electric and magnetic fields by a timestep using the FDTD leapfrog.
+    ///
+    /// The magnetic field is advanced from `t - dt/2` to `t + dt/2` by Faraday's law, after
+    /// which the electric field is advanced from `t` to `t + dt` by Ampere's law driven by the
+    /// node-centered current density. The domain is treated as periodic along every axis, with
+    /// the last node along each axis coinciding with the first. Afterward, both fields are
+    /// averaged onto the nodes so that particles can gather them.
+    pub fn update_em_fields(&mut self, current_density: &Field<Vec3>, dt: f64) {
+        let (dx, dy, dz) = (
+            self.cell_spacings[0],
+            self.cell_spacings[1],
+            self.cell_spacings[2],
+        );
+        let (nx, ny, nz) = (
+            self.dimensions.x - 1,
+            self.dimensions.y - 1,
+            self.dimensions.z - 1,
+        );
+        let c2 = SPEED_OF_LIGHT * SPEED_OF_LIGHT;
+
+        // Advancing the magnetic field with Faraday's law.
+        let e = &self.yee_electric_field;
+        let b = &mut self.yee_magnetic_field;
+
+        for i in 0..nx {
+            for j in 0..ny {
+                for k in 0..nz {
+                    let (ip, jp, kp) = ((i + 1) % nx, (j + 1) % ny, (k + 1) % nz);
+                    let curl = Vec3::new(
+                        (e[[i, jp, k]].z - e[[i, j, k]].z) / dy
+                            - (e[[i, j, kp]].y - e[[i, j, k]].y) / dz,
+                        (e[[i, j, kp]].x - e[[i, j, k]].x) / dz
+                            - (e[[ip, j, k]].z - e[[i, j, k]].z) / dx,
+                        (e[[ip, j, k]].y - e[[i, j, k]].y) / dx
+                            - (e[[i, jp, k]].x - e[[i, j, k]].x) / dy,
+                    );
+                    b[[i, j, k]] -= curl * dt;
+                }
+            }
+        }
+
+        // Advancing the electric field with Ampere's law.
+        let b = &self.yee_magnetic_field;
+        let e = &mut self.yee_electric_field;
+        let j_field = current_density;
+
+        for i in 0..nx {
+            for j in 0..ny {
+                for k in 0..nz {
+                    let (ip, jp, kp) = ((i + 1) % nx, (j + 1) % ny, (k + 1) % nz);
+                    let (im, jm, km) = ((i + nx - 1) % nx, (j + ny - 1) % ny, (k + nz - 1) % nz);
+                    let curl = Vec3::new(
+                        (b[[i, j, k]].z - b[[i, jm, k]].z) / dy
+                            - (b[[i, j, k]].y - b[[i, j, km]].y) / dz,
+                        (b[[i, j, k]].x - b[[i, j, km]].x) / dz
+                            - (b[[i, j, k]].z - b[[im, j, k]].z) / dx,
+                        (b[[i, j, k]].y - b[[im, j, k]].y) / dx
+                            - (b[[i, j, k]].x - b[[i, jm, k]].x) / dy,
+                    );
+
+                    // Averaging the node-centered current density onto the cell edges.
+                    let current = Vec3::new(
+                        0.5 * (j_field[[i, j, k]].x + j_field[[ip, j, k]].x),
+                        0.5 * (j_field[[i, j, k]].y + j_field[[i, jp, k]].y),
+                        0.5 * (j_field[[i, j, k]].z + j_field[[i, j, kp]].z),
+                    );
+
+                    e[[i, j, k]] += (curl * c2 - current * (1.0 / PERMITTIVITY)) * dt;
+                }
+            }
+        }
+
+        Self::copy_periodic_nodes(&mut self.yee_electric_field);
+        Self::copy_periodic_nodes(&mut self.yee_magnetic_field);
+        self.compute_node_em_fields();
+    }
+
+    /// Averages the staggered electric and magnetic fields onto the mesh nodes.
+    fn compute_node_em_fields(&mut self) {
+        let (nx, ny, nz) = (
+            self.dimensions.x - 1,
+            self.dimensions.y - 1,
+            self.dimensions.z - 1,
+        );
+        let e = &self.yee_electric_field;
+        let b = &self.yee_magnetic_field;
+
+        for i in 0..nx {
+            for j in 0..ny {
+                for k in 0..nz {
+                    let (im, jm, km) = ((i + nx - 1) % nx, (j + ny - 1) % ny, (k + nz - 1) % nz);
+
+                    self.electric_field[[i, j, k]] = Vec3::new(
+                        0.5 * (e[[im, j, k]].x + e[[i, j, k]].x),
+                        0.5 * (e[[i, jm, k]].y + e[[i, j, k]].y),
+                        0.5 * (e[[i, j, km]].z + e[[i, j, k]].z),
+                    );
+
+                    self.magnetic_field[[i, j, k]] = Vec3::new(
+                        0.25 * (b[[i, jm, km]].x
+                            + b[[i, j, km]].x
+                            + b[[i, jm, k]].x
+                            + b[[i, j, k]].x),
+                        0.25 * (b[[im, j, km]].y
+                            + b[[i, j, km]].y
+                            + b[[im, j, k]].y
+                            + b[[i, j, k]].y),
+                        0.25 * (b[[im, jm, k]].z
+                            + b[[i, jm, k]].z
+                            + b[[im, j, k]].z
+                            + b[[i, j, k]].z),
+                    );
+                }
+            }
+        }
+
+        Self::copy_periodic_nodes(&mut self.electric_field);
+        Self::copy_periodic_nodes(&mut self.magnetic_field);
+    }
+
+    /// Copies the values on the first node along each axis to the coinciding last node.
+    fn copy_periodic_nodes(field: &mut Field<Vec3>) {
+        let (nx, ny, nz) = field.shape();
+
+        for i in 0..nx {
+            for j in 0..ny {
+                for k in 0..nz {
+                    if i == nx - 1 || j == ny - 1 || k == nz - 1 {
+                        field[[i, j, k]] = field[[i % (nx - 1), j % (ny - 1), k % (nz - 1)]];
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Summarizes the geometry, timestep and boundaries of the mesh over a few lines, leaving out
+/// the fields which make up most of the derived `Debug` output.
+impl fmt::Display for BoxMesh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [dx, dy, dz] = self.cell_spacings;
+
+        writeln!(
+            f,
+            "Box mesh from ({}, {}, {}) m to ({}, {}, {}) m",
+            self.origin.x,
+            self.origin.y,
+            self.origin.z,
+            self.max_bound.x,
+            self.max_bound.y,
+            self.max_bound.z
+        )?;
+        writeln!(
+            f,
+            "Dimensions: {} x {} x {} nodes, cell spacings: {} x {} x {} m",
+            self.dimensions.x, self.dimensions.y, self.dimensions.z, dx, dy, dz
+        )?;
+        writeln!(f, "Timestep: {} s", self.timestep)?;
+
+        write!(f, "Potential boundaries:")?;
+        for face in Face::ALL {
+            write!(f, " {:?} {:?}", face, self.boundary_condition(face))?;
+        }
+        writeln!(f)?;
+
+        write!(f, "Particle boundaries:")?;
+        for face in Face::ALL {
+            write!(f, " {:?} {:?}", face, self.particle_boundary(face))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the value of an attribute of a VTK XML element.
+fn vti_attribute<'a>(element: &'a str, name: &str) -> IoResult<&'a str> {
+    let pattern = format!(" {}=\"", name);
+
+    element
+        .find(&pattern)
+        .map(|start| &element[start + pattern.len()..])
+        .and_then(|value| value.find('"').map(|end| &value[..end]))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Missing {} attribute.", name),
+            )
+        })
+}
+
+/// Parses whitespace separated values from a VTK XML file.
+fn parse_vti_values(text: &str) -> IoResult<Vec<f64>> {
+    text.split_whitespace()
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .map_err(|error| Error::new(ErrorKind::InvalidData, error.to_string()))
+        })
+        .collect()
+}
+
+/// Decodes the 64-bit floats of a binary VTK XML data array, which are base64-encoded behind
+/// a 4-byte header giving their length in bytes, or returns `None` if the data is malformed.
+fn decode_vti_binary(text: &str) -> Option<Vec<f64>> {
+    let bytes = decode_base64(text)?;
+    let header: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+    let data = &bytes[4..];
+
+    if u32::from_le_bytes(header) as usize != data.len() || data.len() % 8 != 0 {
+        return None;
+    }
+
+    Some(
+        data.chunks(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect(),
+    )
+}
+
+/// Returns the values of the named data array of a VTK XML file, either ASCII or binary, if
+/// present, checking that it holds the expected number of values.
+fn vti_data_array(contents: &str, name: &str, num_values: usize) -> IoResult<Option<Vec<f64>>> {
+    let pattern = format!("<DataArray Name=\"{}\"", name);
+
+    let start = match contents.find(&pattern) {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    let data = &contents[start..];
+    let element_end = data.find('>').map_or(data.len(), |end| end + 1);
+    let element = &data[..element_end];
+    let data = &data[element_end..];
+    let data = &data[..data.find("</DataArray>").ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Data array {} is not closed.", name),
+        )
+    })?];
+
+    // Treating arrays without a format attribute as ASCII.
+    let values = if vti_attribute(element, "format").ok() == Some("binary") {
+        decode_vti_binary(data).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Data array {} holds invalid binary data.", name),
+            )
+        })?
+    } else {
+        parse_vti_values(data)?
+    };
+
+    if values.len() != num_values {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Expected {} values in data array {} but found {}.",
+                num_values,
+                name,
+                values.len()
+            ),
+        ));
+    }
+
+    Ok(Some(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn charged_mesh() -> BoxMesh {
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(7, 7, 7),
+            1e-10,
+        );
+
+        for i in 1..6 {
+            for j in 1..6 {
+                for k in 1..6 {
+                    mesh.charge_density[[i, j, k]] = 1e-8;
+                }
+            }
+        }
+
+        mesh
+    }
+
+    #[test]
+    fn test_node_positions() {
+        let origin = Vec3::new(-0.1, -0.1, -0.1);
+        let max_bound = Vec3::new(0.1, 0.1, 0.2);
+        let mesh = BoxMesh::new(origin, max_bound, Dimensions::new(5, 5, 5), 1e-10);
+
+        // The positions are only cached once they are first queried.
+        assert!(mesh.node_positions.get().is_none());
+
+        for i in 0..5 {
+            for j in 0..5 {
+                for k in 0..5 {
+                    assert_eq!(
+                        *mesh.node_position(i, j, k),
+                        mesh.compute_node_position(i, j, k)
+                    );
+                }
+            }
+        }
+        assert!(mesh.node_positions.get().is_some());
+
+        assert_eq!(*mesh.node_position(0, 0, 0), origin);
+
+        let corner = *mesh.node_position(4, 4, 4);
+        assert!((corner.x - max_bound.x).abs() < 1e-12);
+        assert!((corner.y - max_bound.y).abs() < 1e-12);
+        assert!((corner.z - max_bound.z).abs() < 1e-12);
+
+        let corner = *mesh.node_position(4, 0, 4);
+        assert!((corner.x - max_bound.x).abs() < 1e-12);
+        assert!((corner.y - origin.y).abs() < 1e-12);
+        assert!((corner.z - max_bound.z).abs() < 1e-12);
+    }
+
+    fn floating_mesh(pin_mean_potential: bool) -> BoxMesh {
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(9, 9, 9),
+            1e-10,
+        );
+
+        for face in Face::ALL {
+            mesh.set_boundary_condition(face, BoundaryCondition::Neumann);
+        }
+        mesh.set_pin_mean_potential(pin_mean_potential);
+
+        // Placing equal and opposite charge in the two halves of the domain.
+        for i in 1..8 {
+            for j in 1..8 {
+                for k in 1..8 {
+                    if i < 4 {
+                        mesh.charge_density[[i, j, k]] = 1e-9;
+                    } else if i > 4 {
+                        mesh.charge_density[[i, j, k]] = -1e-9;
+                    }
+                }
+            }
+        }
+
+        mesh
+    }
+
+    #[test]
+    fn test_neumann_boundary() {
+        let mut mesh = charged_mesh();
+        mesh.set_boundary_condition(Face::XMax, BoundaryCondition::Neumann);
+        mesh.solve_potential(4000, 1e-6, None).unwrap();
+
+        // The Neumann face follows the adjacent nodes, so the normal gradient vanishes, while
+        // the Dirichlet faces stay grounded.
+        for j in 1..6 {
+            for k in 1..6 {
+                let face = mesh.potential()[[6, j, k]];
+                assert!(face > 0.0);
+                assert_eq!(face, mesh.potential()[[5, j, k]]);
+                assert_eq!(mesh.potential()[[0, j, k]], 0.0);
+            }
+        }
+
+        // The residual accounts for the interior nodes next to the Neumann face.
+        assert!(mesh.potential_residue() < 1e-6);
+
+        // Without a grounded wall there, the potential next to the Neumann face is higher.
+        let mut grounded = charged_mesh();
+        grounded.solve_potential(4000, 1e-6, None).unwrap();
+        assert!(mesh.potential()[[5, 3, 3]] > grounded.potential()[[5, 3, 3]]);
+    }
+
+    #[test]
+    fn test_solid_mask() {
+        use crate::boundary::BoundaryConditions;
+
+        // Pinning a slab through the middle of the x axis to 10 V, between grounded x faces,
+        // with Neumann faces along y and z so that the solution is linear along x.
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(11, 5, 5),
+            1e-10,
+        );
+        mesh.set_boundary_conditions(
+            BoundaryConditions::new()
+                .neumann(Face::YMin)
+                .neumann(Face::YMax)
+                .neumann(Face::ZMin)
+                .neumann(Face::ZMax)
+                .build()
+                .unwrap(),
+        );
+
+        let mut mask = Mask::new(mesh.dimensions());
+        for j in 0..5 {
+            for k in 0..5 {
+                mask[[5, j, k]] = true;
+            }
+        }
+        assert_eq!(mask.count(), 25);
+        mesh.set_solid_mask(mask, 10.0);
+
+        mesh.set_relaxation_parameter(1.8);
+        mesh.solve_potential(4000, 1e-6, None).unwrap();
+        mesh.compute_electric_field();
+
+        assert_eq!(mesh.potential()[[5, 2, 2]], 10.0);
+        assert!((mesh.potential()[[3, 2, 2]] - 6.0).abs() < 1e-3);
+
+        // The field of 200 V/m points away from the slab on either side.
+        let distance = |a: Vec3, b: Vec3| (a - b).magnitude();
+        let before = mesh.electric_field()[[2, 2, 2]];
+        let after = mesh.electric_field()[[8, 2, 2]];
+        assert!(distance(before, Vec3::new(-200.0, 0.0, 0.0)) < 0.1);
+        assert!(distance(after, Vec3::new(200.0, 0.0, 0.0)) < 0.1);
+
+        // A mask without solid nodes reproduces the solve without a mask.
+        let mut masked = charged_mesh();
+        masked.set_solid_mask(Mask::new(masked.dimensions()), 10.0);
+        masked.solve_potential(4000, 1e-6, None).unwrap();
+
+        let mut unmasked = charged_mesh();
+        unmasked.solve_potential(4000, 1e-6, None).unwrap();
+
+        assert_eq!(masked.potential().l2_difference(unmasked.potential()), 0.0);
+    }
+
+    #[test]
+    fn test_pinned_mean_potential() {
+        let mut pinned = floating_mesh(true);
+        pinned.solve_potential(4000, 1e-4, None).unwrap();
+
+        let mut max_potential: f64 = 0.0;
+        for i in 0..9 {
+            max_potential = max_potential.max(pinned.potential()[[i, 4, 4]].abs());
+        }
+
+        assert!(max_potential > 0.0);
+        assert!(pinned.potential().mean().abs() < 1e-6 * max_potential);
+
+        // Without the pin the solution is only determined up to an arbitrary offset.
+        let mut floating = floating_mesh(false);
+        floating.solve_potential(4000, 1e-4, None).unwrap();
+
+        assert!(floating.potential().mean().abs() > 1e-2 * max_potential);
+    }
+
+    #[test]
+    fn test_periodic_boundaries() {
+        use crate::boundary::BoundaryConditions;
+
+        // Uniformly charging a slab between grounded x faces, which is periodic along y and z.
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(11, 7, 5),
+            1e-10,
+        );
+        let conditions = BoundaryConditions::new()
+            .periodic_pair(Axis::Y)
+            .periodic_pair(Axis::Z)
+            .build()
+            .unwrap();
+        mesh.set_boundary_conditions(conditions);
+        mesh.charge_density = mesh.charge_density.clone() + 1e-8;
+
+        mesh.solve_potential(4000, 1e-6, None).unwrap();
+
+        // The potential only varies along x, following the parabola of an infinite slab.
+        for i in 0..11 {
+            let x = 0.01 * i as f64;
+            let expected = 1e-8 / (2.0 * PERMITTIVITY) * x * (0.1 - x);
+
+            for j in 0..7 {
+                for k in 0..5 {
+                    let error = (mesh.potential[[i, j, k]] - expected).abs();
+                    assert!(error < 1e-3 * (expected.abs() + 1e-3));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_potential_cg() {
+        use crate::boundary::BoundaryConditions;
+
+        // Uniformly charging a slab between grounded x faces, which is periodic along y and z,
+        // so that the potential follows the parabola of an infinite slab.
+        let slab_mesh = |potential_solver: PotentialSolver| {
+            let mut mesh = BoxMesh::new(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.1, 0.1, 0.1),
+                Dimensions::new(21, 7, 5),
+                1e-10,
+            );
+            let conditions = BoundaryConditions::new()
+                .periodic_pair(Axis::Y)
+                .periodic_pair(Axis::Z)
+                .build()
+                .unwrap();
+            mesh.set_boundary_conditions(conditions);
+            mesh.set_potential_solver(potential_solver);
+            mesh.charge_density = mesh.charge_density.clone() + 1e-8;
+            mesh
+        };
+
+        let mut sor = slab_mesh(PotentialSolver::SuccessiveOverRelaxation);
+        let sor_report = sor.solve_potential(10000, 1e-6, None).unwrap();
+
+        let mut cg = slab_mesh(PotentialSolver::ConjugateGradient);
+        let cg_report = cg.solve_potential(10000, 1e-6, None).unwrap();
+
+        assert!(cg_report.residual < 1e-6);
+        assert!(cg_report.iterations < sor_report.iterations);
+        assert_eq!(cg_report.max_potential_change, None);
+
+        for i in 0..21 {
+            let x = 0.005 * i as f64;
+            let expected = 1e-8 / (2.0 * PERMITTIVITY) * x * (0.1 - x);
+
+            for j in 0..7 {
+                for k in 0..5 {
+                    let error = (cg.potential[[i, j, k]] - expected).abs();
+                    assert!(error < 1e-3 * (expected.abs() + 1e-3));
+                }
+            }
+        }
+        assert!(cg.potential().l2_difference(sor.potential()) < 1e-3);
+
+        // Neumann faces aren't supported.
+        let mut neumann = charged_mesh();
+        neumann.set_boundary_condition(Face::XMax, BoundaryCondition::Neumann);
+        assert_eq!(
+            neumann.solve_potential_cg(100, 1e-6, None).unwrap_err(),
+            SolverError::UnsupportedBoundary(Face::XMax)
+        );
+
+        // An exactly satisfied system converges even for a zero tolerance.
+        let mut uncharged = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(5, 5, 5),
+            1e-10,
+        );
+        let report = uncharged.solve_potential_cg(100, 0.0, None).unwrap();
+        assert_eq!(report.iterations, 0);
+        assert_eq!(report.residual, 0.0);
+
+        // A fully periodic mesh is singular unless the mean potential is pinned.
+        let mut periodic = slab_mesh(PotentialSolver::ConjugateGradient);
+        periodic.set_periodic(Axis::X);
+        assert_eq!(
+            periodic.solve_potential_cg(100, 1e-6, None).unwrap_err(),
+            SolverError::UnsupportedBoundary(Face::XMin)
+        );
+
+        periodic.charge_density = Field::<f64>::new(periodic.dimensions());
+        periodic.set_pin_mean_potential(true);
+        assert!(periodic.solve_potential_cg(100, 1e-6, None).is_ok());
+    }
+
+    #[test]
+    fn test_periodic_electric_field() {
+        use std::f64::consts::PI;
+
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(21, 5, 5),
+            1e-10,
+        );
+        mesh.set_periodic(Axis::X);
+
+        let wavenumber = 2.0 * PI / 0.1;
+        let mut potential = Field::<f64>::new(mesh.dimensions());
+        for i in 0..21 {
+            for j in 0..5 {
+                for k in 0..5 {
+                    potential[[i, j, k]] = (wavenumber * mesh.node_position(i, j, k).x).sin();
+                }
+            }
+        }
+
+        // At the coinciding end nodes the field is differenced across the wraparound, matching
+        // the central difference of the interior.
+        let electric_field = mesh.electric_field_of(&potential);
+        let central_difference = -2.0 * (wavenumber * 0.005).sin() / 0.01;
+        for j in 0..5 {
+            for k in 0..5 {
+                let first = electric_field[[0, j, k]].x;
+                assert!((first - central_difference).abs() < 1e-9 * central_difference.abs());
+                assert_eq!(first, electric_field[[20, j, k]].x);
+            }
+        }
+
+        // Without the periodic condition, the end nodes fall back to one-sided differences.
+        mesh.set_boundary_condition(Face::XMin, BoundaryCondition::Dirichlet(0.0));
+        let electric_field = mesh.electric_field_of(&potential);
+        assert!((electric_field[[0, 2, 2]].x - central_difference).abs() > 1.0);
+    }
+
+    #[test]
+    fn test_electric_field_of() {
+        let mesh = BoxMesh::new(
+            Vec3::new(-0.1, 0.0, 0.2),
+            Vec3::new(0.1, 0.1, 0.3),
+            Dimensions::new(9, 6, 5),
+            1e-10,
+        );
+
+        // The finite differences are exact for a quadratic potential, even on the boundaries.
+        let analytic_potential = |r: Vec3| r.x * r.x - 2.0 * r.y * r.y + 3.0 * r.x * r.z;
+        let analytic_field = |r: Vec3| Vec3::new(-(2.0 * r.x + 3.0 * r.z), 4.0 * r.y, -3.0 * r.x);
+
+        let mut potential = Field::<f64>::new(mesh.dimensions());
+        for i in 0..9 {
+            for j in 0..6 {
+                for k in 0..5 {
+                    potential[[i, j, k]] = analytic_potential(*mesh.node_position(i, j, k));
+                }
+            }
+        }
+
+        let electric_field = mesh.electric_field_of(&potential);
+
+        for i in 0..9 {
+            for j in 0..6 {
+                for k in 0..5 {
+                    let error =
+                        electric_field[[i, j, k]] - analytic_field(*mesh.node_position(i, j, k));
+                    assert!(error.dot(error).sqrt() < 1e-9);
+
+                    // The stored electric field is left untouched.
+                    assert_eq!(mesh.electric_field()[[i, j, k]], Vec3::new(0.0, 0.0, 0.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_electric_field_filter() {
+        let mut mesh = charged_mesh();
+        mesh.potential[[3, 2, 4]] = 5.0;
+
+        mesh.compute_electric_field();
+        let unfiltered = mesh.electric_field().clone();
+
+        mesh.set_electric_field_filter_passes(0);
+        mesh.compute_electric_field();
+        for i in 0..7 {
+            for j in 0..7 {
+                for k in 0..7 {
+                    assert_eq!(mesh.electric_field()[[i, j, k]], unfiltered[[i, j, k]]);
+                }
+            }
+        }
+
+        mesh.set_electric_field_filter_passes(1);
+        mesh.compute_electric_field();
+
+        let mut max_unfiltered: f64 = 0.0;
+        let mut max_filtered: f64 = 0.0;
+        let mut sum_unfiltered = Vec3::new(0.0, 0.0, 0.0);
+        let mut sum_filtered = Vec3::new(0.0, 0.0, 0.0);
+        for i in 0..7 {
+            for j in 0..7 {
+                for k in 0..7 {
+                    let (a, b) = (unfiltered[[i, j, k]], mesh.electric_field()[[i, j, k]]);
+                    max_unfiltered = max_unfiltered.max(a.dot(a).sqrt());
+                    max_filtered = max_filtered.max(b.dot(b).sqrt());
+                    sum_unfiltered += a;
+                    sum_filtered += b;
+                }
+            }
+        }
+
+        assert!(max_filtered < max_unfiltered);
+
+        let difference = sum_filtered - sum_unfiltered;
+        assert!(difference.dot(difference).sqrt() < 1e-12 * max_unfiltered);
+    }
+
+    #[test]
+    fn test_sinusoidal_boundary() {
+        let timestep = 1e-9;
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(5, 5, 5),
+            timestep,
+        );
+
+        let amplitude = 100.0;
+        let frequency = 13.56e6;
+        let phase = 0.25;
+        mesh.set_boundary_condition(
+            Face::XMax,
+            BoundaryCondition::Sinusoidal {
+                amplitude,
+                frequency,
+                phase,
+            },
+        );
+
+        for step in 0..10 {
+            mesh.solve_potential(100, 1e-3, None).unwrap();
+
+            let time = step as f64 * timestep;
+            let expected =
+                amplitude * (2.0 * std::f64::consts::PI * frequency * time + phase).sin();
+
+            assert!((mesh.current_time() - time).abs() < 1e-20);
+            assert!((mesh.potential()[[4, 2, 2]] - expected).abs() < 1e-9);
+            assert_eq!(mesh.potential()[[0, 2, 2]], 0.0);
+
+            mesh.advance_time();
+        }
+    }
+
+    #[test]
+    fn test_extent_and_domain_volume() {
+        let mesh = BoxMesh::new(
+            Vec3::new(-0.1, 0.2, 0.0),
+            Vec3::new(0.3, 0.25, 0.2),
+            Dimensions::new(9, 3, 5),
+            1e-10,
+        );
+
+        let extent = mesh.extent();
+        assert_eq!(extent, mesh.max_bound() - mesh.origin());
+        assert!((extent.x - 0.4).abs() < 1e-15);
+        assert!((extent.y - 0.05).abs() < 1e-15);
+        assert!((extent.z - 0.2).abs() < 1e-15);
+
+        assert_eq!(mesh.domain_volume(), extent.x * extent.y * extent.z);
+
+        // The volume also matches the sum of the node volumes.
+        let node_volume_sum = mesh.node_volumes().sum();
+        assert!((mesh.domain_volume() - node_volume_sum).abs() < 1e-12 * node_volume_sum);
+    }
+
+    #[test]
+    fn test_net_charge_imbalance() {
+        use crate::constants::{ATOMIC_MASS_UNIT, ELECTRON_MASS, ELEMENTARY_CHARGE};
+
+        let mut mesh = charged_mesh();
+        let mut species = vec![
+            Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            ),
+            Species::new(
+                String::from("e-"),
+                ELECTRON_MASS,
+                -ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            ),
+        ];
+        for s in species.iter_mut() {
+            s.load_particles_box_qs(mesh.origin(), mesh.max_bound(), 1e11, (9, 9, 9), &mesh);
+            s.compute_number_density(&mesh);
+        }
+
+        mesh.compute_charge_density(&species);
+        assert!(mesh.net_charge_imbalance(&species) < 1e-12);
+
+        species.truncate(1);
+        mesh.compute_charge_density(&species);
+        assert!((mesh.net_charge_imbalance(&species) - 1.0).abs() < 1e-12);
+
+        // Electrons alone have no positive charge to normalize by, until a neutralizing
+        // background supplies it.
+        let mut electrons = vec![Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        )];
+        electrons[0].load_particles_box_qs(mesh.origin(), mesh.max_bound(), 1e11, (9, 9, 9), &mesh);
+        electrons[0].compute_number_density(&mesh);
+
+        mesh.compute_charge_density(&electrons);
+        assert_eq!(mesh.net_charge_imbalance(&electrons), f64::INFINITY);
+
+        // The uniform background only cancels the electrons on average, as their deposited
+        // density varies between nodes, leaving a small local imbalance.
+        mesh.set_neutralizing_background(&electrons);
+        mesh.compute_charge_density(&electrons);
+        let imbalance = mesh.net_charge_imbalance(&electrons);
+        assert!(imbalance > 0.0 && imbalance < 0.2);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut mesh = BoxMesh::new(
+            Vec3::new(-0.1, 0.0, 0.5),
+            Vec3::new(0.1, 0.2, 0.75),
+            Dimensions::new(21, 11, 6),
+            2e-10,
+        );
+        mesh.set_boundary_condition(Face::ZMax, BoundaryCondition::Neumann);
+
+        let summary = mesh.to_string();
+        assert!(summary.contains("(-0.1, 0, 0.5)"));
+        assert!(summary.contains("(0.1, 0.2, 0.75)"));
+        assert!(summary.contains("21 x 11 x 6 nodes"));
+        assert!(summary.contains("0.01 x 0.02 x 0.05 m"));
+        assert!(summary.contains("Timestep: 0.0000000002 s"));
+        assert!(summary.contains("ZMax Neumann"));
+        assert!(summary.contains("XMin Reflecting"));
+        assert_eq!(summary.lines().count(), 5);
+    }
+
+    #[test]
+    fn test_set_charge_density() {
+        use std::f64::consts::PI;
+
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(17, 17, 17),
+            1e-10,
+        );
+
+        // The potential of a sinusoidal charge vanishing on the walls is a sinusoid of the
+        // same shape.
+        let wavenumber = PI / 0.1;
+        let exact_potential = |position: &Vec3| {
+            (wavenumber * position.x).sin()
+                * (wavenumber * position.y).sin()
+                * (wavenumber * position.z).sin()
+        };
+
+        let mut charge_density = Field::<f64>::new(mesh.dimensions());
+        for i in 0..17 {
+            for j in 0..17 {
+                for k in 0..17 {
+                    charge_density[[i, j, k]] = 3.0
+                        * wavenumber
+                        * wavenumber
+                        * PERMITTIVITY
+                        * exact_potential(mesh.node_position(i, j, k));
+                }
+            }
+        }
+
+        mesh.set_charge_density(charge_density);
+        mesh.solve_potential(10000, 1e-6, None).unwrap();
+
+        for i in 0..17 {
+            for j in 0..17 {
+                for k in 0..17 {
+                    let exact = exact_potential(mesh.node_position(i, j, k));
+                    assert!((mesh.potential()[[i, j, k]] - exact).abs() < 0.02);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Mesh dimensions must be at least 2 along every axis")]
+    fn test_new_single_node_dimension() {
+        BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(5, 1, 5),
+            1e-10,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Mesh dimensions must be at least 2 along every axis")]
+    fn test_new_zero_dimension() {
+        BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(5, 5, 0),
+            1e-10,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Charge density shape doesn't match the mesh dimensions")]
+    fn test_set_charge_density_shape() {
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(11, 11, 11),
+            1e-10,
+        );
+
+        mesh.set_charge_density(Field::<f64>::new(Dimensions::new(11, 11, 5)));
+    }
+
+    #[test]
+    fn test_neutralizing_background() {
+        use crate::constants::{ELECTRON_MASS, ELEMENTARY_CHARGE};
+
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(11, 11, 11),
+            1e-10,
+        );
+        let mut species = vec![Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        )];
+        species[0].load_particles_box_qs(
+            mesh.origin(),
+            mesh.max_bound(),
+            1e11,
+            (21, 21, 21),
+            &mesh,
+        );
+        species[0].compute_number_density(&mesh);
+
+        mesh.compute_charge_density(&species);
+        mesh.solve_potential(4000, 1e-3, None).unwrap();
+        mesh.compute_electric_field();
+        let unneutralized_energy = mesh.field_energy();
+        let electron_charge = mesh.total_charge().abs();
+
+        mesh.set_neutralizing_background(&species);
+        assert!(mesh.background_charge_density() > 0.0);
+
+        mesh.compute_charge_density(&species);
+        assert!(mesh.total_charge().abs() < 1e-12 * electron_charge);
+
+        mesh.solve_potential(4000, 1e-3, None).unwrap();
+        mesh.compute_electric_field();
+        assert!(mesh.field_energy() < 1e-6 * unneutralized_energy);
+    }
+
+    #[test]
+    fn test_charge_density_of() {
+        use crate::constants::{ATOMIC_MASS_UNIT, ELECTRON_MASS, ELEMENTARY_CHARGE};
+
+        let mut mesh = charged_mesh();
+        let mut species = vec![
+            Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            ),
+            Species::new(
+                String::from("e-"),
+                ELECTRON_MASS,
+                -ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            ),
+        ];
+        species[0].load_particles_box_qs(mesh.origin(), mesh.max_bound(), 1e11, (7, 7, 7), &mesh);
+        species[1].load_particles_box_qs(
+            Vec3::new(0.02, 0.02, 0.02),
+            Vec3::new(0.06, 0.07, 0.08),
+            2e11,
+            (5, 5, 5),
+            &mesh,
+        );
+        for s in species.iter_mut() {
+            s.compute_number_density(&mesh);
+        }
+
+        let contributions: Vec<Field<f64>> =
+            species.iter().map(|s| mesh.charge_density_of(s)).collect();
+        mesh.compute_charge_density(&species);
+
+        for i in 0..7 {
+            for j in 0..7 {
+                for k in 0..7 {
+                    let index = [i, j, k];
+                    let sum = contributions[0][index] + contributions[1][index];
+                    let combined = mesh.charge_density()[index];
+
+                    assert!((sum - combined).abs() <= 1e-12 * combined.abs());
+                }
+            }
+        }
+
+        // The ions alone carry positive charge wherever they are present.
+        assert!(contributions[0][[3, 3, 3]] > 0.0);
+        assert!(contributions[1][[3, 3, 3]] < 0.0);
+    }
+
+    #[test]
+    fn test_sound_crossing_time() {
+        use crate::constants::ATOMIC_MASS_UNIT;
+        use crate::plasma::ion_acoustic_speed;
+
+        let mesh = BoxMesh::new(
+            Vec3::new(-0.1, -0.1, -0.1),
+            Vec3::new(0.1, 0.1, 0.2),
+            Dimensions::new(5, 5, 5),
+            1e-10,
+        );
+
+        // Crossing 0.3 m at about 2.46 km/s for an O+ plasma with 1 eV electrons.
+        let sound_speed = ion_acoustic_speed(1.0, 16.0 * ATOMIC_MASS_UNIT);
+        let crossing_time = mesh.sound_crossing_time(sound_speed);
+
+        assert!((crossing_time - 0.3 / sound_speed).abs() < 1e-18);
+        assert!((crossing_time - 1.22e-4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_estimate_spectral_radius() {
+        let mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(11, 11, 11),
+            1e-10,
+        );
+
+        // For a uniform cubic grid, the Jacobi spectral radius is cos(π / (n - 1)).
+        let expected = (std::f64::consts::PI / 10.0).cos();
+        let estimate = mesh.estimate_spectral_radius(200);
+
+        assert!((estimate - expected).abs() < 1e-3 * expected);
+    }
+
+    #[test]
+    fn test_solve_flop_estimate() {
+        let estimate = |num_nodes: usize| {
+            BoxMesh::new(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.1, 0.1, 0.1),
+                Dimensions::new(num_nodes, num_nodes, num_nodes),
+                1e-10,
+            )
+            .solve_flop_estimate(100)
+        };
+
+        // Doubling the cells along each axis roughly octuples the number of interior nodes.
+        let ratio = estimate(81) as f64 / estimate(41) as f64;
+        assert!((7.5..=8.5).contains(&ratio), "Ratio {}.", ratio);
+    }
+
+    #[test]
+    fn test_sheath_thickness() {
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.2, 0.01, 0.01),
+            Dimensions::new(101, 3, 3),
+            1e-10,
+        );
+        let dx = mesh.cell_spacings()[0];
+
+        // Imposing sheaths decaying exponentially away from both x-walls.
+        let decay_length = 0.005;
+        for i in 0..101 {
+            let x = i as f64 * dx;
+            let distance = x.min(0.2 - x);
+            for j in 0..3 {
+                for k in 0..3 {
+                    mesh.potential[[i, j, k]] = 10.0 * (1.0 - (-distance / decay_length).exp());
+                }
+            }
+        }
+
+        // The potential is within 1% of its bulk value after ln(100) decay lengths.
+        let expected = decay_length * 100.0_f64.ln();
+
+        for wall in [Face::XMin, Face::XMax] {
+            let thickness = mesh.sheath_thickness(wall);
+            assert!((thickness - expected).abs() <= 2.0 * dx);
+        }
+    }
+
+    #[test]
+    fn test_integrate() {
+        let mesh = BoxMesh::new(
+            Vec3::new(-0.1, 0.0, 0.05),
+            Vec3::new(0.1, 0.3, 0.1),
+            Dimensions::new(5, 9, 4),
+            1e-10,
+        );
+        let value = 3.5;
+        let field = Field::<f64>::new(mesh.dimensions()) + value;
+        let volume = 0.2 * 0.3 * 0.05;
+
+        assert!((mesh.integrate(&field) - value * volume).abs() < 1e-12 * value * volume);
+    }
+
+    #[test]
+    fn test_relax_potential_tracks_full_solve() {
+        use crate::constants::{ATOMIC_MASS_UNIT, ELECTRON_MASS, ELEMENTARY_CHARGE};
+        use crate::species::Species;
+
+        let setup = || {
+            let mut mesh = BoxMesh::new(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.1, 0.1, 0.1),
+                Dimensions::new(11, 11, 11),
+                2e-10,
+            );
+            let mut species = vec![
+                Species::new(
+                    String::from("O+"),
+                    16.0 * ATOMIC_MASS_UNIT,
+                    ELEMENTARY_CHARGE,
+                    mesh.dimensions(),
+                ),
+                Species::new(
+                    String::from("e-"),
+                    ELECTRON_MASS,
+                    -ELEMENTARY_CHARGE,
+                    mesh.dimensions(),
+                ),
+            ];
+            species[0].load_particles_box_qs(
+                mesh.origin(),
+                mesh.max_bound(),
+                1e11,
+                (11, 11, 11),
+                &mesh,
+            );
+            species[1].load_particles_box_qs(
+                mesh.origin(),
+                mesh.centroid(),
+                1e11,
+                (11, 11, 11),
+                &mesh,
+            );
+            for s in &mut species {
+                s.compute_number_density(&mesh);
+            }
+            mesh.compute_charge_density(&species);
+            mesh.solve_potential(4000, 1e-3, None).unwrap();
+            mesh.compute_electric_field();
+
+            (mesh, species)
+        };
+
+        let (mut reference_mesh, mut reference_species) = setup();
+        let (mut relaxed_mesh, mut relaxed_species) = setup();
+
+        for _ in 0..20 {
+            for (mesh, species, full_solve) in [
+                (&mut reference_mesh, &mut reference_species, true),
+                (&mut relaxed_mesh, &mut relaxed_species, false),
+            ] {
+                for s in species.iter_mut() {
+                    s.advance(mesh);
+                    s.compute_number_density(mesh);
+                }
+                mesh.compute_charge_density(species);
+
+                if full_solve {
+                    mesh.solve_potential(4000, 1e-3, None).unwrap();
+                } else {
+                    mesh.relax_potential(5);
+                }
+                mesh.compute_electric_field();
+            }
+        }
+
+        // Comparing the electron centroids relative to the distance the electrons have drifted.
+        let initial = setup().1[1].centroid().unwrap();
+        let reference = reference_species[1].centroid().unwrap();
+        let relaxed = relaxed_species[1].centroid().unwrap();
+
+        let distance = |a: Vec3, b: Vec3| (a - b).magnitude();
+        let deviation = distance(reference, relaxed);
+        let displacement = distance(reference, initial);
+
+        assert!(displacement > 0.0);
+        assert!(
+            deviation < 0.05 * displacement,
+            "Deviation {} exceeds 5% of displacement {}.",
+            deviation,
+            displacement
+        );
+    }
+
+    #[test]
+    fn test_solve_potential_converges() {
+        let mut mesh = charged_mesh();
+        let report = mesh.solve_potential(4000, 1e-3, None).unwrap();
+
+        assert!(report.iterations < 4000);
+        assert!(report.residual < 1e-3);
+    }
+
+    #[test]
+    fn test_solve_potential_residual_history() {
+        let mut mesh = charged_mesh();
+        mesh.set_relaxation_parameter(1.0);
+
+        let mut history = Vec::new();
+        let report = mesh
+            .solve_potential(4000, 1e-3, Some(&mut history))
+            .unwrap();
+
+        assert!(history.len() > 1);
+        assert!(history.windows(2).all(|pair| pair[1].1 < pair[0].1));
+        assert!(history.last().unwrap().1 < 1e-3);
+
+        // The report matches the final convergence check.
+        let &(last_iteration, last_residual) = history.last().unwrap();
+        assert_eq!(report.iterations, last_iteration + 1);
+        assert_eq!(report.residual, last_residual);
+
+        // A solve which runs out of iterations still records its history, ending with the
+        // residual reported by the error.
+        let mut mesh = charged_mesh();
+        let mut history = Vec::new();
+        match mesh.solve_potential(60, 0.0, Some(&mut history)) {
+            Err(SolverError::NotConverged {
+                iterations,
+                residual,
+            }) => {
+                assert_eq!(iterations, 60);
+                assert_eq!(history.len(), 3);
+                assert_eq!(history.last().unwrap(), &(59, residual));
+            }
+            result => panic!("Expected the solve not to converge, but got {:?}.", result),
+        }
+    }
+
+    #[test]
+    fn test_max_potential_change() {
+        let mut mesh = charged_mesh();
+        mesh.set_relaxation_parameter(1.0);
+
+        let changes: Vec<f64> = (0..20).map(|_| mesh.relax_potential(1)).collect();
+        assert!(changes.windows(2).all(|pair| pair[1] < pair[0]));
+
+        let mut mesh = charged_mesh();
+        let report = mesh.solve_potential(4000, 1e-3, None).unwrap();
+        let max_potential_change = report.max_potential_change.unwrap();
+
+        // The potential peaks at around half a volt, so the solution is settled to a microvolt.
+        assert!(max_potential_change > 0.0);
+        assert!(max_potential_change < 1e-6);
+    }
+
+    #[test]
+    fn test_load_from_vti() {
+        use crate::output::{write_vti, VtkFieldSelection, VtkFormat};
+
+        let mut mesh = BoxMesh::new(
+            Vec3::new(-0.1, 0.0, 0.05),
+            Vec3::new(0.1, 0.1, 0.2),
+            Dimensions::new(6, 5, 4),
+            1e-10,
+        );
+        for i in 1..5 {
+            for j in 1..4 {
+                mesh.charge_density[[i, j, 1]] = 1e-8 * (i + j) as f64;
+            }
+        }
+        mesh.solve_potential(4000, 1e-6, None).unwrap();
+        mesh.compute_electric_field();
+
+        for format in [VtkFormat::Ascii, VtkFormat::Binary] {
+            let path = std::env::temp_dir().join(format!("field_{}.vti", std::process::id()));
+            let mut file = fs::File::create(&path).unwrap();
+            write_vti(&mut file, &mesh, &[], VtkFieldSelection::ALL, format).unwrap();
+
+            let loaded = BoxMesh::load_from_vti(&path, 1e-10).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded.potential().shape(), mesh.potential().shape());
+            assert!((loaded.max_bound() - mesh.max_bound()).magnitude() < 1e-12);
+
+            for i in 0..6 {
+                for j in 0..5 {
+                    for k in 0..4 {
+                        let index = [i, j, k];
+                        let tolerance = 1e-12 * mesh.potential().max_with_index().0;
+
+                        assert!(
+                            (loaded.potential()[index] - mesh.potential()[index]).abs() < tolerance
+                        );
+                        assert!(
+                            (loaded.charge_density()[index] - mesh.charge_density()[index]).abs()
+                                < 1e-20
+                        );
+                        assert_eq!(loaded.electric_field()[index], mesh.electric_field()[index]);
+                    }
+                }
+            }
+
+            // Binary arrays hold the exact values.
+            if format == VtkFormat::Binary {
+                assert_eq!(loaded.potential().l2_difference(mesh.potential()), 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_from_vti_malformed_extent() {
+        let path = std::env::temp_dir().join(format!("extent_{}.vti", std::process::id()));
+
+        for extent in [
+            "0 0 0 4 0 4",
+            "4 0 0 4 0 4",
+            "-1 4 0 4 0 4",
+            "0 2.5 0 4 0 4",
+            "0 NaN 0 4 0 4",
+            "0 1e300 0 4 0 4",
+            "0 100000 0 100000 0 100000",
+        ] {
+            fs::write(
+                &path,
+                format!(
+                    "<ImageData WholeExtent=\"{}\" Origin=\"0 0 0\" Spacing=\"0.1 0.1 0.1\">",
+                    extent
+                ),
+            )
+            .unwrap();
+
+            let error = BoxMesh::load_from_vti(&path, 1e-10).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData, "{}", extent);
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_potential_warm_start() {
+        let path = std::env::temp_dir().join(format!("potential_{}.csv", std::process::id()));
+        let new_mesh = || {
+            let mut mesh = BoxMesh::new(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.1, 0.1, 0.1),
+                Dimensions::new(21, 21, 21),
+                1e-10,
+            );
+            mesh.charge_density = mesh.charge_density.clone() + 1e-8;
+            mesh
+        };
+
+        let mut cold = new_mesh();
+        let cold_report = cold.solve_potential(4000, 1e-2, None).unwrap();
+        cold.save_potential(&path).unwrap();
+
+        let mut warm = new_mesh();
+        warm.load_potential(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let warm_report = warm.solve_potential(4000, 1e-2, None).unwrap();
+        assert!(warm_report.iterations * 4 < cold_report.iterations);
+
+        // Rejecting a potential saved from a mesh of different dimensions.
+        let other = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(11, 21, 21),
+            1e-10,
+        );
+        other.save_potential(&path).unwrap();
+        let result = warm.load_potential(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+
+        // Rejecting a potential with more values than the mesh has nodes.
+        warm.save_potential(&path).unwrap();
+        let contents = fs::read_to_string(&path).unwrap() + "1.0\n";
+        fs::write(&path, contents).unwrap();
+        let result = warm.load_potential(&path);
+        fs::remove_file(&path).unwrap();
+
+        let error = result.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("Expected 9261"), "{}", error);
+    }
+
+    #[test]
+    fn test_solve_potential_not_converged() {
+        let mut mesh = charged_mesh();
+        let result = mesh.solve_potential(10, 0.0, None);
+
+        match result {
+            Err(SolverError::NotConverged {
+                iterations,
+                residual,
+            }) => {
+                assert_eq!(iterations, 10);
+                assert!(residual.is_finite());
+                assert!(residual > 0.0);
+            }
+            _ => panic!("Expected NotConverged, got {:?}.", result),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Relaxation parameter 2 is outside of the interval (0, 2)")]
+    fn test_set_relaxation_parameter_out_of_range() {
+        let mut mesh = charged_mesh();
+        mesh.set_relaxation_parameter(2.0);
+    }
+
+    #[test]
+    fn test_solve_potential_non_finite_residual() {
+        let mut mesh = charged_mesh();
+        mesh.charge_density[[3, 3, 3]] = f64::NAN;
+
+        let result = mesh.solve_potential(4000, 1e-3, None);
+
+        assert_eq!(result.unwrap_err(), SolverError::NonFiniteResidual);
+    }
+
+    #[cfg(feature = "em")]
+    #[test]
+    fn test_em_plane_wave_propagation() {
+        use std::f64::consts::PI;
+
+        let length = 1.0;
+        let num_nodes = 129;
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(length, 2.0 * length / 128.0, 2.0 * length / 128.0),
+            Dimensions::new(num_nodes, 3, 3),
+            0.0,
+        );
+
+        let dx = mesh.cell_spacings()[0];
+        let dt = 0.5 * dx / SPEED_OF_LIGHT;
+        let wavenumber = 2.0 * PI / length;
+        let amplitude = 1.0;
+
+        // Initializing a plane wave traveling in the +x direction, with the magnetic field
+        // offset by half a timestep behind the electric field.
+        for i in 0..num_nodes {
+            for j in 0..3 {
+                for k in 0..3 {
+                    let x = i as f64 * dx;
+                    mesh.yee_electric_field_mut()[[i, j, k]].y = amplitude * (wavenumber * x).sin();
+                    mesh.yee_magnetic_field_mut()[[i, j, k]].z = amplitude / SPEED_OF_LIGHT
+                        * (wavenumber * (x + 0.5 * dx + 0.5 * SPEED_OF_LIGHT * dt)).sin();
+                }
+            }
+        }
+
+        let current_density = Field::<Vec3>::new(mesh.dimensions());
+        let num_steps = 256;
+        for _ in 0..num_steps {
+            mesh.update_em_fields(&current_density, dt);
+        }
+
+        // Comparing against the analytic wave after traveling a quarter of the domain.
+        let time = num_steps as f64 * dt;
+        let mut max_error: f64 = 0.0;
+        for i in 0..num_nodes {
+            let x = i as f64 * dx;
+            let expected = amplitude * (wavenumber * (x - SPEED_OF_LIGHT * time)).sin();
+            let error = (mesh.electric_field()[[i, 1, 1]].y - expected).abs();
+            max_error = max_error.max(error);
+        }
+
+        assert!(max_error < 0.01 * amplitude, "Maximum error: {}", max_error);
     }
 }
@@ -0,0 +1,333 @@
+use crate::boundary::{Axis, BoundaryCondition, Face};
+use crate::field::Field;
+use crate::mesh::{BoxMesh, Dimensions};
+use crate::solver::{self, SolverError, SolverReport};
+use crate::vector::Vec3;
+
+/// Represents a box domain decomposed into blocks along an axis, each of which is a box mesh.
+///
+/// Neighboring blocks overlap by two cells around the node plane they share, so that the last
+/// node of the lower block coincides with the second interior node of the upper block and
+/// vice versa. These ghost nodes are exchanged between blocks after each solver sweep.
+pub struct MultiBlockMesh {
+    /// The blocks covering the domain, ordered along the decomposition axis.
+    blocks: Vec<BoxMesh>,
+    /// The axis along which the domain is decomposed.
+    axis: Axis,
+    /// The index of the first node of each block within the whole domain.
+    block_offsets: Vec<usize>,
+    /// The index of the node plane shared by each pair of neighboring blocks.
+    shared_nodes: Vec<usize>,
+    /// The number of nodes along each axis of the whole domain.
+    dimensions: Dimensions,
+}
+
+impl MultiBlockMesh {
+    /// Creates a new multi-block mesh covering the box between the origin and maximum bound,
+    /// split along an axis at each of the provided node indices.
+    ///
+    /// Shared nodes must be interior to the domain and increasing, with at least two nodes
+    /// between consecutive shared nodes.
+    pub fn new(
+        origin: Vec3,
+        max_bound: Vec3,
+        dimensions: Dimensions,
+        timestep: f64,
+        axis: Axis,
+        shared_nodes: &[usize],
+    ) -> Self {
+        let axis_index = axis.index();
+        let num_nodes = [dimensions.x, dimensions.y, dimensions.z][axis_index];
+        let spacing = (max_bound[axis_index] - origin[axis_index]) / (num_nodes - 1) as f64;
+
+        let mut previous_node = 0;
+        for &node in shared_nodes {
+            assert!(
+                node >= previous_node + 2 && node + 2 <= num_nodes,
+                "Shared node {} is too close to the previous shared node or mesh boundary.",
+                node
+            );
+            previous_node = node;
+        }
+
+        // Extending each block by a node past the planes it shares with its neighbors.
+        let mut block_offsets = vec![0];
+        block_offsets.extend(shared_nodes.iter().map(|node| node - 1));
+
+        let mut block_ends: Vec<usize> = shared_nodes.iter().map(|node| node + 1).collect();
+        block_ends.push(num_nodes - 1);
+
+        let num_blocks = block_ends.len();
+        let mut blocks = Vec::with_capacity(num_blocks);
+
+        for (index, (&start, &end)) in block_offsets.iter().zip(&block_ends).enumerate() {
+            let mut block_origin = origin;
+            let mut block_max_bound = max_bound;
+            block_origin[axis_index] = origin[axis_index] + start as f64 * spacing;
+            block_max_bound[axis_index] = origin[axis_index] + end as f64 * spacing;
+
+            let mut block_dimensions = dimensions;
+            match axis {
+                Axis::X => block_dimensions.x = end - start + 1,
+                Axis::Y => block_dimensions.y = end - start + 1,
+                Axis::Z => block_dimensions.z = end - start + 1,
+            }
+
+            let mut block = BoxMesh::new(block_origin, block_max_bound, block_dimensions, timestep);
+
+            let (min_face, max_face) = axis.faces();
+            if index > 0 {
+                block.set_boundary_condition(min_face, BoundaryCondition::Interface);
+            }
+            if index < num_blocks - 1 {
+                block.set_boundary_condition(max_face, BoundaryCondition::Interface);
+            }
+
+            blocks.push(block);
+        }
+
+        MultiBlockMesh {
+            blocks,
+            axis,
+            block_offsets,
+            shared_nodes: shared_nodes.to_vec(),
+            dimensions,
+        }
+    }
+
+    /// Returns the blocks covering the domain, ordered along the decomposition axis.
+    pub fn blocks(&self) -> &[BoxMesh] {
+        &self.blocks
+    }
+
+    /// Sets the boundary condition on the potential at a face of the whole domain.
+    pub fn set_boundary_condition(&mut self, face: Face, boundary_condition: BoundaryCondition) {
+        let num_blocks = self.blocks.len();
+
+        for (index, block) in self.blocks.iter_mut().enumerate() {
+            let on_face = face.axis() != self.axis
+                || (face.is_min() && index == 0)
+                || (!face.is_min() && index == num_blocks - 1);
+
+            if on_face {
+                block.set_boundary_condition(face, boundary_condition);
+            }
+        }
+    }
+
+    /// Copies the charge density of the whole domain onto the blocks.
+    pub fn set_charge_density(&mut self, charge_density: &Field<f64>) {
+        let (nx, ny, nz) = charge_density.shape();
+        assert_eq!(
+            [nx, ny, nz],
+            [self.dimensions.x, self.dimensions.y, self.dimensions.z],
+            "Charge density shape doesn't match the mesh dimensions."
+        );
+
+        let axis = self.axis.index();
+
+        for (block, &offset) in self.blocks.iter_mut().zip(&self.block_offsets) {
+            let (bx, by, bz) = block.charge_density().shape();
+
+            for i in 0..bx {
+                for j in 0..by {
+                    for k in 0..bz {
+                        let mut index = [i, j, k];
+                        index[axis] += offset;
+                        block.charge_density_mut()[[i, j, k]] = charge_density[index];
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the potential over the whole domain, assembled from the blocks.
+    ///
+    /// Each shared node plane is taken from the lower of the two blocks sharing it.
+    pub fn potential(&self) -> Field<f64> {
+        let mut potential = Field::<f64>::new(self.dimensions);
+        let axis = self.axis.index();
+
+        for i in 0..self.dimensions.x {
+            for j in 0..self.dimensions.y {
+                for k in 0..self.dimensions.z {
+                    let index = [i, j, k];
+
+                    // Finding the first block whose upper shared node lies beyond the node.
+                    let block_index = self
+                        .shared_nodes
+                        .iter()
+                        .position(|&node| index[axis] <= node)
+                        .unwrap_or(self.shared_nodes.len());
+
+                    let mut block_node = index;
+                    block_node[axis] -= self.block_offsets[block_index];
+                    potential[index] = self.blocks[block_index].potential()[block_node];
+                }
+            }
+        }
+
+        potential
+    }
+
+    /// Solves the potential over the whole domain with an additive Schwarz method.
+    ///
+    /// Each iteration applies a sweep to every block independently, after which the ghost
+    /// nodes are exchanged between neighboring blocks. Convergence is reached once the
+    /// largest residue of any block falls below the tolerance. When provided,
+    /// `residual_history` is extended with the iteration and residual of each convergence
+    /// check, and the report holds the largest change of the potential over the final sweep.
+    pub fn solve_potential(
+        &mut self,
+        max_solver_iterations: usize,
+        tolerance: f64,
+        residual_history: Option<&mut Vec<(usize, f64)>>,
+    ) -> Result<SolverReport, SolverError> {
+        for block in &self.blocks {
+            solver::validate_relaxation_parameter(block.relaxation_parameter())?;
+        }
+
+        self.exchange_ghost_nodes();
+
+        solver::solve_sor(
+            self,
+            max_solver_iterations,
+            tolerance,
+            residual_history,
+            |mesh| {
+                let max_potential_change = mesh
+                    .blocks
+                    .iter_mut()
+                    .map(|block| block.relax_potential(1))
+                    .fold(0.0, f64::max);
+                mesh.exchange_ghost_nodes();
+
+                max_potential_change
+            },
+            |mesh| {
+                mesh.blocks
+                    .iter()
+                    .map(BoxMesh::potential_residue)
+                    .fold(0.0, f64::max)
+            },
+        )
+    }
+
+    /// Copies the potential on the nodes neighboring each shared node plane onto the
+    /// coinciding ghost nodes of the adjacent block.
+    fn exchange_ghost_nodes(&mut self) {
+        let axis = self.axis.index();
+        let u_axis = (axis + 1) % 3;
+        let v_axis = (axis + 2) % 3;
+        let dimensions = [self.dimensions.x, self.dimensions.y, self.dimensions.z];
+
+        for (lower, &shared_node) in self.shared_nodes.iter().enumerate() {
+            let upper = lower + 1;
+            let lower_offset = self.block_offsets[lower];
+            let upper_offset = self.block_offsets[upper];
+
+            for u in 0..dimensions[u_axis] {
+                for v in 0..dimensions[v_axis] {
+                    let mut index = [0; 3];
+                    index[u_axis] = u;
+                    index[v_axis] = v;
+
+                    // Filling the last node of the lower block from the upper block.
+                    let mut ghost = index;
+                    ghost[axis] = shared_node + 1 - lower_offset;
+                    let mut source = index;
+                    source[axis] = shared_node + 1 - upper_offset;
+                    let value = self.blocks[upper].potential()[source];
+                    self.blocks[lower].potential_mut()[ghost] = value;
+
+                    // Filling the first node of the upper block from the lower block.
+                    let mut ghost = index;
+                    ghost[axis] = shared_node - 1 - upper_offset;
+                    let mut source = index;
+                    source[axis] = shared_node - 1 - lower_offset;
+                    let value = self.blocks[lower].potential()[source];
+                    self.blocks[upper].potential_mut()[ghost] = value;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a charge density concentrated in a cube off the center of the domain.
+    fn charge_density(dimensions: Dimensions) -> Field<f64> {
+        let mut charge_density = Field::<f64>::new(dimensions);
+
+        for i in 6..12 {
+            for j in 3..8 {
+                for k in 4..7 {
+                    charge_density[[i, j, k]] = 1e-8;
+                }
+            }
+        }
+
+        charge_density
+    }
+
+    #[test]
+    fn test_block_geometry() {
+        let mesh = MultiBlockMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.2, 0.1, 0.1),
+            Dimensions::new(21, 11, 11),
+            1e-10,
+            Axis::X,
+            &[10],
+        );
+
+        let blocks = mesh.blocks();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].dimensions().x, 12);
+        assert_eq!(blocks[1].dimensions().x, 12);
+
+        // Both blocks share the node plane at x = 0.1 and overlap by a node on either side.
+        assert!((blocks[0].node_position(10, 0, 0).x - 0.1).abs() < 1e-12);
+        assert!((blocks[1].node_position(1, 0, 0).x - 0.1).abs() < 1e-12);
+        assert!((blocks[0].max_bound().x - 0.11).abs() < 1e-12);
+        assert!((blocks[1].origin().x - 0.09).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_two_block_solve_matches_single_mesh() {
+        let origin = Vec3::new(0.0, 0.0, 0.0);
+        let max_bound = Vec3::new(0.2, 0.1, 0.1);
+        let dimensions = Dimensions::new(21, 11, 11);
+        let tolerance = 1e-4;
+
+        let mut single = BoxMesh::new(origin, max_bound, dimensions, 1e-10);
+        single.set_boundary_condition(Face::XMin, BoundaryCondition::Dirichlet(2.0));
+        *single.charge_density_mut() = charge_density(dimensions);
+        single.solve_potential(10000, tolerance, None).unwrap();
+
+        let mut multi = MultiBlockMesh::new(origin, max_bound, dimensions, 1e-10, Axis::X, &[10]);
+        multi.set_boundary_condition(Face::XMin, BoundaryCondition::Dirichlet(2.0));
+        multi.set_charge_density(&charge_density(dimensions));
+        let mut history = Vec::new();
+        let report = multi
+            .solve_potential(10000, tolerance, Some(&mut history))
+            .unwrap();
+        assert_eq!(history.last().unwrap().1, report.residual);
+        assert!(report.max_potential_change.unwrap() > 0.0);
+
+        let potential = multi.potential();
+        let (_, max_index) = single.potential().max_with_index();
+        let max_potential = single.potential()[[max_index.0, max_index.1, max_index.2]];
+
+        for i in 0..21 {
+            for j in 0..11 {
+                for k in 0..11 {
+                    let error = (potential[[i, j, k]] - single.potential()[[i, j, k]]).abs();
+                    assert!(error < 1e-3 * max_potential);
+                }
+            }
+        }
+    }
+}
@@ -1,92 +1,1301 @@
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Display;
 use std::fs;
-use std::fs::File;
-use std::io::{Result, Write};
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, Result, Write};
+use std::ops::{AddAssign, BitOr, Mul};
 use std::path::Path;
 
+use num_traits::identities::Zero;
+
+use crate::constants::ELEMENTARY_CHARGE;
+use crate::field::Field;
 use crate::mesh::BoxMesh;
 use crate::species::Species;
+use crate::vector::Vec3;
+
+/// Selects which data arrays are written to VTI files.
+///
+/// Selections can be combined with `|`, e.g. `VtkFieldSelection::POTENTIAL |
+/// VtkFieldSelection::SPECIES_DENSITIES`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VtkFieldSelection(u8);
+
+impl VtkFieldSelection {
+    /// Selects no data arrays.
+    pub const NONE: VtkFieldSelection = VtkFieldSelection(0);
+    /// Selects the node volumes, which are static over a simulation.
+    pub const NODE_VOLUMES: VtkFieldSelection = VtkFieldSelection(1 << 0);
+    /// Selects the potential.
+    pub const POTENTIAL: VtkFieldSelection = VtkFieldSelection(1 << 1);
+    /// Selects the charge density.
+    pub const CHARGE_DENSITY: VtkFieldSelection = VtkFieldSelection(1 << 2);
+    /// Selects the number density of every species.
+    pub const SPECIES_DENSITIES: VtkFieldSelection = VtkFieldSelection(1 << 3);
+    /// Selects the electric field.
+    pub const ELECTRIC_FIELD: VtkFieldSelection = VtkFieldSelection(1 << 4);
+    /// Selects every data array.
+    pub const ALL: VtkFieldSelection = VtkFieldSelection(0b11111);
+
+    /// Returns whether every data array in the other selection is also in this selection.
+    pub fn contains(self, other: VtkFieldSelection) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for VtkFieldSelection {
+    type Output = VtkFieldSelection;
+
+    fn bitor(self, other: VtkFieldSelection) -> VtkFieldSelection {
+        VtkFieldSelection(self.0 | other.0)
+    }
+}
+
+/// Represents the encoding of the data arrays within VTI files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VtkFormat {
+    /// Writes values as text, which is readable but large and slow to parse.
+    #[default]
+    Ascii,
+    /// Writes values as base64-encoded little-endian floats, preceded by a 4-byte header
+    /// holding their length in bytes, which suits meshes beyond a few dozen nodes per side.
+    Binary,
+}
+
+impl fmt::Display for VtkFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VtkFormat::Ascii => write!(f, "ascii"),
+            VtkFormat::Binary => write!(f, "binary"),
+        }
+    }
+}
+
+/// Identifies an output file, either by a running index or by the simulation time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputStamp {
+    /// Names the file by an index, zero-padded to five digits.
+    Index(usize),
+    /// Names the file by a simulation time, in seconds, rounded to whole nanoseconds and
+    /// zero-padded to nine digits, e.g. `t000002000ns` for 2 µs.
+    ///
+    /// This conveys the physical time when outputs happen at irregular intervals, such as
+    /// with an adaptive timestep, while keeping files in order when sorted by name.
+    Time(f64),
+}
+
+impl fmt::Display for OutputStamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputStamp::Index(index) => write!(f, "{:05}", index),
+            OutputStamp::Time(time) => write!(f, "t{:09}ns", (time * 1e9).round() as u64),
+        }
+    }
+}
+
+/// Outputs the current state of the simulation to a VTI file, writing only the selected
+/// data arrays in the provided format.
+pub fn vtk_output(
+    mesh: &BoxMesh,
+    species: &[Species],
+    file_index: usize,
+    fields: VtkFieldSelection,
+    format: VtkFormat,
+) -> Result<()> {
+    vtk_output_stamped(
+        mesh,
+        species,
+        OutputStamp::Index(file_index),
+        fields,
+        format,
+    )
+}
+
+/// Outputs the current state of the simulation to a VTI file named by the provided stamp,
+/// writing only the selected data arrays in the provided format.
+pub fn vtk_output_stamped(
+    mesh: &BoxMesh,
+    species: &[Species],
+    stamp: OutputStamp,
+    fields: VtkFieldSelection,
+    format: VtkFormat,
+) -> Result<()> {
+    // Checking before creating the file, so that a diverged simulation leaves no output.
+    check_finite_fields(mesh, species, fields)?;
 
-/// Outputs the current state of the simulation to a VTI file.
-pub fn vtk_output(mesh: &BoxMesh, species: &Vec<Species>, file_index: usize) -> Result<()> {
     // Creating the results directory, if it doesn't exist.
     fs::create_dir_all("results")?;
 
-    let filepath = format!("results/field_{:05}.vti", file_index);
+    let filepath = format!("results/field_{}.vti", stamp);
     let path = Path::new(&filepath);
 
-    let mut vti_file = File::create(path).unwrap();
+    let mut vti_file = File::create(path)?;
+
+    write_vti(&mut vti_file, mesh, species, fields, format)
+}
+
+/// Outputs a ParaView collection, `results/simulation.pvd`, linking the VTI files written by
+/// `vtk_output` with the provided indices as a time series.
+///
+/// This is meant to be called at the end of a run, with the iterations at which output was
+/// written and the timestep of the mesh.
+pub fn write_pvd_collection(file_indices: &[usize], timestep: f64) -> Result<()> {
+    // Creating the results directory, if it doesn't exist.
+    fs::create_dir_all("results")?;
+
+    let mut pvd_file = File::create("results/simulation.pvd")?;
+
+    write_pvd(&mut pvd_file, file_indices, timestep)
+}
+
+/// Writes a ParaView collection of the VTI files with the provided indices, each at the
+/// simulation time of its index, to be placed alongside the files.
+pub fn write_pvd<W: Write>(writer: &mut W, file_indices: &[usize], timestep: f64) -> Result<()> {
+    writeln!(writer, "<?xml version=\"1.0\"?>")?;
+    writeln!(
+        writer,
+        "<VTKFile type=\"Collection\" version=\"0.1\" byte_order=\"LittleEndian\">"
+    )?;
+    writeln!(writer, "<Collection>")?;
+
+    for &index in file_indices {
+        writeln!(
+            writer,
+            "<DataSet timestep=\"{}\" group=\"\" part=\"0\" file=\"field_{}.vti\"/>",
+            index as f64 * timestep,
+            OutputStamp::Index(index)
+        )?;
+    }
+
+    writeln!(writer, "</Collection>")?;
+    writeln!(writer, "</VTKFile>")?;
+
+    Ok(())
+}
+
+/// Outputs the number density of each species to its own VTI file, named after the species.
+///
+/// This complements `vtk_output`, whose combined file can leave out the species densities
+/// by not selecting `VtkFieldSelection::SPECIES_DENSITIES`, so that each density can be
+/// loaded selectively.
+pub fn vtk_species_density_output(
+    mesh: &BoxMesh,
+    species: &[Species],
+    file_index: usize,
+) -> Result<()> {
+    write_species_density_vtis(Path::new("results"), mesh, species, file_index)
+}
+
+/// Writes the number density of each species to its own VTI file within a directory.
+fn write_species_density_vtis(
+    directory: &Path,
+    mesh: &BoxMesh,
+    species: &[Species],
+    file_index: usize,
+) -> Result<()> {
+    check_finite_fields(mesh, species, VtkFieldSelection::SPECIES_DENSITIES)?;
+
+    // Creating the directory, if it doesn't exist.
+    fs::create_dir_all(directory)?;
+
+    for s in species {
+        let path = directory.join(format!("density_{}_{:05}.vti", s.name(), file_index));
+        let mut vti_file = File::create(path)?;
+
+        write_vti(
+            &mut vti_file,
+            mesh,
+            std::slice::from_ref(s),
+            VtkFieldSelection::SPECIES_DENSITIES,
+            VtkFormat::Ascii,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Appends the runtime diagnostics of a simulation iteration to `results/runtime_diags.csv`,
+/// starting the file afresh with a header row at the first iteration.
+pub fn diagnostic(mesh: &BoxMesh, species: &[Species], iteration: usize) -> Result<()> {
+    // Creating the results directory, if it doesn't exist.
+    fs::create_dir_all("results")?;
+
+    append_diagnostic(
+        Path::new("results/runtime_diags.csv"),
+        mesh,
+        species,
+        iteration,
+    )
+}
+
+/// Appends a row of runtime diagnostics to a CSV file, truncating it and writing the header
+/// row when the iteration is zero.
+///
+/// Each row holds the iteration, the energy stored in the electric field, the kinetic energy
+/// of every species, the number of macroparticles in each species and the L2 norm of the
+/// electric field over the nodes.
+fn append_diagnostic(
+    path: &Path,
+    mesh: &BoxMesh,
+    species: &[Species],
+    iteration: usize,
+) -> Result<()> {
+    let mut file = if iteration == 0 {
+        let mut file = File::create(path)?;
+
+        write!(file, "iteration,potential_energy,kinetic_energy")?;
+        for s in species {
+            write!(file, ",count_{}", s.name())?;
+        }
+        writeln!(file, ",electric_field_l2_norm")?;
+
+        file
+    } else {
+        OpenOptions::new().append(true).create(true).open(path)?
+    };
+
+    let kinetic_energy: f64 = species.iter().map(Species::kinetic_energy).sum();
+
+    let electric_field = mesh.electric_field();
+    let (nx, ny, nz) = electric_field.shape();
+    let mut squared_norm = 0.0;
+
+    for i in 0..nx {
+        for j in 0..ny {
+            for k in 0..nz {
+                let ef = electric_field[[i, j, k]];
+                squared_norm += ef.dot(ef);
+            }
+        }
+    }
+
+    write!(
+        file,
+        "{},{},{}",
+        iteration,
+        mesh.field_energy(),
+        kinetic_energy
+    )?;
+    for s in species {
+        write!(file, ",{}", s.particles().len())?;
+    }
+    writeln!(file, ",{}", squared_norm.sqrt())?;
+
+    Ok(())
+}
+
+/// Returns an error naming the first selected data array holding a NaN or infinite value,
+/// which indicates that the simulation has diverged.
+fn check_finite_fields(
+    mesh: &BoxMesh,
+    species: &[Species],
+    fields: VtkFieldSelection,
+) -> Result<()> {
+    let non_finite = |name: &str| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Data array {} holds a non-finite value, so the simulation has likely diverged.",
+                name
+            ),
+        )
+    };
+
+    if fields.contains(VtkFieldSelection::NODE_VOLUMES) && mesh.node_volumes().has_non_finite() {
+        return Err(non_finite("NodeVol"));
+    }
+
+    if fields.contains(VtkFieldSelection::POTENTIAL) && mesh.potential().has_non_finite() {
+        return Err(non_finite("phi"));
+    }
+
+    if fields.contains(VtkFieldSelection::CHARGE_DENSITY) && mesh.charge_density().has_non_finite()
+    {
+        return Err(non_finite("rho"));
+    }
+
+    if fields.contains(VtkFieldSelection::SPECIES_DENSITIES) {
+        for s in species {
+            if s.number_density().has_non_finite() {
+                return Err(non_finite(&s.name()));
+            }
+        }
+    }
+
+    if fields.contains(VtkFieldSelection::ELECTRIC_FIELD) && mesh.electric_field().has_non_finite()
+    {
+        return Err(non_finite("ef"));
+    }
+
+    Ok(())
+}
+
+/// Writes the selected data arrays of the current state of the simulation in the VTK image
+/// data format, encoding the arrays in the provided format.
+///
+/// Fails without writing anything if a selected data array holds a NaN or infinite value.
+pub fn write_vti<W: Write>(
+    writer: &mut W,
+    mesh: &BoxMesh,
+    species: &[Species],
+    fields: VtkFieldSelection,
+    format: VtkFormat,
+) -> Result<()> {
+    check_finite_fields(mesh, species, fields)?;
 
     let dimensions = mesh.dimensions();
     let origin = mesh.origin();
     let cell_spacings = mesh.cell_spacings();
 
     // ImageData is the VTK format for structured Cartesian meshes.
-    writeln!(&mut vti_file, "<VTKFile type=\"ImageData\">")?;
+    writeln!(
+        writer,
+        "<VTKFile type=\"ImageData\" byte_order=\"LittleEndian\" header_type=\"UInt32\">"
+    )?;
 
-    write!(&mut vti_file, "<ImageData Origin=\"{}\" ", origin)?;
+    write!(writer, "<ImageData Origin=\"{}\" ", origin)?;
     write!(
-        &mut vti_file,
+        writer,
         "Spacing=\"{} {} {}\" ",
         cell_spacings[0], cell_spacings[1], cell_spacings[2]
     )?;
-    write!(
-        &mut vti_file,
-        "WholeExtent=\"0 {} 0 {} 0 {}\">\n",
+    writeln!(
+        writer,
+        "WholeExtent=\"0 {} 0 {} 0 {}\">",
         dimensions.x - 1,
         dimensions.y - 1,
         dimensions.z - 1
     )?;
 
     // Output data is stored on nodes (point data).
-    writeln!(&mut vti_file, "<PointData>")?;
+    writeln!(writer, "<PointData>")?;
 
     // Writing node volumes.
+    if fields.contains(VtkFieldSelection::NODE_VOLUMES) {
+        write_vti_data_array(writer, "NodeVol", &mesh.node_volumes(), format)?;
+    }
+
+    // Writing potential.
+    if fields.contains(VtkFieldSelection::POTENTIAL) {
+        write_vti_data_array(writer, "phi", mesh.potential(), format)?;
+    }
+
+    // Writing charge density.
+    if fields.contains(VtkFieldSelection::CHARGE_DENSITY) {
+        write_vti_data_array(writer, "rho", mesh.charge_density(), format)?;
+    }
+
+    // Writing species number densities.
+    if fields.contains(VtkFieldSelection::SPECIES_DENSITIES) {
+        for s in species {
+            write_vti_data_array(writer, &s.name(), &s.number_density(), format)?;
+        }
+    }
+
+    // Writing electric field.
+    if fields.contains(VtkFieldSelection::ELECTRIC_FIELD) {
+        write_vti_data_array(writer, "ef", mesh.electric_field(), format)?;
+    }
+
+    // Closing tags.
+    writeln!(writer, "</PointData>")?;
+    writeln!(writer, "</ImageData>")?;
+    writeln!(writer, "</VTKFile>")?;
+
+    Ok(())
+}
+
+/// Outputs the current state of the simulation to a legacy VTK file.
+pub fn vtk_legacy_output(mesh: &BoxMesh, species: &Vec<Species>, file_index: usize) -> Result<()> {
+    // Creating the results directory, if it doesn't exist.
+    fs::create_dir_all("results")?;
+
+    let filepath = format!("results/field_{:05}.vtk", file_index);
+    let path = Path::new(&filepath);
+
+    let mut vtk_file = File::create(path)?;
+
+    write_vtk_legacy(&mut vtk_file, mesh, species)
+}
+
+/// Writes the current state of the simulation in the legacy VTK structured points format.
+pub fn write_vtk_legacy<W: Write>(
+    writer: &mut W,
+    mesh: &BoxMesh,
+    species: &Vec<Species>,
+) -> Result<()> {
+    let dimensions = mesh.dimensions();
+    let origin = mesh.origin();
+    let cell_spacings = mesh.cell_spacings();
+    let num_nodes = dimensions.x * dimensions.y * dimensions.z;
+
+    writeln!(writer, "# vtk DataFile Version 3.0")?;
+    writeln!(writer, "Plasma simulation field data")?;
+    writeln!(writer, "ASCII")?;
+    writeln!(writer, "DATASET STRUCTURED_POINTS")?;
     writeln!(
-        &mut vti_file,
-        "<DataArray Name=\"NodeVol\" NumberOfComponents=\"1\" format=\"ascii\" type=\"Float64\">"
+        writer,
+        "DIMENSIONS {} {} {}",
+        dimensions.x, dimensions.y, dimensions.z
+    )?;
+    writeln!(writer, "ORIGIN {}", origin)?;
+    writeln!(
+        writer,
+        "SPACING {} {} {}",
+        cell_spacings[0], cell_spacings[1], cell_spacings[2]
     )?;
-    writeln!(&mut vti_file, "{}", mesh.node_volumes())?;
-    writeln!(&mut vti_file, "</DataArray>")?;
 
-    // Writing potential.
+    // Output data is stored on nodes (point data).
+    writeln!(writer, "POINT_DATA {}", num_nodes)?;
+
+    write_legacy_scalars(writer, "NodeVol", &mesh.node_volumes())?;
+    write_legacy_scalars(writer, "phi", mesh.potential())?;
+    write_legacy_scalars(writer, "rho", mesh.charge_density())?;
+
+    for s in species {
+        write_legacy_scalars(writer, &s.name(), &s.number_density())?;
+    }
+
+    writeln!(writer, "VECTORS ef double")?;
+    write_point_values(writer, mesh.electric_field())?;
+
+    Ok(())
+}
+
+/// Outputs the particles of every species to a VTP file.
+pub fn vtp_particle_output(species: &[Species], file_index: usize) -> Result<()> {
+    // Creating the results directory, if it doesn't exist.
+    fs::create_dir_all("results")?;
+
+    let filepath = format!("results/particles_{:05}.vtp", file_index);
+    let path = Path::new(&filepath);
+
+    let mut vtp_file = File::create(path)?;
+
+    write_vtp_particles(&mut vtp_file, species)
+}
+
+/// Writes the particles of every species as VTK poly data, tagging each particle with the
+/// index of its species so that species can be distinguished when visualized.
+pub fn write_vtp_particles<W: Write>(writer: &mut W, species: &[Species]) -> Result<()> {
+    let num_particles: usize = species.iter().map(|s| s.particles().len()).sum();
+
+    // PolyData is the VTK format for unstructured points, lines and polygons.
+    writeln!(writer, "<VTKFile type=\"PolyData\">")?;
+    writeln!(writer, "<PolyData>")?;
     writeln!(
-        &mut vti_file,
-        "<DataArray Name=\"phi\" NumberOfComponents=\"1\" format=\"ascii\" type=\"Float64\">"
+        writer,
+        "<Piece NumberOfPoints=\"{}\" NumberOfVerts=\"{}\">",
+        num_particles, num_particles
     )?;
-    writeln!(&mut vti_file, "{}", mesh.potential())?;
-    writeln!(&mut vti_file, "</DataArray>")?;
 
-    // Writing charge density.
+    // Writing particle positions.
+    writeln!(writer, "<Points>")?;
     writeln!(
-        &mut vti_file,
-        "<DataArray Name=\"rho\" NumberOfComponents=\"1\" format=\"ascii\" type=\"Float64\">"
+        writer,
+        "<DataArray NumberOfComponents=\"3\" format=\"ascii\" type=\"Float64\">"
     )?;
-    writeln!(&mut vti_file, "{}", mesh.charge_density())?;
-    writeln!(&mut vti_file, "</DataArray>")?;
+    for s in species {
+        for particle in s.particles() {
+            writeln!(writer, "{}", particle.position)?;
+        }
+    }
+    writeln!(writer, "</DataArray>")?;
+    writeln!(writer, "</Points>")?;
 
-    // Writing species number densities.
+    // Each particle is rendered as a vertex cell.
+    writeln!(writer, "<Verts>")?;
+    writeln!(
+        writer,
+        "<DataArray Name=\"connectivity\" format=\"ascii\" type=\"Int64\">"
+    )?;
+    for index in 0..num_particles {
+        writeln!(writer, "{}", index)?;
+    }
+    writeln!(writer, "</DataArray>")?;
+    writeln!(
+        writer,
+        "<DataArray Name=\"offsets\" format=\"ascii\" type=\"Int64\">"
+    )?;
+    for index in 0..num_particles {
+        writeln!(writer, "{}", index + 1)?;
+    }
+    writeln!(writer, "</DataArray>")?;
+    writeln!(writer, "</Verts>")?;
+
+    // Output data is stored on particles (point data).
+    writeln!(writer, "<PointData>")?;
+
+    // Writing species indices.
+    writeln!(
+        writer,
+        "<DataArray Name=\"species_index\" NumberOfComponents=\"1\" format=\"ascii\" type=\"Int32\">"
+    )?;
+    for (species_index, s) in species.iter().enumerate() {
+        for _ in s.particles() {
+            writeln!(writer, "{}", species_index)?;
+        }
+    }
+    writeln!(writer, "</DataArray>")?;
+
+    // Writing particle velocities.
+    writeln!(
+        writer,
+        "<DataArray Name=\"velocity\" NumberOfComponents=\"3\" format=\"ascii\" type=\"Float64\">"
+    )?;
     for s in species {
-        writeln!(
-            &mut vti_file,
-            "<DataArray Name=\"{}\" NumberOfComponents=\"1\" format=\"ascii\" type=\"Float64\">",
-            s.name()
-        )?;
-        writeln!(&mut vti_file, "{}", s.number_density())?;
-        writeln!(&mut vti_file, "</DataArray>")?;
+        for particle in s.particles() {
+            writeln!(writer, "{}", particle.velocity)?;
+        }
     }
+    writeln!(writer, "</DataArray>")?;
 
-    // Writing electric field.
+    // Writing particle kinetic energies, in electron volts.
     writeln!(
-        &mut vti_file,
-        "<DataArray Name=\"ef\" NumberOfComponents=\"3\" format=\"ascii\" type=\"Float64\">"
+        writer,
+        "<DataArray Name=\"kinetic_energy\" NumberOfComponents=\"1\" format=\"ascii\" type=\"Float64\">"
     )?;
-    writeln!(&mut vti_file, "{}", mesh.electric_field())?;
-    writeln!(&mut vti_file, "</DataArray>")?;
+    for s in species {
+        for particle in s.particles() {
+            let velocity = particle.velocity;
+            let kinetic_energy =
+                0.5 * s.particle_mass(particle) * velocity.dot(velocity) / ELEMENTARY_CHARGE;
+            writeln!(writer, "{}", kinetic_energy)?;
+        }
+    }
+    writeln!(writer, "</DataArray>")?;
 
     // Closing tags.
-    writeln!(&mut vti_file, "</PointData>")?;
-    writeln!(&mut vti_file, "</ImageData>")?;
-    writeln!(&mut vti_file, "</VTKFile>")?;
+    writeln!(writer, "</PointData>")?;
+    writeln!(writer, "</Piece>")?;
+    writeln!(writer, "</PolyData>")?;
+    writeln!(writer, "</VTKFile>")?;
 
     Ok(())
 }
+
+/// A single step of simulation diagnostics, serialized as one line of JSON.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct DiagnosticsRecord {
+    /// The simulation iteration.
+    iteration: usize,
+    /// The simulation time.
+    time: f64,
+    /// The kinetic energy of every species.
+    kinetic_energy: f64,
+    /// The kinetic energy of the random motion of every species, about its mean velocity.
+    thermal_energy: f64,
+    /// The energy stored in the electric field.
+    field_energy: f64,
+    /// The number of macroparticles in each species, keyed by species name.
+    counts: BTreeMap<String, usize>,
+}
+
+/// Writes the diagnostics of a simulation step as a single line of JSON, so that a file of
+/// consecutive steps follows the JSON Lines format and can be read while it is written.
+#[cfg(feature = "serde")]
+pub fn write_diagnostics_jsonl<W: Write>(
+    writer: &mut W,
+    iteration: usize,
+    mesh: &BoxMesh,
+    species: &[Species],
+) -> Result<()> {
+    let record = DiagnosticsRecord {
+        iteration,
+        time: mesh.current_time(),
+        kinetic_energy: species.iter().map(Species::kinetic_energy).sum(),
+        thermal_energy: species.iter().map(|s| s.thermal_energy(mesh)).sum(),
+        field_energy: mesh.field_energy(),
+        counts: species
+            .iter()
+            .map(|s| (s.name(), s.particles().len()))
+            .collect(),
+    };
+
+    serde_json::to_writer(&mut *writer, &record)?;
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+/// Represents a value at a node, written to VTK files as one or more components.
+trait VtkComponents: Copy {
+    /// The number of components of each value.
+    const NUM_COMPONENTS: usize;
+
+    /// Appends the components of the value.
+    fn extend_components(self, components: &mut Vec<f64>);
+}
+
+impl VtkComponents for f64 {
+    const NUM_COMPONENTS: usize = 1;
+
+    fn extend_components(self, components: &mut Vec<f64>) {
+        components.push(self);
+    }
+}
+
+impl VtkComponents for Vec3 {
+    const NUM_COMPONENTS: usize = 3;
+
+    fn extend_components(self, components: &mut Vec<f64>) {
+        components.extend([self.x, self.y, self.z]);
+    }
+}
+
+/// Writes a field as a VTK XML data array of 64-bit floats in the provided format.
+///
+/// Values are written in VTK point ordering (x varying fastest). Binary arrays hold a single
+/// base64 encoding of a 4-byte little-endian header, giving the length of the data in bytes,
+/// followed by the little-endian data.
+fn write_vti_data_array<W, T>(
+    writer: &mut W,
+    name: &str,
+    field: &Field<T>,
+    format: VtkFormat,
+) -> Result<()>
+where
+    W: Write,
+    T: VtkComponents + Zero + Mul<f64, Output = T> + AddAssign<<T as Mul<f64>>::Output>,
+{
+    let (nx, ny, nz) = field.shape();
+    let mut components = Vec::with_capacity(nx * ny * nz * T::NUM_COMPONENTS);
+
+    for k in 0..nz {
+        for j in 0..ny {
+            for i in 0..nx {
+                field[[i, j, k]].extend_components(&mut components);
+            }
+        }
+    }
+
+    writeln!(
+        writer,
+        "<DataArray Name=\"{}\" NumberOfComponents=\"{}\" format=\"{}\" type=\"Float64\">",
+        name,
+        T::NUM_COMPONENTS,
+        format
+    )?;
+
+    match format {
+        VtkFormat::Ascii => {
+            for component in components {
+                write!(writer, "{} ", component)?;
+            }
+            writeln!(writer)?;
+        }
+        VtkFormat::Binary => {
+            let num_bytes = u32::try_from(8 * components.len()).map_err(|_| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Data array {} is too large for a 4-byte header.", name),
+                )
+            })?;
+
+            let mut bytes = Vec::with_capacity(4 + num_bytes as usize);
+            bytes.extend(num_bytes.to_le_bytes());
+            for component in components {
+                bytes.extend(component.to_le_bytes());
+            }
+
+            writeln!(writer, "{}", encode_base64(&bytes))?;
+        }
+    }
+
+    writeln!(writer, "</DataArray>")
+}
+
+/// The alphabet of the standard base64 encoding.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as standard base64, padded with `=`.
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(4 * bytes.len() / 3 + 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = (u32::from(chunk[0]) << 16)
+            | (u32::from(*chunk.get(1).unwrap_or(&0)) << 8)
+            | u32::from(*chunk.get(2).unwrap_or(&0));
+
+        for position in 0..4 {
+            if position <= chunk.len() {
+                let sextet = (group >> (18 - 6 * position)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Decodes standard base64, ignoring whitespace, or returns `None` if the text isn't valid
+/// base64.
+pub(crate) fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let sextets: Vec<u8> = text
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .take_while(|&byte| byte != b'=')
+        .map(|byte| {
+            BASE64_ALPHABET
+                .iter()
+                .position(|&symbol| symbol == byte)
+                .map(|sextet| sextet as u8)
+        })
+        .collect::<Option<_>>()?;
+
+    if sextets.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(3 * sextets.len() / 4);
+    for chunk in sextets.chunks(4) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (position, &sextet)| {
+                group | (u32::from(sextet) << (18 - 6 * position))
+            });
+
+        for position in 0..chunk.len() - 1 {
+            bytes.push((group >> (16 - 8 * position)) as u8);
+        }
+    }
+
+    Some(bytes)
+}
+
+/// Writes a scalar field as a legacy VTK point data array.
+fn write_legacy_scalars<W: Write>(writer: &mut W, name: &str, field: &Field<f64>) -> Result<()> {
+    writeln!(writer, "SCALARS {} double 1", name)?;
+    writeln!(writer, "LOOKUP_TABLE default")?;
+    write_point_values(writer, field)
+}
+
+/// Writes the values of a field one per line, in VTK point ordering (x varying fastest).
+fn write_point_values<W, T>(writer: &mut W, field: &Field<T>) -> Result<()>
+where
+    W: Write,
+    T: Copy + Clone + Zero + Mul<f64, Output = T> + AddAssign<<T as Mul<f64>>::Output> + Display,
+{
+    let (nx, ny, nz) = field.shape();
+
+    for k in 0..nz {
+        for j in 0..ny {
+            for i in 0..nx {
+                writeln!(writer, "{}", field[[i, j, k]])?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::mesh::Dimensions;
+    use crate::vector::Vec3;
+
+    /// Returns the values of the named data array within VTK XML output.
+    fn data_array_values<'a>(contents: &'a str, name: &str) -> Vec<&'a str> {
+        let opening = format!("<DataArray Name=\"{}\"", name);
+
+        contents
+            .lines()
+            .skip_while(|line| !line.starts_with(&opening))
+            .skip(1)
+            .take_while(|line| !line.starts_with("</DataArray>"))
+            .collect()
+    }
+
+    #[test]
+    fn test_vtp_species_index() {
+        use crate::constants::{ELECTRON_MASS, ELEMENTARY_CHARGE};
+
+        let mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(5, 5, 5),
+            1e-10,
+        );
+        let velocity = Vec3::new(0.0, 0.0, 0.0);
+
+        let mut ions = Species::new(
+            String::from("H+"),
+            1836.0 * ELECTRON_MASS,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        ions.add_particle(Vec3::new(0.01, 0.02, 0.03), velocity, 1.0, &mesh);
+
+        let mut electrons = Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        electrons.add_particle(Vec3::new(0.04, 0.05, 0.06), velocity, 1.0, &mesh);
+        electrons.add_particle(Vec3::new(0.07, 0.08, 0.09), velocity, 1.0, &mesh);
+
+        let mut buffer = Vec::new();
+        write_vtp_particles(&mut buffer, &[ions, electrons]).unwrap();
+        let contents = String::from_utf8(buffer).unwrap();
+
+        assert!(contents.contains("NumberOfPoints=\"3\""));
+        assert_eq!(
+            data_array_values(&contents, "species_index"),
+            vec!["0", "1", "1"]
+        );
+    }
+
+    #[test]
+    fn test_vtp_kinetic_energy() {
+        use crate::constants::{ELECTRON_MASS, ELEMENTARY_CHARGE};
+
+        let mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(5, 5, 5),
+            1e-10,
+        );
+
+        let mut species = Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let velocities = [Vec3::new(1e6, 0.0, 0.0), Vec3::new(3e5, -4e5, 0.0)];
+        for velocity in velocities {
+            species.add_particle(Vec3::new(0.05, 0.05, 0.05), velocity, 1.0, &mesh);
+        }
+
+        // A particle overriding the species mass carries energy in proportion to its mass.
+        species.add_particle_with_mass(
+            Vec3::new(0.05, 0.05, 0.05),
+            velocities[0],
+            1.0,
+            2.0 * ELECTRON_MASS,
+            &mesh,
+        );
+
+        let mut buffer = Vec::new();
+        write_vtp_particles(&mut buffer, &[species]).unwrap();
+        let contents = String::from_utf8(buffer).unwrap();
+
+        let energies: Vec<f64> = data_array_values(&contents, "kinetic_energy")
+            .iter()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        let expected = [
+            0.5 * ELECTRON_MASS * 1e12 / ELEMENTARY_CHARGE,
+            0.5 * ELECTRON_MASS * 2.5e11 / ELEMENTARY_CHARGE,
+            ELECTRON_MASS * 1e12 / ELEMENTARY_CHARGE,
+        ];
+
+        assert_eq!(energies.len(), expected.len());
+        for (energy, expected) in energies.iter().zip(expected) {
+            assert!((energy - expected).abs() < 1e-12 * expected);
+        }
+    }
+
+    #[test]
+    fn test_vti_field_selection() {
+        let mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(3, 3, 3),
+            1e-10,
+        );
+
+        let mut buffer = Vec::new();
+        write_vti(
+            &mut buffer,
+            &mesh,
+            &[],
+            VtkFieldSelection::POTENTIAL,
+            VtkFormat::Ascii,
+        )
+        .unwrap();
+        let contents = String::from_utf8(buffer).unwrap();
+
+        let arrays: Vec<&str> = contents
+            .lines()
+            .filter(|line| line.starts_with("<DataArray"))
+            .collect();
+        assert_eq!(arrays.len(), 1);
+        assert!(arrays[0].starts_with("<DataArray Name=\"phi\""));
+
+        let selection = VtkFieldSelection::POTENTIAL | VtkFieldSelection::ELECTRIC_FIELD;
+        assert!(selection.contains(VtkFieldSelection::ELECTRIC_FIELD));
+        assert!(!selection.contains(VtkFieldSelection::NODE_VOLUMES));
+        assert!(VtkFieldSelection::ALL.contains(selection));
+    }
+
+    #[test]
+    fn test_vti_binary_format() {
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.3, 0.2, 0.2),
+            Dimensions::new(4, 3, 3),
+            1e-10,
+        );
+        for i in 0..4 {
+            for j in 0..3 {
+                for k in 0..3 {
+                    mesh.potential_mut()[[i, j, k]] = (i + 10 * j + 100 * k) as f64;
+                }
+            }
+        }
+        mesh.compute_electric_field();
+
+        let fields = VtkFieldSelection::POTENTIAL | VtkFieldSelection::ELECTRIC_FIELD;
+        let write = |format: VtkFormat| {
+            let mut buffer = Vec::new();
+            write_vti(&mut buffer, &mesh, &[], fields, format).unwrap();
+            String::from_utf8(buffer).unwrap()
+        };
+        let ascii = write(VtkFormat::Ascii);
+        let binary = write(VtkFormat::Binary);
+        let ascii_values = |name: &str| -> Vec<String> {
+            data_array_values(&ascii, name)
+                .iter()
+                .flat_map(|line| line.split_whitespace())
+                .map(String::from)
+                .collect()
+        };
+
+        // Values follow VTK point ordering, with the x-index varying fastest.
+        let potential: Vec<f64> = ascii_values("phi")
+            .iter()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        let expected: Vec<f64> = (0..3)
+            .flat_map(|k| {
+                (0..3).flat_map(move |j| (0..4).map(move |i| (i + 10 * j + 100 * k) as f64))
+            })
+            .collect();
+        assert_eq!(potential, expected);
+
+        for (name, num_values) in [("phi", 36), ("ef", 108)] {
+            let start = binary
+                .find(&format!("<DataArray Name=\"{}\"", name))
+                .unwrap();
+            let array = &binary[start..];
+            assert!(array.lines().next().unwrap().contains("format=\"binary\""));
+
+            let encoded = array.lines().nth(1).unwrap();
+            let bytes = decode_base64(encoded).unwrap();
+            assert_eq!(encode_base64(&bytes), encoded);
+
+            // The header gives the length of the data which follows it.
+            let header = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            assert_eq!(header as usize, 8 * num_values);
+            assert_eq!(bytes.len(), 4 + 8 * num_values);
+
+            let decoded: Vec<String> = bytes[4..]
+                .chunks(8)
+                .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()).to_string())
+                .collect();
+            assert_eq!(decoded, ascii_values(name));
+        }
+
+        // Padding covers data whose length isn't a multiple of three bytes.
+        assert_eq!(encode_base64(b"ab"), "YWI=");
+        assert_eq!(decode_base64("YWI=").unwrap(), b"ab");
+        assert_eq!(decode_base64("YW*="), None);
+    }
+
+    #[test]
+    fn test_pvd() {
+        let mut buffer = Vec::new();
+        write_pvd(&mut buffer, &[0, 100, 200], 2e-10).unwrap();
+        let contents = String::from_utf8(buffer).unwrap();
+
+        assert!(contents.contains("<VTKFile type=\"Collection\""));
+
+        let datasets: Vec<&str> = contents
+            .lines()
+            .filter(|line| line.starts_with("<DataSet"))
+            .collect();
+        assert_eq!(datasets.len(), 3);
+
+        // Each file is listed at the simulation time of its iteration.
+        for (dataset, (time, file)) in datasets.iter().zip([
+            (0.0, "field_00000.vti"),
+            (2e-8, "field_00100.vti"),
+            (4e-8, "field_00200.vti"),
+        ]) {
+            assert!(dataset.contains(&format!("timestep=\"{}\"", time)));
+            assert!(dataset.contains(&format!("file=\"{}\"", file)));
+        }
+    }
+
+    #[test]
+    fn test_species_density_vtis() {
+        use crate::constants::{ELECTRON_MASS, ELEMENTARY_CHARGE};
+
+        let mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(3, 3, 3),
+            1e-10,
+        );
+        let species = [
+            Species::new(
+                String::from("H+"),
+                1836.0 * ELECTRON_MASS,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            ),
+            Species::new(
+                String::from("e-"),
+                ELECTRON_MASS,
+                -ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            ),
+        ];
+
+        let directory = std::env::temp_dir().join(format!("densities_{}", std::process::id()));
+        write_species_density_vtis(&directory, &mesh, &species, 7).unwrap();
+
+        let mut entries: Vec<String> = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            vec!["density_H+_00007.vti", "density_e-_00007.vti"]
+        );
+
+        for s in &species {
+            let path = directory.join(format!("density_{}_00007.vti", s.name()));
+            let contents = fs::read_to_string(path).unwrap();
+
+            let arrays: Vec<&str> = contents
+                .lines()
+                .filter(|line| line.starts_with("<DataArray"))
+                .collect();
+            assert_eq!(arrays.len(), 1);
+            assert!(arrays[0].starts_with(&format!("<DataArray Name=\"{}\"", s.name())));
+        }
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_append_diagnostic() {
+        use crate::constants::{ELECTRON_MASS, ELEMENTARY_CHARGE};
+
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(3, 3, 3),
+            1e-10,
+        );
+        // A linear potential produces a uniform field of 100 V/m over the 27 nodes.
+        for i in 0..3 {
+            for j in 0..3 {
+                for k in 0..3 {
+                    mesh.potential_mut()[[i, j, k]] = -100.0 * 0.05 * i as f64;
+                }
+            }
+        }
+        mesh.compute_electric_field();
+
+        let mut electrons = Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        electrons.add_particle(
+            Vec3::new(0.05, 0.05, 0.05),
+            Vec3::new(0.0, 0.0, 0.0),
+            1.0,
+            &mesh,
+        );
+        let species = [electrons];
+
+        let path = std::env::temp_dir().join(format!("runtime_diags_{}.csv", std::process::id()));
+        let read_lines = || -> Vec<String> {
+            fs::read_to_string(&path)
+                .unwrap()
+                .lines()
+                .map(String::from)
+                .collect()
+        };
+
+        for iteration in 0..3 {
+            append_diagnostic(&path, &mesh, &species, iteration).unwrap();
+        }
+
+        let lines = read_lines();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "iteration,potential_energy,kinetic_energy,count_e-,electric_field_l2_norm"
+        );
+
+        let row: Vec<&str> = lines[3].split(',').collect();
+        assert_eq!(row[0], "2");
+        assert_eq!(row[1].parse::<f64>().unwrap(), mesh.field_energy());
+        assert_eq!(row[3], "1");
+        assert!((row[4].parse::<f64>().unwrap() - 100.0 * 27f64.sqrt()).abs() < 1e-9);
+
+        // Starting over at the first iteration discards the previous run.
+        append_diagnostic(&path, &mesh, &species, 0).unwrap();
+        assert_eq!(read_lines().len(), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_output_stamp() {
+        assert_eq!(OutputStamp::Index(42).to_string(), "00042");
+
+        let early = format!("field_{}.vti", OutputStamp::Time(2e-6));
+        let late = format!("field_{}.vti", OutputStamp::Time(1.5e-5));
+        assert_eq!(early, "field_t000002000ns.vti");
+        assert_eq!(late, "field_t000015000ns.vti");
+        assert!(early < late);
+    }
+
+    #[test]
+    fn test_vti_non_finite() {
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(5, 5, 5),
+            1e-10,
+        );
+        mesh.potential_mut()[[2, 3, 1]] = f64::NAN;
+
+        let error =
+            vtk_output(&mesh, &[], 0, VtkFieldSelection::ALL, VtkFormat::Ascii).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("phi"));
+
+        let mut output = Vec::new();
+        assert!(write_vti(
+            &mut output,
+            &mesh,
+            &[],
+            VtkFieldSelection::ALL,
+            VtkFormat::Binary
+        )
+        .is_err());
+        assert!(output.is_empty());
+
+        // Arrays that aren't written aren't checked.
+        let fields = VtkFieldSelection::CHARGE_DENSITY | VtkFieldSelection::ELECTRIC_FIELD;
+        write_vti(&mut output, &mesh, &[], fields, VtkFormat::Ascii).unwrap();
+        assert!(!output.is_empty());
+    }
+
+    #[test]
+    fn test_legacy_vtk_header() {
+        let mesh = BoxMesh::new(
+            Vec3::new(-0.1, -0.2, 0.0),
+            Vec3::new(0.1, 0.2, 0.3),
+            Dimensions::new(5, 9, 4),
+            1e-10,
+        );
+
+        let mut buffer = Vec::new();
+        write_vtk_legacy(&mut buffer, &mesh, &Vec::new()).unwrap();
+
+        let contents = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+
+        assert_eq!(lines[0], "# vtk DataFile Version 3.0");
+        assert_eq!(lines[3], "DATASET STRUCTURED_POINTS");
+
+        let values = |line: &str, keyword: &str| -> Vec<f64> {
+            let mut tokens = line.split_whitespace();
+            assert_eq!(tokens.next(), Some(keyword));
+            tokens.map(|token| token.parse::<f64>().unwrap()).collect()
+        };
+
+        assert_eq!(values(lines[4], "DIMENSIONS"), vec![5.0, 9.0, 4.0]);
+        assert_eq!(values(lines[5], "ORIGIN"), vec![-0.1, -0.2, 0.0]);
+
+        let spacing = values(lines[6], "SPACING");
+        let expected_spacing = mesh.cell_spacings();
+        for axis in 0..3 {
+            assert!((spacing[axis] - expected_spacing[axis]).abs() < 1e-15);
+        }
+
+        assert_eq!(values(lines[7], "POINT_DATA"), vec![180.0]);
+
+        // Each array is written with one value per node.
+        let num_vector_values = contents
+            .lines()
+            .skip_while(|line| !line.starts_with("VECTORS"))
+            .skip(1)
+            .count();
+        assert_eq!(num_vector_values, 180);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_diagnostics_jsonl() {
+        use crate::constants::{ELECTRON_MASS, ELEMENTARY_CHARGE};
+
+        let mut mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(5, 5, 5),
+            1e-10,
+        );
+        let mut electrons = Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        electrons.load_particles_box_qs(
+            Vec3::new(0.02, 0.02, 0.02),
+            Vec3::new(0.08, 0.08, 0.08),
+            1e10,
+            (4, 4, 4),
+            &mesh,
+        );
+        let mut species = vec![electrons];
+
+        let mut output = Vec::new();
+        for iteration in 0..3 {
+            species[0].compute_number_density(&mesh);
+            mesh.compute_charge_density(&species);
+            mesh.solve_potential(4000, 1e-3, None).unwrap();
+            mesh.compute_electric_field();
+            species[0].advance(&mesh);
+            mesh.advance_time();
+
+            write_diagnostics_jsonl(&mut output, iteration, &mesh, &species).unwrap();
+        }
+
+        let contents = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+
+        for (iteration, line) in lines.iter().enumerate() {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+
+            assert_eq!(record["iteration"], iteration);
+            assert!(record["time"].as_f64().unwrap() > 0.0);
+            assert!(record["kinetic_energy"].as_f64().unwrap() > 0.0);
+            assert!(record["thermal_energy"].as_f64().unwrap() >= 0.0);
+            assert!(record["field_energy"].as_f64().unwrap() > 0.0);
+            assert_eq!(record["counts"]["e-"], 64);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::vector::Vec3;
 
 /// Represents a particle.
+#[derive(Clone)]
 pub struct Particle {
     /// The position of the particle.
     pub position: Vec3,
@@ -8,6 +9,10 @@ pub struct Particle {
     pub velocity: Vec3,
     /// The macroparticle weight.
     pub macroparticle_weight: f64,
+    /// The charge state, as a multiple of the species charge.
+    pub charge_state: i8,
+    /// The mass of the particle, overriding the species mass when present.
+    pub mass: Option<f64>,
 }
 
 impl Particle {
@@ -17,6 +22,30 @@ impl Particle {
             position: position,
             velocity: velocity,
             macroparticle_weight: macroparticle_weight,
+            charge_state: 1,
+            mass: None,
         }
     }
+
+    /// Creates a new particle with the provided charge state.
+    pub fn with_charge_state(
+        position: Vec3,
+        velocity: Vec3,
+        macroparticle_weight: f64,
+        charge_state: i8,
+    ) -> Self {
+        Particle {
+            position: position,
+            velocity: velocity,
+            macroparticle_weight: macroparticle_weight,
+            charge_state: charge_state,
+            mass: None,
+        }
+    }
+
+    /// Sets a mass overriding that of the species containing the particle.
+    pub fn with_mass(mut self, mass: f64) -> Self {
+        self.mass = Some(mass);
+        self
+    }
 }
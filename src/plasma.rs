@@ -0,0 +1,45 @@
+use crate::constants::{ELEMENTARY_CHARGE, PERMITTIVITY};
+
+/// Computes the ion acoustic speed, in meters per second, for cold ions of the provided mass,
+/// in kilograms, and an electron temperature in electron volts.
+pub fn ion_acoustic_speed(electron_temperature: f64, ion_mass: f64) -> f64 {
+    (electron_temperature * ELEMENTARY_CHARGE / ion_mass).sqrt()
+}
+
+/// Computes the Debye length, in meters, of a species at the provided temperature, in electron
+/// volts, and number density, in particles per cubic meter.
+pub fn debye_length(temperature: f64, number_density: f64) -> f64 {
+    (PERMITTIVITY * temperature / (number_density * ELEMENTARY_CHARGE)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::constants::ATOMIC_MASS_UNIT;
+
+    #[test]
+    fn test_ion_acoustic_speed() {
+        // An O+ plasma with 1 eV electrons has a sound speed of about 2.46 km/s.
+        let speed = ion_acoustic_speed(1.0, 16.0 * ATOMIC_MASS_UNIT);
+        assert!((speed - 2456.0).abs() < 1.0);
+
+        // The speed scales with the square root of the electron temperature.
+        let hotter = ion_acoustic_speed(4.0, 16.0 * ATOMIC_MASS_UNIT);
+        assert!((hotter / speed - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_debye_length() {
+        // A plasma with 1 eV electrons at 1e11 m^-3 has a Debye length of about 2.35 cm.
+        let length = debye_length(1.0, 1e11);
+        assert!((length - 0.02351).abs() < 1e-5);
+
+        // The length scales with the square root of the temperature over the density.
+        let hotter = debye_length(4.0, 1e11);
+        assert!((hotter / length - 2.0).abs() < 1e-12);
+
+        let denser = debye_length(1.0, 4e11);
+        assert!((denser / length - 0.5).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,202 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::constants::ELEMENTARY_CHARGE;
+use crate::vector::Vec3;
+
+/// Samples a value from a standard normal distribution using the Box-Muller transform.
+pub fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    // Excluding zero from the first sample to keep the logarithm finite.
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+/// Samples a velocity from a Maxwellian distribution for a particle of the provided mass, in
+/// kilograms, at the provided temperature, in electron volts.
+pub fn maxwellian_velocity<R: Rng>(rng: &mut R, mass: f64, temperature: f64) -> Vec3 {
+    let thermal_speed = (temperature * ELEMENTARY_CHARGE / mass).sqrt();
+
+    Vec3::new(
+        standard_normal(rng),
+        standard_normal(rng),
+        standard_normal(rng),
+    ) * thermal_speed
+}
+
+/// Samples a unit direction uniformly distributed over the sphere, as for isotropic scattering.
+pub fn sample_isotropic_direction<R: Rng>(rng: &mut R) -> Vec3 {
+    // Sampling the polar cosine uniformly gives equal probability to equal areas.
+    let cos_theta = 2.0 * rng.gen::<f64>() - 1.0;
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+    let phi = 2.0 * PI * rng.gen::<f64>();
+
+    Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+/// Samples a unit direction from the cosine (Lambertian) distribution about the provided
+/// surface normal, as for diffuse reflection or emission from a surface.
+///
+/// The normal needn't have unit length, and the direction always lies within its hemisphere.
+pub fn sample_cosine_direction<R: Rng>(normal: Vec3, rng: &mut R) -> Vec3 {
+    let normal = normal.normalize();
+
+    // Building two tangents from whichever axis is furthest from parallel to the normal.
+    let helper = if normal.x.abs() < 0.9 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        Vec3::new(0.0, 1.0, 0.0)
+    };
+    let tangent = normal.cross(helper).normalize();
+    let bitangent = normal.cross(tangent);
+
+    // The squared sine of the polar angle is uniformly distributed for a cosine distribution.
+    let sin_theta_squared = rng.gen::<f64>();
+    let sin_theta = sin_theta_squared.sqrt();
+    let cos_theta = (1.0 - sin_theta_squared).sqrt();
+    let phi = 2.0 * PI * rng.gen::<f64>();
+
+    normal * cos_theta + tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin())
+}
+
+/// Generates a fixed pseudo-random sequence with a 64-bit linear congruential generator.
+///
+/// Unlike the generators of the `rand` crate, the sequence for a seed is defined here and so
+/// never changes between machines or crate versions, which keeps benchmark inputs identical.
+/// It is not suitable where statistical quality matters.
+#[derive(Clone, Debug)]
+pub struct LinearCongruential {
+    /// The current state of the generator.
+    state: u64,
+}
+
+impl LinearCongruential {
+    /// The multiplier of the generator, from Knuth's MMIX.
+    const MULTIPLIER: u64 = 6364136223846793005;
+    /// The increment of the generator, from Knuth's MMIX.
+    const INCREMENT: u64 = 1442695040888963407;
+
+    /// Creates a generator starting from the provided seed.
+    pub fn new(seed: u64) -> Self {
+        LinearCongruential { state: seed }
+    }
+
+    /// Advances the generator and returns the next value, uniformly distributed in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        self.state = self
+            .state
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(Self::INCREMENT);
+
+        // Keeping the 53 high bits, which are the most random, as the mantissa.
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::constants::ELECTRON_MASS;
+
+    #[test]
+    fn test_maxwellian_velocity() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let temperature = 2.0;
+        let num_samples = 100000;
+
+        // Each velocity component should have a variance of kT / m.
+        let mut sum = 0.0;
+        let mut sum_squares = 0.0;
+
+        for _ in 0..num_samples {
+            let v = maxwellian_velocity(&mut rng, ELECTRON_MASS, temperature);
+            sum += v.x;
+            sum_squares += v.x * v.x;
+        }
+
+        let mean = sum / num_samples as f64;
+        let variance = sum_squares / num_samples as f64 - mean * mean;
+        let expected_variance = temperature * ELEMENTARY_CHARGE / ELECTRON_MASS;
+
+        assert!(mean.abs() < 0.02 * expected_variance.sqrt());
+        assert!((variance / expected_variance - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_sample_isotropic_direction() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let num_samples = 100000;
+
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        let mut sum_z_squares = 0.0;
+
+        for _ in 0..num_samples {
+            let direction = sample_isotropic_direction(&mut rng);
+            assert!((direction.dot(direction) - 1.0).abs() < 1e-12);
+
+            sum += direction;
+            sum_z_squares += direction.z * direction.z;
+        }
+
+        // The mean vanishes, while each squared component averages a third.
+        let mean = sum / num_samples as f64;
+        assert!(mean.dot(mean).sqrt() < 0.01);
+        assert!((sum_z_squares / num_samples as f64 - 1.0 / 3.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_sample_cosine_direction() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let num_samples = 100000;
+        let normal = Vec3::new(1.0, 2.0, -2.0);
+        let unit_normal = normal / 3.0;
+
+        let mut sum = Vec3::new(0.0, 0.0, 0.0);
+        let mut sum_cosines = 0.0;
+        let mut num_within_60_degrees = 0;
+
+        for _ in 0..num_samples {
+            let direction = sample_cosine_direction(normal, &mut rng);
+            assert!((direction.dot(direction) - 1.0).abs() < 1e-12);
+
+            let cos_theta = direction.dot(unit_normal);
+            assert!(cos_theta >= 0.0);
+
+            sum += direction;
+            sum_cosines += cos_theta;
+            if cos_theta > 0.5 {
+                num_within_60_degrees += 1;
+            }
+        }
+
+        // The mean direction lies along the normal, with a mean polar cosine of 2/3.
+        let mean = sum / num_samples as f64;
+        let tangential = mean - unit_normal * mean.dot(unit_normal);
+        assert!(tangential.dot(tangential).sqrt() < 0.01);
+        assert!((sum_cosines / num_samples as f64 - 2.0 / 3.0).abs() < 0.01);
+
+        // A fraction 1 - c^2 of the directions have a polar cosine above c.
+        assert!((num_within_60_degrees as f64 / num_samples as f64 - 0.75).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_linear_congruential() {
+        let mut generator = LinearCongruential::new(42);
+        let values: Vec<f64> = (0..1000).map(|_| generator.next_f64()).collect();
+
+        assert!(values.iter().all(|value| (0.0..1.0).contains(value)));
+
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert!((mean - 0.5).abs() < 0.05);
+
+        // The same seed always reproduces the same sequence.
+        let mut repeated = LinearCongruential::new(42);
+        assert!(values.iter().all(|&value| value == repeated.next_f64()));
+    }
+}
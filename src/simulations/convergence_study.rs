@@ -0,0 +1,137 @@
+use std::f64::consts::PI;
+use std::io::{Error, ErrorKind};
+
+use crate::constants::PERMITTIVITY;
+use crate::field::Field;
+use crate::mesh::{BoxMesh, Dimensions};
+use crate::solver::{optimal_relaxation_parameter, SolverError};
+use crate::vector::Vec3;
+
+/// The number of nodes along each axis of the meshes solved, in order of increasing resolution.
+const MESH_RESOLUTIONS: [usize; 4] = [5, 9, 17, 33];
+
+/// The length of each side of the cubic domain.
+const DOMAIN_LENGTH: f64 = 0.1;
+
+/// The amplitude of the exact potential.
+const POTENTIAL_AMPLITUDE: f64 = 1.0;
+
+const MAX_ITERATIONS: usize = 10000;
+const CONVERGENCE_TOLERANCE: f64 = 1e-4;
+
+/// Measures the spatial accuracy of the potential solver by solving the same problem on meshes
+/// of increasing resolution.
+///
+/// The charge density is a sinusoid vanishing on the grounded walls, for which the exact
+/// potential is a sinusoid of the same shape. The error against it should shrink with the
+/// square of the cell spacing for the second-order stencil.
+pub fn simulate() -> std::io::Result<()> {
+    let mut previous: Option<(f64, f64)> = None;
+
+    for num_mesh_nodes in MESH_RESOLUTIONS {
+        let (spacing, error) = potential_error(num_mesh_nodes)
+            .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+        match previous {
+            Some(coarse) => println!(
+                "Nodes: {}, spacing: {} m, L2 error: {} V, order: {}",
+                num_mesh_nodes,
+                spacing,
+                error,
+                convergence_order(coarse, (spacing, error))
+            ),
+            None => println!(
+                "Nodes: {}, spacing: {} m, L2 error: {} V",
+                num_mesh_nodes, spacing, error
+            ),
+        }
+
+        previous = Some((spacing, error));
+    }
+
+    Ok(())
+}
+
+/// Returns the exact potential at a position in the domain.
+fn exact_potential(position: Vec3) -> f64 {
+    let wavenumber = PI / DOMAIN_LENGTH;
+
+    POTENTIAL_AMPLITUDE
+        * (wavenumber * position.x).sin()
+        * (wavenumber * position.y).sin()
+        * (wavenumber * position.z).sin()
+}
+
+/// Solves the potential on a mesh with the provided number of nodes along each axis, returning
+/// the cell spacing along with the L2 error against the exact potential.
+///
+/// The error is integrated over the node volumes, so that it approximates the root mean square
+/// error over the domain independently of the fraction of nodes lying on the walls.
+fn potential_error(num_mesh_nodes: usize) -> Result<(f64, f64), SolverError> {
+    let dimensions = Dimensions::new(num_mesh_nodes, num_mesh_nodes, num_mesh_nodes);
+    let mut mesh = BoxMesh::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(DOMAIN_LENGTH, DOMAIN_LENGTH, DOMAIN_LENGTH),
+        dimensions,
+        1e-10,
+    );
+
+    // The Jacobi spectral radius of a cube is known exactly, giving the optimal relaxation.
+    let spectral_radius = (PI / (num_mesh_nodes - 1) as f64).cos();
+    mesh.set_relaxation_parameter(optimal_relaxation_parameter(spectral_radius));
+
+    // Since the Laplacian of the exact potential is proportional to it, so is the charge.
+    let wavenumber = PI / DOMAIN_LENGTH;
+    for i in 0..num_mesh_nodes {
+        for j in 0..num_mesh_nodes {
+            for k in 0..num_mesh_nodes {
+                let potential = exact_potential(*mesh.node_position(i, j, k));
+                mesh.charge_density_mut()[[i, j, k]] =
+                    3.0 * wavenumber * wavenumber * PERMITTIVITY * potential;
+            }
+        }
+    }
+
+    mesh.solve_potential(MAX_ITERATIONS, CONVERGENCE_TOLERANCE, None)?;
+
+    let mut squared_error = Field::<f64>::new(dimensions);
+    for i in 0..num_mesh_nodes {
+        for j in 0..num_mesh_nodes {
+            for k in 0..num_mesh_nodes {
+                let error =
+                    mesh.potential()[[i, j, k]] - exact_potential(*mesh.node_position(i, j, k));
+                squared_error[[i, j, k]] = error * error;
+            }
+        }
+    }
+
+    let error = (mesh.integrate(&squared_error) / mesh.domain_volume()).sqrt();
+
+    Ok((mesh.cell_spacings()[0], error))
+}
+
+/// Returns the observed order of convergence between the spacing and error of a coarse mesh
+/// and those of a finer mesh.
+fn convergence_order(coarse: (f64, f64), fine: (f64, f64)) -> f64 {
+    (coarse.1 / fine.1).ln() / (coarse.0 / fine.0).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_order_convergence() {
+        let coarse = potential_error(9).unwrap();
+        let fine = potential_error(17).unwrap();
+
+        assert!(fine.1 < coarse.1);
+
+        let order = convergence_order(coarse, fine);
+        assert!(
+            (1.8..=2.2).contains(&order),
+            "Order of convergence {}.",
+            order
+        );
+    }
+}
@@ -1,6 +1,11 @@
+use std::io::Error;
+
 use crate::constants::{ATOMIC_MASS_UNIT, ELECTRON_MASS, ELEMENTARY_CHARGE};
 use crate::mesh::{BoxMesh, Dimensions};
-use crate::output::vtk_output;
+use crate::output::{
+    diagnostic, vtk_output, vtp_particle_output, write_pvd_collection, VtkFieldSelection, VtkFormat,
+};
+use crate::solver::{optimal_relaxation_parameter, PotentialSolver, SolverError};
 use crate::species::Species;
 use crate::vector::Vec3;
 
@@ -8,6 +13,21 @@ const SIMULATION_ITERATIONS: usize = 10000;
 const MAX_ITERATIONS: usize = 4000;
 const CONVERGENCE_TOLERANCE: f64 = 1e-6;
 
+// Method used for full potential solves. Conjugate gradient needs far fewer iterations on
+// large meshes, while successive over-relaxation is kept as the default to compare against.
+const POTENTIAL_SOLVER: PotentialSolver = PotentialSolver::SuccessiveOverRelaxation;
+
+// Encoding of the VTI data arrays. Binary output is far smaller and faster to load in
+// ParaView than ASCII once the mesh grows beyond a few dozen nodes per side.
+const VTK_FORMAT: VtkFormat = VtkFormat::Binary;
+
+// Number of timesteps between full potential solves, if any. Between full solves, the
+// potential is only relaxed by a few sweeps starting from the previous solution, which is
+// adequate when the charge density changes little from one step to the next. `None` solves
+// fully at every step.
+const FULL_SOLVE_INTERVAL: Option<usize> = None;
+const RELAXATION_SWEEPS: usize = 10;
+
 pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
     // Note that the mesh dimensions must be high enough, relative to the distance
     // between the origin and maximum bound, that the maximum dimension of a cell is
@@ -21,8 +41,16 @@ pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
         mesh_dimensions,
         2e-10,
     );
+    grounded_box_mesh.set_potential_solver(POTENTIAL_SOLVER);
 
-    grounded_box_mesh.solve_potential(MAX_ITERATIONS, CONVERGENCE_TOLERANCE);
+    let spectral_radius = grounded_box_mesh.estimate_spectral_radius(100);
+    println!(
+        "Estimated Jacobi spectral radius: {} (optimal relaxation parameter: {})",
+        spectral_radius,
+        optimal_relaxation_parameter(spectral_radius)
+    );
+
+    solve_potential(&mut grounded_box_mesh)?;
     grounded_box_mesh.compute_electric_field();
 
     let mut species = vec![
@@ -57,6 +85,8 @@ pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
         &grounded_box_mesh,
     );
 
+    let mut output_indices = Vec::new();
+
     // Runing the simulation for 10,000 iterations.
     for iteration in 0..SIMULATION_ITERATIONS {
         println!("Iteration: {}", iteration);
@@ -65,7 +95,12 @@ pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
         grounded_box_mesh.compute_charge_density(&species);
 
         // Update potential.
-        grounded_box_mesh.solve_potential(MAX_ITERATIONS, CONVERGENCE_TOLERANCE);
+        match FULL_SOLVE_INTERVAL {
+            Some(interval) if iteration % interval != 0 => {
+                grounded_box_mesh.relax_potential(RELAXATION_SWEEPS);
+            }
+            _ => solve_potential(&mut grounded_box_mesh)?,
+        }
 
         // Update electric field.
         grounded_box_mesh.compute_electric_field();
@@ -76,11 +111,39 @@ pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
             s.compute_number_density(&grounded_box_mesh);
         }
 
+        grounded_box_mesh.advance_time();
+
+        // Recording runtime diagnostics every iteration.
+        diagnostic(&grounded_box_mesh, &species, iteration)?;
+
         // Outputing simulation state every so often.
         if iteration == 0 || iteration % 100 == 0 || iteration == SIMULATION_ITERATIONS {
-            vtk_output(&grounded_box_mesh, &species, iteration)?;
+            vtk_output(
+                &grounded_box_mesh,
+                &species,
+                iteration,
+                VtkFieldSelection::ALL,
+                VTK_FORMAT,
+            )?;
+            vtp_particle_output(&species, iteration)?;
+            output_indices.push(iteration);
         }
     }
 
+    // Linking the outputs as a time series for ParaView.
+    write_pvd_collection(&output_indices, grounded_box_mesh.timestep())?;
+
     Ok(())
 }
+
+/// Solves the potential, tolerating non-convergence but aborting on any other solver failure.
+fn solve_potential(mesh: &mut BoxMesh) -> std::io::Result<()> {
+    match mesh.solve_potential(MAX_ITERATIONS, CONVERGENCE_TOLERANCE, None) {
+        Ok(_) => Ok(()),
+        Err(error @ SolverError::NotConverged { .. }) => {
+            println!("{}", error);
+            Ok(())
+        }
+        Err(error) => Err(Error::other(error)),
+    }
+}
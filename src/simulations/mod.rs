@@ -1,2 +1,4 @@
+pub mod convergence_study;
 pub mod grounded_box;
+pub mod neutral_box;
 pub mod single_particle;
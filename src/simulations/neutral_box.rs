@@ -0,0 +1,154 @@
+use std::io::{Error, ErrorKind};
+
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use crate::config::SimulationConfig;
+use crate::constants::{ATOMIC_MASS_UNIT, ELECTRON_MASS, ELEMENTARY_CHARGE};
+use crate::mesh::{BoxMesh, Dimensions};
+use crate::output::{
+    vtk_output, vtp_particle_output, write_pvd_collection, VtkFieldSelection, VtkFormat,
+};
+use crate::solver::SolverError;
+use crate::species::{load_neutral_plasma, Species};
+use crate::state::SimulationState;
+use crate::vector::Vec3;
+
+const SIMULATION_ITERATIONS: usize = 10000;
+const MAX_ITERATIONS: usize = 4000;
+const CONVERGENCE_TOLERANCE: f64 = 1e-6;
+
+/// The number density of both the ions and electrons.
+const NUMBER_DENSITY: f64 = 1e11;
+
+/// The temperature of the electrons, in electron volts.
+const ELECTRON_TEMPERATURE: f64 = 1.0;
+
+/// Simulates a neutral plasma of cold ions and thermal electrons in a grounded box.
+///
+/// Unlike the grounded box simulation, both species are loaded at the same density over the
+/// whole box, so the plasma starts out quasineutral and remains so as the electrons thermally
+/// move about, apart from the sheaths forming at the walls.
+pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
+    let mut state = initial_state(num_mesh_nodes, 2e-10, 41, &mut rand::thread_rng())
+        .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+    println!("{}", state.mesh());
+
+    // Recording the configuration alongside the results, so that the run can be reproduced.
+    #[cfg(feature = "serde")]
+    {
+        std::fs::create_dir_all("results")?;
+        SimulationConfig::from_state(&state, None).write_effective("results/run_config.toml")?;
+    }
+
+    let mut output_indices = Vec::new();
+
+    for iteration in 0..SIMULATION_ITERATIONS {
+        println!("Iteration: {}", iteration);
+
+        match state.step() {
+            Ok(_) => {}
+            Err(error @ SolverError::NotConverged { .. }) => println!("{}", error),
+            Err(error) => return Err(Error::new(ErrorKind::InvalidData, error)),
+        }
+
+        // Outputing simulation state every so often.
+        if iteration % 100 == 0 {
+            println!(
+                "Net charge: {} C, field energy: {} J",
+                state.mesh().integrate(state.mesh().charge_density()),
+                state.mesh().field_energy()
+            );
+
+            vtk_output(
+                state.mesh(),
+                state.species(),
+                iteration,
+                VtkFieldSelection::ALL,
+                VtkFormat::Ascii,
+            )?;
+            vtp_particle_output(state.species(), iteration)?;
+            output_indices.push(iteration);
+        }
+    }
+
+    // Linking the outputs as a time series for ParaView.
+    write_pvd_collection(&output_indices, state.mesh().timestep())?;
+
+    Ok(())
+}
+
+/// Creates a grounded box filled with a neutral plasma of O+ ions and electrons, loading the
+/// provided number of particles of each species along each axis.
+fn initial_state<R: Rng>(
+    num_mesh_nodes: usize,
+    timestep: f64,
+    particles_per_dim: usize,
+    rng: &mut R,
+) -> Result<SimulationState, SolverError> {
+    let mesh = BoxMesh::new(
+        Vec3::new(-0.1, -0.1, -0.1),
+        Vec3::new(0.1, 0.1, 0.2),
+        Dimensions::new(num_mesh_nodes, num_mesh_nodes, num_mesh_nodes),
+        timestep,
+    );
+
+    let mut ions = Species::new(
+        String::from("O+"),
+        16.0 * ATOMIC_MASS_UNIT,
+        ELEMENTARY_CHARGE,
+        mesh.dimensions(),
+    );
+    let mut electrons = Species::new(
+        String::from("e-"),
+        ELECTRON_MASS,
+        -ELEMENTARY_CHARGE,
+        mesh.dimensions(),
+    );
+
+    load_neutral_plasma(
+        &mut ions,
+        &mut electrons,
+        mesh.origin(),
+        mesh.max_bound(),
+        NUMBER_DENSITY,
+        (particles_per_dim, particles_per_dim, particles_per_dim),
+        ELECTRON_TEMPERATURE,
+        &mesh,
+        rng,
+    );
+
+    SimulationState::new(
+        mesh,
+        vec![ions, electrons],
+        MAX_ITERATIONS,
+        CONVERGENCE_TOLERANCE,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_quasineutral() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut state = initial_state(11, 1e-9, 11, &mut rng).unwrap();
+
+        let ion_charge = ELEMENTARY_CHARGE * state.species()[0].num_real_particles();
+        let kinetic_energy = state.species()[1].kinetic_energy();
+
+        for _ in 0..20 {
+            state.step().unwrap();
+
+            let net_charge = state.mesh().integrate(state.mesh().charge_density());
+            assert!(net_charge.abs() < 1e-9 * ion_charge);
+
+            // The field only holds a small fraction of the thermal energy of the electrons.
+            assert!(state.mesh().field_energy() < 0.01 * kinetic_energy);
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::fs::File;
 use std::io::Write;
 
 use crate::constants::{ELECTRON_MASS, ELEMENTARY_CHARGE, PERMITTIVITY};
+use crate::solver::{SolverError, SolverReport};
 
 /// Maximum number of iterations for the potential solver.
 const MAX_ITERATIONS: usize = 4000;
@@ -31,7 +32,13 @@ pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
     let dx = (mesh_end - mesh_origin) / (num_mesh_nodes - 1) as f64;
 
     // Computing potential on mesh based on charge density.
-    solve_potential(&mut potential, &mut charge_density, dx);
+    match solve_potential(&mut potential, &mut charge_density, dx) {
+        Ok(report) => println!(
+            "Gauss-Seidel solver converged after {} iterations (residual: {}).",
+            report.iterations, report.residual
+        ),
+        Err(error) => println!("{}", error),
+    }
 
     // Computing electric field on mesh based on the potential.
     compute_electric_field(&mut potential, &mut electric_field, dx, true);
@@ -69,6 +76,8 @@ pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
     )
     .unwrap();
 
+    let mut positions = Vec::with_capacity(NUM_SIMULATION_TIMESTEPS);
+
     // Simulating motion of a single particle through an electric field.
     for ts in 1..=NUM_SIMULATION_TIMESTEPS {
         // Sampling mesh data at particle position.
@@ -79,6 +88,7 @@ pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
         let previous_position = position;
         velocity += (charge / mass) * interpolated_electric_field * dt;
         position += velocity * dt;
+        positions.push(position);
 
         // Interpolating the potential at the average position.
         let average_position = 0.5 * (position + previous_position);
@@ -118,15 +128,60 @@ pub fn simulate(num_mesh_nodes: usize) -> std::io::Result<()> {
         }
     }
 
+    println!(
+        "Measured oscillation frequency: {} Hz",
+        estimate_oscillation_frequency(&positions, dt)
+    );
+
     Ok(())
 }
 
+/// Estimates the frequency of an oscillating signal sampled at a fixed interval, in hertz.
+///
+/// The frequency is measured from the times at which the signal crosses its mean, which are
+/// linearly interpolated between samples. Returns zero if the signal crosses its mean fewer
+/// than twice, as no full half period is sampled.
+pub fn estimate_oscillation_frequency(positions: &[f64], dt: f64) -> f64 {
+    let mean = positions.iter().sum::<f64>() / positions.len() as f64;
+
+    let mut first_crossing = None;
+    let mut last_crossing = 0.0;
+    let mut num_crossings = 0;
+
+    for (index, pair) in positions.windows(2).enumerate() {
+        let (previous, next) = (pair[0] - mean, pair[1] - mean);
+
+        if (previous < 0.0) == (next < 0.0) {
+            continue;
+        }
+
+        let crossing = (index as f64 + previous / (previous - next)) * dt;
+        first_crossing.get_or_insert(crossing);
+        last_crossing = crossing;
+        num_crossings += 1;
+    }
+
+    match first_crossing {
+        // Consecutive crossings are half a period apart.
+        Some(first_crossing) if num_crossings >= 2 => {
+            0.5 * (num_crossings - 1) as f64 / (last_crossing - first_crossing)
+        }
+        _ => 0.0,
+    }
+}
+
 /// Solves the potential field.
-fn solve_potential(potential: &mut Vec<f64>, charge_density: &mut Vec<f64>, dx: f64) {
+fn solve_potential(
+    potential: &mut Vec<f64>,
+    charge_density: &mut Vec<f64>,
+    dx: f64,
+) -> Result<SolverReport, SolverError> {
     let dx2 = dx * dx;
     let relaxation_parameter: f64 = 1.4;
     let num_mesh_nodes = potential.len();
 
+    let mut residue_l2_norm = f64::INFINITY;
+
     // Iterating until convergence or the maximum number of iterations have been applied.
     for iteration in 0..MAX_ITERATIONS {
         // Specifying Dirichlet boundary conditions on edges of mesh.
@@ -151,24 +206,27 @@ fn solve_potential(potential: &mut Vec<f64>, charge_density: &mut Vec<f64>, dx:
                 residue_sum += residue_component * residue_component;
             }
 
-            let residue_l2_norm = (residue_sum).sqrt() / num_mesh_nodes as f64;
+            residue_l2_norm = (residue_sum).sqrt() / num_mesh_nodes as f64;
+
+            if !residue_l2_norm.is_finite() {
+                return Err(SolverError::NonFiniteResidual);
+            }
 
             // Convergence implies we've found a solution, so we return.
             if residue_l2_norm < CONVERGENCE_TOLERANCE {
-                println!(
-                    "Gauss-Seidel solver converged after {} iterations.",
-                    iteration
-                );
-
-                return;
+                return Ok(SolverReport {
+                    iterations: iteration + 1,
+                    residual: residue_l2_norm,
+                    max_potential_change: None,
+                });
             }
         }
     }
 
-    println!(
-        "Gauss-Seidel solver failed to converge after {} iterations.",
-        MAX_ITERATIONS
-    );
+    Err(SolverError::NotConverged {
+        iterations: MAX_ITERATIONS,
+        residual: residue_l2_norm,
+    })
 }
 
 /// Computes the electric field.
@@ -205,10 +263,17 @@ fn position_to_logical_coordinate(position: f64, dx: f64, mesh_origin: f64) -> f
 }
 
 /// Interpolates field values at points between mesh nodes.
+///
+/// Logical coordinates outside of the mesh are clamped to its ends, so that a particle which
+/// momentarily leaves the domain samples the boundary value rather than indexing out of bounds.
 fn gather(logical_coordinate: f64, field: &mut Vec<f64>) -> f64 {
-    let left_node_index = logical_coordinate.trunc() as usize;
+    let last_node_index = field.len() - 1;
+    let logical_coordinate = logical_coordinate.max(0.0).min(last_node_index as f64);
+
+    // Using the last cell for coordinates on the final node.
+    let left_node_index = (logical_coordinate.trunc() as usize).min(last_node_index - 1);
     let right_node_index = left_node_index + 1;
-    let fractional_distance = logical_coordinate.fract();
+    let fractional_distance = logical_coordinate - left_node_index as f64;
 
     return field[left_node_index] * (1.0 - fractional_distance)
         + field[right_node_index] * fractional_distance;
@@ -231,3 +296,55 @@ fn _output_simulation_state(
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gather_out_of_domain() {
+        let mut field = vec![1.0, 2.0, 4.0, 8.0];
+
+        assert_eq!(gather(1.5, &mut field), 3.0);
+        assert_eq!(gather(-1e-3, &mut field), 1.0);
+        assert_eq!(gather(3.0, &mut field), 8.0);
+        assert_eq!(gather(3.0 + 1e-3, &mut field), 8.0);
+    }
+
+    #[test]
+    fn test_electric_field_of_linear_ramp() {
+        let dx = 0.01;
+        let slope = 250.0;
+
+        for second_order_boundary_approx in [false, true] {
+            let mut potential: Vec<f64> = (0..11).map(|i| 3.0 + slope * i as f64 * dx).collect();
+            let mut electric_field = vec![0.0; potential.len()];
+
+            compute_electric_field(
+                &mut potential,
+                &mut electric_field,
+                dx,
+                second_order_boundary_approx,
+            );
+
+            // Every difference is exact for a linear potential, including on the boundaries.
+            for value in electric_field {
+                assert!((value + slope).abs() < 1e-9 * slope);
+            }
+        }
+    }
+
+    #[test]
+    fn test_estimate_oscillation_frequency() {
+        let frequency = 8.9e6;
+        let dt = 1e-10;
+        let positions: Vec<f64> = (0..5000)
+            .map(|ts| 0.05 + 0.01 * (2.0 * std::f64::consts::PI * frequency * ts as f64 * dt).cos())
+            .collect();
+
+        let estimate = estimate_oscillation_frequency(&positions, dt);
+        assert!((estimate - frequency).abs() < 0.02 * frequency);
+
+        assert_eq!(estimate_oscillation_frequency(&[1.0, 2.0, 3.0], dt), 0.0);
+    }
+}
@@ -0,0 +1,223 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::boundary::Face;
+
+/// Represents a method for solving the potential of a box mesh.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PotentialSolver {
+    /// Gauss-Seidel with successive over-relaxation, which supports every boundary condition.
+    #[default]
+    SuccessiveOverRelaxation,
+    /// Conjugate gradient, which converges in far fewer iterations on large meshes, but only
+    /// supports faces with a prescribed potential and periodic axes.
+    ConjugateGradient,
+}
+
+/// The number of iterations between checks for convergence of the SOR potential solvers.
+const CONVERGENCE_CHECK_RATE: usize = 25;
+
+/// The number of consecutive growing residues after which an SOR potential solver is deemed
+/// to be diverging.
+const DIVERGENCE_CHECKS: usize = 3;
+
+/// Checks that an SOR relaxation parameter lies within the open interval (0, 2), outside of
+/// which the iteration doesn't converge.
+pub fn validate_relaxation_parameter(relaxation_parameter: f64) -> Result<(), SolverError> {
+    if relaxation_parameter > 0.0 && relaxation_parameter < 2.0 {
+        Ok(())
+    } else {
+        Err(SolverError::InvalidRelaxation(relaxation_parameter))
+    }
+}
+
+/// Returns the optimal SOR relaxation parameter for a Jacobi iteration matrix with the
+/// provided spectral radius, as given by Young's formula.
+pub fn optimal_relaxation_parameter(spectral_radius: f64) -> f64 {
+    2.0 / (1.0 + (1.0 - spectral_radius * spectral_radius).sqrt())
+}
+
+/// Summarizes the outcome of a converged potential solve.
+#[derive(Clone, Copy, Debug)]
+pub struct SolverReport {
+    /// The number of iterations applied before convergence.
+    pub iterations: usize,
+    /// The L2 norm of the residue at the final convergence check.
+    pub residual: f64,
+    /// The largest absolute change of the potential over the final sweep, in volts, for
+    /// solvers which track it.
+    pub max_potential_change: Option<f64>,
+}
+
+/// Represents the ways in which a potential solve can fail.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SolverError {
+    /// The residue didn't fall below the tolerance within the iteration budget.
+    NotConverged { iterations: usize, residual: f64 },
+    /// The residue became NaN or infinite, usually due to non-finite charge density.
+    NonFiniteResidual,
+    /// The residue grew over several consecutive convergence checks.
+    Diverging { iteration: usize },
+    /// The relaxation parameter lies outside of the range in which SOR is stable.
+    InvalidRelaxation(f64),
+    /// The solver doesn't support the boundary condition on a face.
+    UnsupportedBoundary(Face),
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::NotConverged {
+                iterations,
+                residual,
+            } => write!(
+                f,
+                "Solver failed to converge after {} iterations (residual: {}).",
+                iterations, residual
+            ),
+            SolverError::NonFiniteResidual => write!(f, "Solver residual is not finite."),
+            SolverError::Diverging { iteration } => {
+                write!(f, "Solver diverged as of iteration {}.", iteration)
+            }
+            SolverError::InvalidRelaxation(relaxation_parameter) => write!(
+                f,
+                "Relaxation parameter {} is outside of the interval (0, 2).",
+                relaxation_parameter
+            ),
+            SolverError::UnsupportedBoundary(face) => write!(
+                f,
+                "Solver doesn't support the boundary condition on face {:?}.",
+                face
+            ),
+        }
+    }
+}
+
+impl Error for SolverError {}
+
+/// Iterates an SOR potential solver on a mesh until its residue falls below the tolerance.
+///
+/// Each iteration applies `sweep`, which relaxes the potential along with its boundary
+/// conditions and returns the largest absolute change of the potential. The L2 norm of the
+/// residue is measured by `residual` every so many iterations and after the last one, and
+/// the solve is aborted once it grows over several consecutive checks. When provided,
+/// `residual_history` is extended with the iteration and residual of each check.
+pub(crate) fn solve_sor<M, S, R>(
+    mesh: &mut M,
+    max_solver_iterations: usize,
+    tolerance: f64,
+    mut residual_history: Option<&mut Vec<(usize, f64)>>,
+    mut sweep: S,
+    residual: R,
+) -> Result<SolverReport, SolverError>
+where
+    S: FnMut(&mut M) -> f64,
+    R: Fn(&M) -> f64,
+{
+    let mut residue_l2_norm = residual(mesh);
+    let mut num_growing_residues = 0;
+
+    for iteration in 0..max_solver_iterations {
+        let max_potential_change = sweep(mesh);
+
+        // Checking for convergence.
+        if (iteration != 0 && iteration % CONVERGENCE_CHECK_RATE == 0)
+            || iteration == max_solver_iterations - 1
+        {
+            let previous_residue_l2_norm = residue_l2_norm;
+            residue_l2_norm = residual(mesh);
+
+            if let Some(history) = residual_history.as_mut() {
+                history.push((iteration, residue_l2_norm));
+            }
+
+            if !residue_l2_norm.is_finite() {
+                return Err(SolverError::NonFiniteResidual);
+            }
+
+            // Checking for a residue which keeps growing rather than shrinking.
+            if residue_l2_norm > previous_residue_l2_norm {
+                num_growing_residues += 1;
+
+                if num_growing_residues >= DIVERGENCE_CHECKS {
+                    return Err(SolverError::Diverging { iteration });
+                }
+            } else {
+                num_growing_residues = 0;
+            }
+
+            if residue_l2_norm < tolerance {
+                return Ok(SolverReport {
+                    iterations: iteration + 1,
+                    residual: residue_l2_norm,
+                    max_potential_change: Some(max_potential_change),
+                });
+            }
+        }
+    }
+
+    Err(SolverError::NotConverged {
+        iterations: max_solver_iterations,
+        residual: residue_l2_norm,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_relaxation_parameter() {
+        assert_eq!(validate_relaxation_parameter(1.4), Ok(()));
+
+        for relaxation_parameter in [0.0, -0.5, 2.0, 2.5, f64::INFINITY] {
+            assert_eq!(
+                validate_relaxation_parameter(relaxation_parameter),
+                Err(SolverError::InvalidRelaxation(relaxation_parameter))
+            );
+        }
+        assert!(validate_relaxation_parameter(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_optimal_relaxation_parameter() {
+        // Gauss-Seidel is optimal without coupling, while strong coupling approaches 2.
+        assert_eq!(optimal_relaxation_parameter(0.0), 1.0);
+        assert!((optimal_relaxation_parameter(0.8) - 1.25).abs() < 1e-12);
+        assert!(optimal_relaxation_parameter(0.999) > 1.9);
+    }
+
+    #[test]
+    fn test_solve_sor() {
+        // Standing in for a mesh with a residue scaled by each sweep.
+        let sweep = |factor: f64| {
+            move |residue: &mut f64| {
+                let change = (factor - 1.0).abs() * *residue;
+                *residue *= factor;
+                change
+            }
+        };
+
+        let mut history = Vec::new();
+        let report = solve_sor(
+            &mut 1.0,
+            1000,
+            1e-3,
+            Some(&mut history),
+            sweep(0.5),
+            |residue| *residue,
+        )
+        .unwrap();
+        assert_eq!(report.iterations, CONVERGENCE_CHECK_RATE + 1);
+        assert_eq!(history, vec![(CONVERGENCE_CHECK_RATE, report.residual)]);
+        assert_eq!(report.max_potential_change, Some(report.residual));
+
+        assert_eq!(
+            solve_sor(&mut 1.0, 1000, 1e-3, None, sweep(2.0), |residue| *residue).unwrap_err(),
+            SolverError::Diverging {
+                iteration: DIVERGENCE_CHECKS * CONVERGENCE_CHECK_RATE
+            }
+        );
+    }
+}
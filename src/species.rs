@@ -1,14 +1,72 @@
+use std::error::Error;
+use std::fmt;
 use std::string::String;
 
 use rand;
+use rand::seq::SliceRandom;
 use rand::Rng;
 
-use crate::field::Field;
+use crate::boundary::{BoundaryMode, Face};
+use crate::constants::ELEMENTARY_CHARGE;
+use crate::field::{Field, Weighting};
 use crate::mesh::{BoxMesh, Dimensions};
 use crate::particle::Particle;
+use crate::plasma;
+use crate::sampling::{maxwellian_velocity, LinearCongruential};
 use crate::vector::Vec3;
 
+/// Represents a scheme for integrating the motion of particles over a timestep.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Integrator {
+    /// Kicks the velocity by the field at the current position, then drifts the position by
+    /// the new velocity. Velocities are stored half a timestep behind positions.
+    #[default]
+    Leapfrog,
+    /// Kicks the velocity by the field gathered at the midpoint of the step, which is
+    /// predicted from the current velocity and corrected once. Velocities are stored at the
+    /// same time as positions, and energy is conserved more closely in nonlinear fields at
+    /// the cost of a second field gather per step.
+    Midpoint,
+}
+
+/// Represents what happens when a particle is added to a species already holding its maximum
+/// number of particles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The new particle is silently discarded.
+    #[default]
+    RejectNew,
+    /// The oldest particle is merged with the next oldest one of the same charge state and
+    /// mass to make room for the new particle, which is discarded if there is no such pair.
+    MergeOldest,
+    /// Adding the particle fails with `ParticleError::LimitExceeded`.
+    Error,
+}
+
+/// Represents the ways in which adding a particle to a species can fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleError {
+    /// The species already holds its maximum number of particles.
+    LimitExceeded { max_particles: usize },
+}
+
+impl fmt::Display for ParticleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParticleError::LimitExceeded { max_particles } => write!(
+                f,
+                "Species already holds its maximum of {} particles.",
+                max_particles
+            ),
+        }
+    }
+}
+
+impl Error for ParticleError {}
+
 /// Represents a species of particle.
+#[derive(Clone)]
 pub struct Species {
     /// The name of the species.
     name: String,
@@ -18,8 +76,24 @@ pub struct Species {
     charge: f64,
     /// The number density of the species.
     number_density: Field<f64>,
+    /// The charge density of the species, accounting for particle charge states.
+    charge_density: Field<f64>,
     /// The particles within the species.
     particles: Vec<Particle>,
+    /// The charge collected by each absorbing face during the last advance.
+    collected_charge: [f64; 6],
+    /// Whether the particles of the species are pushed when advancing.
+    mobile: bool,
+    /// The scheme used to weight particles onto the mesh.
+    weighting: Weighting,
+    /// The scheme used to integrate the motion of the particles.
+    integrator: Integrator,
+    /// The fraction of a macroparticle left over from previous injections.
+    injection_remainder: f64,
+    /// The maximum number of particles, if limited.
+    max_particles: Option<usize>,
+    /// What happens when adding a particle would exceed the maximum number of particles.
+    overflow_policy: OverflowPolicy,
 }
 
 impl Species {
@@ -30,7 +104,15 @@ impl Species {
             mass: mass,
             charge: charge,
             number_density: Field::<f64>::new(mesh_dimensions),
+            charge_density: Field::<f64>::new(mesh_dimensions),
             particles: Vec::<Particle>::new(),
+            collected_charge: [0.0; 6],
+            mobile: true,
+            weighting: Weighting::default(),
+            integrator: Integrator::default(),
+            injection_remainder: 0.0,
+            max_particles: None,
+            overflow_policy: OverflowPolicy::default(),
         }
     }
 
@@ -39,17 +121,371 @@ impl Species {
         self.name.clone()
     }
 
+    /// Returns the mass of the particles in the species.
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
     /// Returns the charge of the particles in the species.
     pub fn charge(&self) -> f64 {
         self.charge
     }
 
+    /// Returns whether the particles of the species are pushed when advancing.
+    pub fn is_mobile(&self) -> bool {
+        self.mobile
+    }
+
+    /// Sets whether the particles of the species are pushed when advancing.
+    ///
+    /// An immobile species still contributes to the number and charge densities, which
+    /// allows heavy ions to be treated as a static neutralizing background.
+    pub fn set_mobile(&mut self, mobile: bool) {
+        self.mobile = mobile;
+    }
+
+    /// Returns the scheme used to weight particles onto the mesh.
+    pub fn weighting(&self) -> Weighting {
+        self.weighting
+    }
+
+    /// Sets the scheme used to weight particles onto the mesh when computing densities.
+    pub fn set_weighting(&mut self, weighting: Weighting) {
+        self.weighting = weighting;
+    }
+
+    /// Returns the scheme used to integrate the motion of the particles.
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+
+    /// Sets the scheme used to integrate the motion of the particles when advancing.
+    ///
+    /// This should be set before any particle is added, since the schemes store velocities at
+    /// different times. Particles already in the species keep their velocities, which for
+    /// leapfrog were rewound by half a timestep when added, and aren't converted.
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    /// Returns the maximum number of particles, if limited.
+    pub fn max_particles(&self) -> Option<usize> {
+        self.max_particles
+    }
+
+    /// Returns what happens when adding a particle would exceed the maximum number of
+    /// particles.
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    /// Limits the number of particles, applying the policy whenever adding a particle would
+    /// exceed the limit, or removes the limit when `None`.
+    ///
+    /// This bounds the memory of runs with heavy injection. Particles already beyond the limit
+    /// are kept. Under the `Error` policy, `try_add_particle` and `inject_rate` return the
+    /// error, while the other ways of adding particles, including the loaders, panic.
+    pub fn set_max_particles(&mut self, max_particles: Option<usize>, policy: OverflowPolicy) {
+        self.max_particles = max_particles;
+        self.overflow_policy = policy;
+    }
+
+    /// Returns the number of real particles represented by the macroparticles.
+    pub fn num_real_particles(&self) -> f64 {
+        self.particles
+            .iter()
+            .map(|particle| particle.macroparticle_weight)
+            .sum()
+    }
+
+    /// Returns the particles within the species, for analysis without copying them.
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Returns whether the particles of another species have the same positions and
+    /// velocities, in order, with each component differing by at most epsilon.
+    pub fn particles_approx_eq(&self, other: &Species, epsilon: f64) -> bool {
+        self.particles.len() == other.particles.len()
+            && self.particles.iter().zip(&other.particles).all(|(p, q)| {
+                p.position.approx_eq(q.position, epsilon)
+                    && p.velocity.approx_eq(q.velocity, epsilon)
+            })
+    }
+
     /// Returns the number density of the species.
     pub fn number_density(&self) -> Field<f64> {
         self.number_density.clone()
     }
 
+    /// Returns the charge density of the species.
+    pub fn charge_density(&self) -> Field<f64> {
+        self.charge_density.clone()
+    }
+
+    /// Returns the weight-averaged position of the particles, or `None` if there are none.
+    pub fn centroid(&self) -> Option<Vec3> {
+        let total_weight = self.num_real_particles();
+
+        if self.particles.is_empty() || total_weight == 0.0 {
+            return None;
+        }
+
+        let mut weighted_sum = Vec3::new(0.0, 0.0, 0.0);
+        for particle in &self.particles {
+            weighted_sum += particle.position * particle.macroparticle_weight;
+        }
+
+        Some(weighted_sum / total_weight)
+    }
+
+    /// Returns the total momentum of the particles, computed from the stored velocities, which
+    /// lag the positions by half a timestep.
+    pub fn momentum(&self) -> Vec3 {
+        let mut momentum = Vec3::new(0.0, 0.0, 0.0);
+        for particle in &self.particles {
+            momentum +=
+                particle.velocity * (self.particle_mass(particle) * particle.macroparticle_weight);
+        }
+
+        momentum
+    }
+
+    /// Returns the total kinetic energy of the particles, computed from the stored velocities,
+    /// which lag the positions by half a timestep.
+    pub fn kinetic_energy(&self) -> f64 {
+        let mut energy = 0.0;
+        for particle in &self.particles {
+            energy += self.particle_mass(particle)
+                * particle.macroparticle_weight
+                * particle.velocity.dot(particle.velocity);
+        }
+
+        0.5 * energy
+    }
+
+    /// Returns the velocity of each particle advanced by half a timestep in the mesh electric
+    /// field, so that it refers to the same time as the position.
+    ///
+    /// Leapfrog stores velocities half a timestep behind positions, which biases diagnostics
+    /// such as the kinetic energy. The particles themselves are left untouched. Velocities of
+    /// the midpoint integrator are already synchronized, so they are returned as is.
+    pub fn synchronized_velocity(&self, mesh: &BoxMesh) -> Vec<Vec3> {
+        let half_timestep = 0.5 * mesh.timestep();
+
+        self.particles
+            .iter()
+            .map(|particle| {
+                if !self.mobile || self.integrator == Integrator::Midpoint {
+                    return particle.velocity;
+                }
+
+                let lc = mesh.position_to_logical_coordinate(particle.position);
+                let electric_field = mesh.electric_field().gather(lc);
+
+                particle.velocity
+                    + electric_field * (self.charge_to_mass_ratio(particle) * half_timestep)
+            })
+            .collect()
+    }
+
+    /// Returns the total kinetic energy of the particles, computed from the velocities
+    /// synchronized with the positions.
+    pub fn synchronized_kinetic_energy(&self, mesh: &BoxMesh) -> f64 {
+        let mut energy = 0.0;
+        for (particle, velocity) in self.particles.iter().zip(self.synchronized_velocity(mesh)) {
+            energy += self.particle_mass(particle)
+                * particle.macroparticle_weight
+                * velocity.dot(velocity);
+        }
+
+        0.5 * energy
+    }
+
+    /// Returns the mean velocity of the particles weighted onto a mesh node, computed from the
+    /// velocities synchronized with the positions.
+    ///
+    /// Particles are weighted with the scheme of the species, as when computing the number
+    /// density. Returns zero if no particle contributes to the node.
+    pub fn drift_velocity_at(&self, node: (usize, usize, usize), mesh: &BoxMesh) -> Vec3 {
+        let mut flux = Field::<Vec3>::new(mesh.dimensions());
+        let mut weight = Field::<f64>::new(mesh.dimensions());
+
+        for (particle, velocity) in self.particles.iter().zip(self.synchronized_velocity(mesh)) {
+            let lc = mesh.position_to_logical_coordinate(particle.position);
+            flux.scatter_weighted(lc, velocity * particle.macroparticle_weight, self.weighting);
+            weight.scatter_weighted(lc, particle.macroparticle_weight, self.weighting);
+        }
+
+        let index = [node.0, node.1, node.2];
+        if weight[index] > 0.0 {
+            flux[index] * (1.0 / weight[index])
+        } else {
+            Vec3::new(0.0, 0.0, 0.0)
+        }
+    }
+
+    /// Returns whether the particles at a sheath edge node drift towards a wall at least at
+    /// the Bohm speed, `sqrt(k_B Te / m_i)`, for the provided electron temperature, in
+    /// electron volts.
+    ///
+    /// The Bohm criterion requires ions to enter the sheath at the ion sound speed for a
+    /// stable sheath to form, so this is meant for ion species. A relative tolerance allows
+    /// for rounding when averaging drifts at exactly the Bohm speed.
+    pub fn satisfies_bohm_criterion(
+        &self,
+        node: (usize, usize, usize),
+        wall: Face,
+        electron_temperature: f64,
+        mesh: &BoxMesh,
+    ) -> bool {
+        let bohm_speed = (electron_temperature * ELEMENTARY_CHARGE / self.mass).sqrt();
+
+        let drift = self.drift_velocity_at(node, mesh)[wall.axis().index()];
+        let drift_towards_wall = if wall.is_min() { -drift } else { drift };
+
+        drift_towards_wall >= bohm_speed * (1.0 - 1e-12)
+    }
+
+    /// Returns the temperature of the particles, in electron volts, found from the spread of
+    /// their velocities synchronized with the positions about the mean velocity.
+    ///
+    /// The spread is averaged over all three components and weighted by the macroparticle
+    /// weights. Returns zero if there are no particles.
+    pub fn temperature(&self, mesh: &BoxMesh) -> f64 {
+        let total_weight = self.num_real_particles();
+
+        if self.particles.is_empty() || total_weight == 0.0 {
+            return 0.0;
+        }
+
+        2.0 * self.thermal_energy(mesh) / (3.0 * total_weight * ELEMENTARY_CHARGE)
+    }
+
+    /// Returns the kinetic energy of the random motion of the particles, about their mean
+    /// velocity, computed from the velocities synchronized with the positions.
+    ///
+    /// Unlike the total kinetic energy, this excludes the energy of any bulk drift, so that it
+    /// isolates heating. Returns zero if there are no particles.
+    pub fn thermal_energy(&self, mesh: &BoxMesh) -> f64 {
+        let total_weight = self.num_real_particles();
+
+        if self.particles.is_empty() || total_weight == 0.0 {
+            return 0.0;
+        }
+
+        let velocities = self.synchronized_velocity(mesh);
+
+        let mut mean_velocity = Vec3::new(0.0, 0.0, 0.0);
+        for (particle, &velocity) in self.particles.iter().zip(&velocities) {
+            mean_velocity += velocity * particle.macroparticle_weight;
+        }
+        mean_velocity = mean_velocity / total_weight;
+
+        let mut thermal_energy = 0.0;
+        for (particle, &velocity) in self.particles.iter().zip(&velocities) {
+            let deviation = velocity - mean_velocity;
+            thermal_energy += self.particle_mass(particle)
+                * particle.macroparticle_weight
+                * deviation.dot(deviation);
+        }
+
+        0.5 * thermal_energy
+    }
+
+    /// Returns the Debye length of the species, in meters, from its own temperature and its
+    /// number density averaged over the domain.
+    ///
+    /// Species in the same plasma may have very different Debye lengths, such as cold ions and
+    /// hot electrons. The number density must have been computed. Returns infinity if nothing
+    /// is deposited on the mesh.
+    pub fn debye_length(&self, mesh: &BoxMesh) -> f64 {
+        let number_density = self.deposited_total(mesh) / mesh.domain_volume();
+
+        plasma::debye_length(self.temperature(mesh), number_density)
+    }
+
+    /// Returns the potential energy of the particles in the mesh potential, interpolated to
+    /// each particle position. Particles on the faces of the mesh gather the potential there,
+    /// while particles outside of the mesh contribute nothing.
+    pub fn potential_energy(&self, mesh: &BoxMesh) -> f64 {
+        let mut energy = 0.0;
+        for particle in &self.particles {
+            let lc = mesh.position_to_logical_coordinate(particle.position);
+            let potential = mesh.potential().gather_closed(lc);
+
+            energy += f64::from(particle.charge_state) * particle.macroparticle_weight * potential;
+        }
+
+        self.charge * energy
+    }
+
+    /// Adds a uniform drift velocity to every particle.
+    ///
+    /// Since the drift is constant in time, adding it to the half-step velocities stored for
+    /// the leapfrog integrator is equivalent to adding it to the synchronized velocities.
+    pub fn add_drift(&mut self, drift: Vec3) {
+        for particle in &mut self.particles {
+            particle.velocity += drift;
+        }
+    }
+
+    /// Multiplies the macroparticle weight of every particle by a factor.
+    ///
+    /// This changes the number of real particles each macroparticle represents without
+    /// reloading, such as when merging two loaded populations. The densities are only updated
+    /// by the next call to `compute_number_density`.
+    pub fn rescale_weights(&mut self, factor: f64) {
+        for particle in &mut self.particles {
+            particle.macroparticle_weight *= factor;
+        }
+    }
+
+    /// Returns the number of real particles deposited on the mesh, found by integrating the
+    /// number density over the node volumes.
+    ///
+    /// This matches `num_real_particles` for particles within the mesh whenever the weighting
+    /// scheme conserves the deposited total.
+    pub fn deposited_total(&self, mesh: &BoxMesh) -> f64 {
+        mesh.integrate(&self.number_density)
+    }
+
+    /// Returns the peak number density and the indices of the node where it occurs.
+    pub fn peak_density(&self) -> (f64, (usize, usize, usize)) {
+        self.number_density.max_with_index()
+    }
+
+    /// Returns the mean number density over the nodes at least `margin_cells` away from each
+    /// boundary of the mesh, or zero if the margin leaves no nodes.
+    ///
+    /// Excluding the nodes near the walls avoids biasing the bulk density with sheaths.
+    pub fn average_density(&self, mesh: &BoxMesh, margin_cells: usize) -> f64 {
+        let dimensions = mesh.dimensions();
+        let interior = |n: usize| margin_cells..n.saturating_sub(margin_cells);
+
+        let mut sum = 0.0;
+        let mut node_count = 0;
+
+        for i in interior(dimensions.x) {
+            for j in interior(dimensions.y) {
+                for k in interior(dimensions.z) {
+                    sum += self.number_density[[i, j, k]];
+                    node_count += 1;
+                }
+            }
+        }
+
+        if node_count == 0 {
+            return 0.0;
+        }
+
+        sum / node_count as f64
+    }
+
     /// Adds a particle.
+    ///
+    /// Panics if the species holds its maximum number of particles with the `Error` overflow
+    /// policy, see `try_add_particle`.
     pub fn add_particle(
         &mut self,
         position: Vec3,
@@ -57,70 +493,416 @@ impl Species {
         macroparticle_weight: f64,
         mesh: &BoxMesh,
     ) {
-        let lc = mesh.position_to_logical_coordinate(position);
-        let electric_field = mesh.electric_field().gather(lc);
+        self.add_particle_with_charge_state(position, velocity, macroparticle_weight, 1, mesh);
+    }
+
+    /// Adds a particle, failing if the species holds its maximum number of particles with the
+    /// `Error` overflow policy.
+    ///
+    /// Returns whether the particle was added, which it isn't when the overflow policy rejects
+    /// it.
+    pub fn try_add_particle(
+        &mut self,
+        position: Vec3,
+        velocity: Vec3,
+        macroparticle_weight: f64,
+        mesh: &BoxMesh,
+    ) -> Result<bool, ParticleError> {
+        self.push_particle(
+            Particle::new(position, velocity, macroparticle_weight),
+            mesh,
+        )
+    }
+
+    /// Adds a particle carrying a multiple of the species charge.
+    pub fn add_particle_with_charge_state(
+        &mut self,
+        position: Vec3,
+        velocity: Vec3,
+        macroparticle_weight: f64,
+        charge_state: i8,
+        mesh: &BoxMesh,
+    ) {
+        let particle =
+            Particle::with_charge_state(position, velocity, macroparticle_weight, charge_state);
 
-        let updated_velocity =
-            velocity - electric_field * (self.charge / self.mass) * (0.5 * mesh.timestep());
+        self.push_particle(particle, mesh)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
 
-        self.particles.push(Particle::new(
-            position,
-            updated_velocity,
-            macroparticle_weight,
-        ));
+    /// Adds a particle whose mass differs from that of the species, such as another isotope.
+    pub fn add_particle_with_mass(
+        &mut self,
+        position: Vec3,
+        velocity: Vec3,
+        macroparticle_weight: f64,
+        mass: f64,
+        mesh: &BoxMesh,
+    ) {
+        let particle = Particle::new(position, velocity, macroparticle_weight).with_mass(mass);
+
+        self.push_particle(particle, mesh)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    /// Adds a particle, applying the overflow policy if the species is full, and returns
+    /// whether the particle was added.
+    ///
+    /// For the leapfrog integrator, the velocity is first rewound by half a timestep. The
+    /// midpoint integrator stores velocities at the same time as positions, so it is kept.
+    fn push_particle(
+        &mut self,
+        mut particle: Particle,
+        mesh: &BoxMesh,
+    ) -> Result<bool, ParticleError> {
+        if let Some(max_particles) = self.max_particles {
+            if self.particles.len() >= max_particles {
+                match self.overflow_policy {
+                    OverflowPolicy::RejectNew => return Ok(false),
+                    OverflowPolicy::MergeOldest => {
+                        // Rejecting the particle when there aren't two matching particles to merge.
+                        if !self.merge_oldest_particles() {
+                            return Ok(false);
+                        }
+                    }
+                    OverflowPolicy::Error => {
+                        return Err(ParticleError::LimitExceeded { max_particles })
+                    }
+                }
+            }
+        }
+
+        if self.integrator == Integrator::Leapfrog {
+            let lc = mesh.position_to_logical_coordinate(particle.position);
+            let electric_field = mesh.electric_field().gather(lc);
+
+            particle.velocity -=
+                electric_field * self.charge_to_mass_ratio(&particle) * (0.5 * mesh.timestep());
+        }
+
+        self.particles.push(particle);
+
+        Ok(true)
+    }
+
+    /// Merges the oldest particle with the next oldest one of the same charge state and mass
+    /// into one at their weighted mean position and velocity, returning whether there was such
+    /// a pair to merge.
+    ///
+    /// The total weight, charge and momentum are conserved. The newest particle takes the
+    /// place of the one merged away, so the particles are only roughly kept in order of age.
+    fn merge_oldest_particles(&mut self) -> bool {
+        if self.particles.is_empty() {
+            return false;
+        }
+
+        let oldest = &self.particles[0];
+        let matching = self.particles[1..].iter().position(|particle| {
+            particle.charge_state == oldest.charge_state && particle.mass == oldest.mass
+        });
+
+        let index = match matching {
+            Some(index) => index + 1,
+            None => return false,
+        };
+
+        let second = self.particles.swap_remove(index);
+        let first = &mut self.particles[0];
+        let weight = first.macroparticle_weight + second.macroparticle_weight;
+
+        first.position = (first.position * first.macroparticle_weight
+            + second.position * second.macroparticle_weight)
+            / weight;
+        first.velocity = (first.velocity * first.macroparticle_weight
+            + second.velocity * second.macroparticle_weight)
+            / weight;
+        first.macroparticle_weight = weight;
+
+        true
+    }
+
+    /// Returns the charge to mass ratio of a particle within the species.
+    fn charge_to_mass_ratio(&self, particle: &Particle) -> f64 {
+        self.charge * f64::from(particle.charge_state) / self.particle_mass(particle)
+    }
+
+    /// Returns the mass of a particle within the species.
+    pub(crate) fn particle_mass(&self, particle: &Particle) -> f64 {
+        particle.mass.unwrap_or(self.mass)
+    }
+
+    /// Returns the charge collected by each absorbing face during the last advance.
+    pub fn collected_charge(&self) -> [f64; 6] {
+        self.collected_charge
+    }
+
+    /// Returns the current collected by each face during the last advance, indexed by face.
+    pub fn face_currents(&self, dt: f64) -> [f64; 6] {
+        self.collected_charge.map(|charge| charge / dt)
     }
 
     /// Adjusts particle positions and velocities.
     pub fn advance(&mut self, mesh: &BoxMesh) {
+        if !self.mobile {
+            self.collected_charge = [0.0; 6];
+            return;
+        }
+
         let origin = mesh.origin();
         let max_bound = mesh.max_bound();
-        let dimensions = mesh.dimensions();
         let dt = mesh.timestep();
 
-        for particle in &mut self.particles {
-            let lc = mesh.position_to_logical_coordinate(particle.position);
-            let electric_field = mesh.electric_field().gather(lc);
-            particle.velocity += electric_field * (dt * (self.charge / self.mass));
-            particle.position += particle.velocity * dt;
+        let charge = self.charge;
+        let mass = self.mass;
+        let integrator = self.integrator;
+        let mut collected_charge = [0.0; 6];
+
+        self.particles.retain_mut(|particle| {
+            let charge_to_mass_ratio =
+                charge * f64::from(particle.charge_state) / particle.mass.unwrap_or(mass);
+
+            // Accelerates a velocity over the timestep by the fields at a position.
+            let kick = |mut velocity: Vec3, position: Vec3| {
+                let lc = mesh.position_to_logical_coordinate(position);
+                let electric_field = mesh.electric_field().gather(lc);
+
+                #[cfg(not(feature = "em"))]
+                {
+                    velocity += electric_field * (dt * charge_to_mass_ratio);
+                }
+
+                #[cfg(feature = "em")]
+                {
+                    let magnetic_field = mesh.magnetic_field().gather(lc);
+                    velocity = boris_push(
+                        velocity,
+                        electric_field,
+                        magnetic_field,
+                        charge_to_mass_ratio,
+                        dt,
+                    );
+                }
+
+                velocity
+            };
+
+            match integrator {
+                Integrator::Leapfrog => {
+                    particle.velocity = kick(particle.velocity, particle.position);
+                    particle.position += particle.velocity * dt;
+                }
+                Integrator::Midpoint => {
+                    let initial_velocity = particle.velocity;
 
-            // Reflecting particles leaving the mesh.
-            if lc.x < 0.0 {
-                particle.position.x = 2.0 * origin.x - particle.position.x;
-                particle.velocity.x *= -1.0;
-            } else if lc.x >= (dimensions.x - 1) as f64 {
-                particle.position.x = 2.0 * max_bound.x - particle.position.x;
-                particle.velocity.x *= -1.0;
+                    // Predicting the midpoint by drifting with the initial velocity, then
+                    // correcting it with the average of the initial and final velocities.
+                    let midpoint = particle.position + initial_velocity * (0.5 * dt);
+                    let final_velocity = kick(initial_velocity, midpoint);
+                    let midpoint =
+                        particle.position + (initial_velocity + final_velocity) * (0.25 * dt);
+                    let final_velocity = kick(initial_velocity, midpoint);
+
+                    particle.position += (initial_velocity + final_velocity) * (0.5 * dt);
+                    particle.velocity = final_velocity;
+                }
             }
 
-            if lc.y < 0.0 {
-                particle.position.y = 2.0 * origin.y - particle.position.y;
-                particle.velocity.y *= -1.0;
-            } else if lc.y >= (dimensions.y - 1) as f64 {
-                particle.position.y = 2.0 * max_bound.y - particle.position.y;
-                particle.velocity.y *= -1.0;
+            // Applying particle boundary conditions to particles leaving the mesh.
+            for face in Face::ALL {
+                let axis = face.axis().index();
+                let (bound, outside) = if face.is_min() {
+                    (origin[axis], particle.position[axis] < origin[axis])
+                } else {
+                    (max_bound[axis], particle.position[axis] >= max_bound[axis])
+                };
+
+                if !outside {
+                    continue;
+                }
+
+                match mesh.particle_boundary(face) {
+                    BoundaryMode::Reflecting => {
+                        // Scaling the normal velocity so that its energy shrinks by the
+                        // reflection energy coefficient of the face.
+                        let coefficient = mesh.reflection_energy_coefficient(face);
+                        particle.position[axis] = 2.0 * bound - particle.position[axis];
+                        particle.velocity[axis] *= -coefficient.sqrt();
+                    }
+                    BoundaryMode::Absorbing => {
+                        collected_charge[face.index()] += charge
+                            * f64::from(particle.charge_state)
+                            * particle.macroparticle_weight;
+                        return false;
+                    }
+                    BoundaryMode::Periodic => {
+                        let length = max_bound[axis] - origin[axis];
+                        let offset = (particle.position[axis] - origin[axis]).rem_euclid(length);
+
+                        // Rounding a tiny negative offset can land exactly on the length.
+                        particle.position[axis] =
+                            origin[axis] + if offset < length { offset } else { 0.0 };
+                    }
+                }
             }
 
-            if lc.z < 0.0 {
-                particle.position.z = 2.0 * origin.z - particle.position.z;
-                particle.velocity.z *= -1.0;
-            } else if lc.z >= (dimensions.z - 1) as f64 {
-                particle.position.z = 2.0 * max_bound.z - particle.position.z;
-                particle.velocity.z *= -1.0;
+            true
+        });
+
+        self.collected_charge = collected_charge;
+    }
+
+    /// Computes the current density of the species based on the simulation mesh.
+    #[cfg(feature = "em")]
+    pub fn compute_current_density(&self, mesh: &BoxMesh) -> Field<Vec3> {
+        let mut current_density = Field::<Vec3>::new(mesh.dimensions());
+
+        for particle in &self.particles {
+            let logical_coordinate = mesh.position_to_logical_coordinate(particle.position);
+            let charge = self.charge * f64::from(particle.charge_state);
+            current_density.scatter(
+                logical_coordinate,
+                particle.velocity * (charge * particle.macroparticle_weight),
+            );
+        }
+
+        let node_volumes = mesh.node_volumes();
+        let (nx, ny, nz) = current_density.shape();
+
+        for i in 0..nx {
+            for j in 0..ny {
+                for k in 0..nz {
+                    current_density[[i, j, k]] =
+                        current_density[[i, j, k]] * (1.0 / node_volumes[[i, j, k]]);
+                }
             }
         }
+
+        current_density
+    }
+
+    /// Reorders the particles by the index of their containing cell, so that consecutive
+    /// particles access neighboring mesh nodes when depositing and gathering.
+    pub fn sort_by_cell(&mut self, mesh: &BoxMesh) {
+        self.particles
+            .sort_by_cached_key(|particle| mesh.cell_index(particle.position));
+    }
+
+    /// Randomizes the order of the particles with a Fisher-Yates shuffle.
+    ///
+    /// Loaders place particles in a regular order, such as along the lattice of a quiet
+    /// start, so any selection by position in the list is spatially correlated. Shuffling
+    /// helps when taking a subset of the particles, such as sampling them for output or
+    /// splitting them into batches, while depositing every particle is unaffected. A seeded
+    /// random number generator keeps the order reproducible.
+    pub fn shuffle_particles<R: Rng>(&mut self, rng: &mut R) {
+        self.particles.shuffle(rng);
+    }
+
+    /// Returns the largest number of cells any particle would cross in a timestep, found from
+    /// its speed over the smallest cell spacing, or zero if there are no particles.
+    ///
+    /// This must stay below one for deposition to resolve the motion of the particles, so
+    /// checking it before a run catches a timestep that is too large.
+    pub fn max_cells_crossed_per_step(&self, mesh: &BoxMesh) -> f64 {
+        let min_cell_spacing = mesh
+            .cell_spacings()
+            .iter()
+            .fold(f64::INFINITY, |min, &spacing| min.min(spacing));
+
+        self.particles
+            .iter()
+            .map(|particle| particle.velocity.magnitude())
+            .fold(0.0, f64::max)
+            * mesh.timestep()
+            / min_cell_spacing
+    }
+
+    /// Returns the mean, minimum, and maximum number of macroparticles per cell.
+    ///
+    /// A minimum of zero flags cells without any macroparticles, where the statistical noise
+    /// of the deposited density is unbounded.
+    pub fn particles_per_cell_stats(&self, mesh: &BoxMesh) -> (f64, usize, usize) {
+        let mut counts = vec![0; mesh.num_cells()];
+
+        for particle in &self.particles {
+            counts[mesh.cell_index(particle.position)] += 1;
+        }
+
+        let mean = self.particles.len() as f64 / counts.len() as f64;
+        let min = counts.iter().copied().min().unwrap_or(0);
+        let max = counts.iter().copied().max().unwrap_or(0);
+
+        (mean, min, max)
     }
 
-    /// Computes the number density of the species based on the simulation mesh.
+    /// Computes the number and charge densities of the species based on the simulation mesh.
     pub fn compute_number_density(&mut self, mesh: &BoxMesh) {
         self.number_density.clear();
+        self.charge_density.clear();
 
         for particle in &self.particles {
             let logical_coordinate = mesh.position_to_logical_coordinate(particle.position);
-            self.number_density
-                .scatter(logical_coordinate, particle.macroparticle_weight);
+            self.number_density.scatter_weighted(
+                logical_coordinate,
+                particle.macroparticle_weight,
+                self.weighting,
+            );
+            self.charge_density.scatter_weighted(
+                logical_coordinate,
+                particle.macroparticle_weight * f64::from(particle.charge_state),
+                self.weighting,
+            );
         }
 
         self.number_density = self.number_density.clone() / mesh.node_volumes();
+        self.charge_density = self.charge_density.clone() / mesh.node_volumes() * self.charge;
+
+        mesh.fold_periodic_nodes(&mut self.number_density);
+        mesh.fold_periodic_nodes(&mut self.charge_density);
+    }
+
+    /// Injects macroparticles at a constant rate, in macroparticles per second, over a
+    /// timestep, returning the number of macroparticles added to the species.
+    ///
+    /// Particles rejected by the overflow policy aren't counted, and under the `Error` policy
+    /// injection stops at the first particle exceeding the limit.
+    ///
+    /// Particles are placed uniformly within the box defined by the opposite corners of the
+    /// region, with velocities drawn from the provided distribution. The fraction of a
+    /// macroparticle left over after each injection is carried to the next, so that the
+    /// long-run average matches the rate.
+    #[allow(clippy::too_many_arguments)]
+    pub fn inject_rate<R: Rng, F: FnMut(&mut R) -> Vec3>(
+        &mut self,
+        particles_per_second: f64,
+        region: (Vec3, Vec3),
+        macroparticle_weight: f64,
+        mut velocity_dist: F,
+        dt: f64,
+        mesh: &BoxMesh,
+        rng: &mut R,
+    ) -> Result<usize, ParticleError> {
+        let (origin, opposite) = region;
+        let diagonal_vector = opposite - origin;
+
+        let expected = particles_per_second * dt + self.injection_remainder;
+        let num_injected = expected.floor() as usize;
+        self.injection_remainder = expected - num_injected as f64;
+
+        self.particles.reserve(num_injected);
+
+        let mut num_added = 0;
+        for _ in 0..num_injected {
+            let r = Vec3::new(rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>());
+            let position = origin + diagonal_vector * r;
+            let velocity = velocity_dist(rng);
+
+            if self.try_add_particle(position, velocity, macroparticle_weight, mesh)? {
+                num_added += 1;
+            }
+        }
+
+        Ok(num_added)
     }
 
     /// Loads particles in a box defined by points in opposite corners of the box.
@@ -137,18 +919,143 @@ impl Species {
         let num_real_particles = number_density * box_volume;
         let macroparticle_weight = num_real_particles / num_macroparticles as f64;
 
-        self.particles.reserve(num_macroparticles);
-
-        let mut rng = rand::thread_rng();
+        self.load_particles_box_fixed_weight(
+            origin,
+            opposite,
+            macroparticle_weight,
+            num_macroparticles,
+            mesh,
+        );
+    }
 
-        for _ in 0..num_macroparticles {
-            let r = rng.gen::<f64>();
+    /// Loads particles with a fixed macroparticle weight in a box defined by points in
+    /// opposite corners of the box, such that the number density follows from the weight.
+    pub fn load_particles_box_fixed_weight(
+        &mut self,
+        origin: Vec3,
+        opposite: Vec3,
+        macroparticle_weight: f64,
+        num_macroparticles: usize,
+        mesh: &BoxMesh,
+    ) {
+        let diagonal_vector = opposite - origin;
+
+        self.particles.reserve(num_macroparticles);
+
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..num_macroparticles {
+            let r = Vec3::new(rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>());
+            let position = origin + diagonal_vector * r;
+            let velocity = Vec3::new(0.0, 0.0, 0.0);
+            self.add_particle(position, velocity, macroparticle_weight, mesh);
+        }
+    }
+
+    /// Loads cold particles uniformly in a box defined by points in opposite corners of the
+    /// box, drawing positions from a linear congruential generator with the provided seed.
+    ///
+    /// The positions depend only on the arguments, rather than on the `rand` crate, so that
+    /// benchmarks load identical particles across machines and dependency versions.
+    pub fn load_particles_box_deterministic(
+        &mut self,
+        origin: Vec3,
+        opposite: Vec3,
+        number_density: f64,
+        num_macroparticles: usize,
+        seed: u64,
+        mesh: &BoxMesh,
+    ) {
+        let diagonal_vector = opposite - origin;
+        let box_volume = diagonal_vector.x * diagonal_vector.y * diagonal_vector.z;
+        let macroparticle_weight = number_density * box_volume / num_macroparticles as f64;
+
+        self.particles.reserve(num_macroparticles);
+
+        let mut generator = LinearCongruential::new(seed);
+
+        for _ in 0..num_macroparticles {
+            let r = Vec3::new(
+                generator.next_f64(),
+                generator.next_f64(),
+                generator.next_f64(),
+            );
             let position = origin + diagonal_vector * r;
             let velocity = Vec3::new(0.0, 0.0, 0.0);
             self.add_particle(position, velocity, macroparticle_weight, mesh);
         }
     }
 
+    /// Loads particles in a box defined by points in opposite corners of the box, with a number
+    /// density following the provided profile.
+    ///
+    /// Positions are drawn uniformly and accepted with probability proportional to the
+    /// density there, relative to the maximum of the profile. That maximum is found by
+    /// evaluating the profile on a lattice at the mesh spacing, so the profile should vary
+    /// little within a cell. Every macroparticle has the same weight, set so that the total
+    /// matches the integral of the profile as estimated from the acceptance rate.
+    pub fn load_particles_box_profile<R: Rng, F: Fn(Vec3) -> f64>(
+        &mut self,
+        origin: Vec3,
+        opposite: Vec3,
+        density_fn: F,
+        num_macroparticles: usize,
+        mesh: &BoxMesh,
+        rng: &mut R,
+    ) {
+        let diagonal_vector = opposite - origin;
+        let box_volume = diagonal_vector.x * diagonal_vector.y * diagonal_vector.z;
+
+        // Finding the maximum density on a lattice spanning the box.
+        let cell_spacings = mesh.cell_spacings();
+        let num_points: Vec<usize> = (0..3)
+            .map(|axis| (diagonal_vector[axis].abs() / cell_spacings[axis]).ceil() as usize + 1)
+            .collect();
+        let mut max_density: f64 = 0.0;
+        for i in 0..num_points[0] {
+            for j in 0..num_points[1] {
+                for k in 0..num_points[2] {
+                    let fraction = Vec3::new(
+                        i as f64 / (num_points[0] - 1) as f64,
+                        j as f64 / (num_points[1] - 1) as f64,
+                        k as f64 / (num_points[2] - 1) as f64,
+                    );
+                    max_density = max_density.max(density_fn(origin + diagonal_vector * fraction));
+                }
+            }
+        }
+
+        if num_macroparticles == 0 || max_density <= 0.0 {
+            return;
+        }
+
+        let mut positions = Vec::with_capacity(num_macroparticles);
+        let mut num_trials = 0;
+
+        while positions.len() < num_macroparticles {
+            let r = Vec3::new(rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>());
+            let position = origin + diagonal_vector * r;
+            num_trials += 1;
+
+            if rng.gen::<f64>() * max_density < density_fn(position) {
+                positions.push(position);
+            }
+        }
+
+        // The integral of the profile is the fraction of accepted trials of that of the
+        // maximum density over the box.
+        let num_real_particles =
+            box_volume * max_density * num_macroparticles as f64 / num_trials as f64;
+        let macroparticle_weight = num_real_particles / num_macroparticles as f64;
+
+        self.particles.reserve(num_macroparticles);
+
+        for position in positions {
+            let velocity = Vec3::new(0.0, 0.0, 0.0);
+            self.add_particle(position, velocity, macroparticle_weight, mesh);
+        }
+    }
+
     /// Loads particles in a box using the quite start method.
     pub fn load_particles_box_qs(
         &mut self,
@@ -157,6 +1064,27 @@ impl Species {
         number_density: f64,
         num_macroparticles: (usize, usize, usize),
         mesh: &BoxMesh,
+    ) {
+        self.load_particles_box_qs_with_velocity(
+            origin,
+            opposite,
+            number_density,
+            num_macroparticles,
+            mesh,
+            || Vec3::new(0.0, 0.0, 0.0),
+        );
+    }
+
+    /// Loads particles in a box using the quite start method, drawing the velocity of each
+    /// particle from the provided closure.
+    fn load_particles_box_qs_with_velocity<F: FnMut() -> Vec3>(
+        &mut self,
+        origin: Vec3,
+        opposite: Vec3,
+        number_density: f64,
+        num_macroparticles: (usize, usize, usize),
+        mesh: &BoxMesh,
+        mut sample_velocity: F,
     ) {
         let diagonal_vector = opposite - origin;
         let box_volume = diagonal_vector.x * diagonal_vector.y * diagonal_vector.z;
@@ -204,7 +1132,7 @@ impl Species {
                         weight_factor *= 0.5;
                     }
 
-                    let velocity = Vec3::new(0.0, 0.0, 0.0);
+                    let velocity = sample_velocity();
                     self.add_particle(
                         position,
                         velocity,
@@ -216,3 +1144,1403 @@ impl Species {
         }
     }
 }
+
+/// Loads a quasineutral plasma in a box defined by points in opposite corners of the box.
+///
+/// Both species are loaded with the quiet start method at the same number density, so that
+/// their charge densities cancel. Ions are loaded cold, while electron velocities are drawn
+/// from a Maxwellian distribution at the provided temperature in electron volts.
+#[allow(clippy::too_many_arguments)]
+pub fn load_neutral_plasma<R: Rng>(
+    ion_species: &mut Species,
+    electron_species: &mut Species,
+    origin: Vec3,
+    opposite: Vec3,
+    number_density: f64,
+    particles_per_dim: (usize, usize, usize),
+    electron_temperature: f64,
+    mesh: &BoxMesh,
+    rng: &mut R,
+) {
+    ion_species.load_particles_box_qs(origin, opposite, number_density, particles_per_dim, mesh);
+
+    let electron_mass = electron_species.mass;
+    electron_species.load_particles_box_qs_with_velocity(
+        origin,
+        opposite,
+        number_density,
+        particles_per_dim,
+        mesh,
+        || maxwellian_velocity(rng, electron_mass, electron_temperature),
+    );
+}
+
+/// Rotates a velocity about a magnetic field and accelerates it by an electric field using
+/// the Boris method, which reduces to the leapfrog update when the magnetic field is zero.
+#[cfg(feature = "em")]
+fn boris_push(
+    velocity: Vec3,
+    electric_field: Vec3,
+    magnetic_field: Vec3,
+    charge_to_mass_ratio: f64,
+    dt: f64,
+) -> Vec3 {
+    let half_acceleration = electric_field * (0.5 * charge_to_mass_ratio * dt);
+    let t = magnetic_field * (0.5 * charge_to_mass_ratio * dt);
+    let s = t * (2.0 / (1.0 + t.dot(t)));
+
+    let v_minus = velocity + half_acceleration;
+    let v_prime = v_minus + v_minus.cross(t);
+    let v_plus = v_minus + v_prime.cross(s);
+
+    v_plus + half_acceleration
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::constants::{ATOMIC_MASS_UNIT, ELEMENTARY_CHARGE};
+
+    fn test_mesh() -> BoxMesh {
+        BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(11, 11, 11),
+            1e-9,
+        )
+    }
+
+    #[test]
+    fn test_quiet_start_weight_conservation() {
+        let mesh = test_mesh();
+        let origin = Vec3::new(0.01, 0.0, 0.02);
+        let opposite = Vec3::new(0.09, 0.05, 0.1);
+        let box_volume = 0.08 * 0.05 * 0.08;
+        let number_density = 1e11;
+
+        for num_macroparticles in [(2, 2, 2), (5, 5, 5), (11, 21, 31), (31, 3, 8)] {
+            let mut species = Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+            species.load_particles_box_qs(
+                origin,
+                opposite,
+                number_density,
+                num_macroparticles,
+                &mesh,
+            );
+
+            let expected = number_density * box_volume;
+            let relative_error = (species.num_real_particles() - expected).abs() / expected;
+
+            assert!(
+                relative_error < 1e-12,
+                "Weight not conserved for {:?}: relative error {}.",
+                num_macroparticles,
+                relative_error
+            );
+        }
+    }
+
+    #[test]
+    fn test_face_currents() {
+        let mut mesh = test_mesh();
+        mesh.set_particle_boundary(Face::XMax, BoundaryMode::Absorbing);
+
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let weight = 1e6;
+        let dt = mesh.timestep();
+
+        // One particle reaches the absorbing wall, the other stays inside the domain.
+        species.add_particle(
+            Vec3::new(0.099, 0.05, 0.05),
+            Vec3::new(0.01 / dt, 0.0, 0.0),
+            weight,
+            &mesh,
+        );
+        species.add_particle(
+            Vec3::new(0.05, 0.05, 0.05),
+            Vec3::new(0.0, 0.0, 0.0),
+            weight,
+            &mesh,
+        );
+        species.advance(&mesh);
+
+        let currents = species.face_currents(dt);
+        let expected = ELEMENTARY_CHARGE * weight / dt;
+
+        assert_eq!(species.particles.len(), 1);
+        assert!((currents[Face::XMax.index()] - expected).abs() < 1e-12 * expected);
+
+        for face in [Face::XMin, Face::YMin, Face::YMax, Face::ZMin, Face::ZMax] {
+            assert_eq!(currents[face.index()], 0.0);
+        }
+
+        // Collected charge is reset on every advance.
+        species.advance(&mesh);
+        assert_eq!(species.face_currents(dt)[Face::XMax.index()], 0.0);
+    }
+
+    #[test]
+    fn test_reflection_energy_coefficient() {
+        let dt = test_mesh().timestep();
+        let velocity = Vec3::new(0.004 / dt, 1e3, -2e3);
+
+        // Reflects a particle heading into the x-max wall, returning its new velocity.
+        let reflect = |coefficient: f64| {
+            let mut mesh = test_mesh();
+            mesh.set_reflection_energy_coefficient(Face::XMax, coefficient);
+
+            let mut species = Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+            species.add_particle(Vec3::new(0.098, 0.05, 0.05), velocity, 1.0, &mesh);
+            species.advance(&mesh);
+
+            let particle = &species.particles[0];
+            assert!(particle.position.x < mesh.max_bound().x);
+            assert_eq!(particle.velocity.y, velocity.y);
+            assert_eq!(particle.velocity.z, velocity.z);
+
+            particle.velocity
+        };
+
+        let speed = |v: Vec3| v.dot(v).sqrt();
+
+        let elastic = reflect(1.0);
+        assert_eq!(elastic.x, -velocity.x);
+        assert!((speed(elastic) - speed(velocity)).abs() < 1e-12 * speed(velocity));
+
+        assert_eq!(reflect(0.25).x, -0.5 * velocity.x);
+        assert_eq!(reflect(0.0).x, 0.0);
+    }
+
+    #[test]
+    fn test_reflected_particle_position() {
+        let mesh = test_mesh();
+        let dt = mesh.timestep();
+        let velocity = Vec3::new(0.004 / dt, 1e3, -2e3);
+
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        species.add_particle(Vec3::new(0.098, 0.05, 0.05), velocity, 1.0, &mesh);
+
+        // The particle overshoots the x-max wall by 2 mm and is mirrored back inside it.
+        species.advance(&mesh);
+        let particle = &species.particles()[0];
+        assert!(particle
+            .position
+            .approx_eq(Vec3::new(0.098, 0.050001, 0.049998), 1e-12));
+        assert!(particle
+            .velocity
+            .approx_eq_rel(Vec3::new(-velocity.x, velocity.y, velocity.z), 1e-12));
+
+        // It then drifts away from the wall with the reversed velocity.
+        species.advance(&mesh);
+        assert!(species.particles()[0]
+            .position
+            .approx_eq(Vec3::new(0.094, 0.050002, 0.049996), 1e-12));
+    }
+
+    #[test]
+    fn test_immobile_species() {
+        let mut mesh = test_mesh();
+
+        let mut ions = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        ions.set_mobile(false);
+        ions.load_particles_box_qs(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.05, 0.05, 0.05),
+            1e12,
+            (6, 6, 6),
+            &mesh,
+        );
+        ions.compute_number_density(&mesh);
+
+        let mut species = vec![ions];
+        mesh.compute_charge_density(&species);
+        mesh.solve_potential(4000, 1e-3, None).unwrap();
+        mesh.compute_electric_field();
+
+        let positions: Vec<Vec3> = species[0].particles.iter().map(|p| p.position).collect();
+        species[0].advance(&mesh);
+
+        for (particle, position) in species[0].particles.iter().zip(&positions) {
+            assert_eq!(particle.position, *position);
+        }
+
+        species[0].compute_number_density(&mesh);
+        mesh.compute_charge_density(&species);
+
+        assert!(mesh.charge_density()[[2, 2, 2]] > 0.0);
+        assert_eq!(mesh.charge_density()[[8, 8, 8]], 0.0);
+    }
+
+    #[test]
+    fn test_peak_density() {
+        let mesh = test_mesh();
+        let new_species = || {
+            Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            )
+        };
+
+        let mut uniform = new_species();
+        uniform.load_particles_box_qs(mesh.origin(), mesh.max_bound(), 1e11, (21, 21, 21), &mesh);
+        uniform.compute_number_density(&mesh);
+
+        // Loading the same number of real particles into a small region.
+        let mut concentrated = new_species();
+        concentrated.load_particles_box_qs(
+            Vec3::new(0.06, 0.06, 0.06),
+            Vec3::new(0.08, 0.08, 0.08),
+            1e11 * 125.0,
+            (11, 11, 11),
+            &mesh,
+        );
+        concentrated.compute_number_density(&mesh);
+
+        let (uniform_peak, _) = uniform.peak_density();
+        let (concentrated_peak, (i, j, k)) = concentrated.peak_density();
+
+        for index in [i, j, k] {
+            assert!((6..=8).contains(&index));
+        }
+        assert!(concentrated_peak > uniform_peak);
+    }
+
+    #[test]
+    fn test_average_density() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+
+        // Leaving the outermost cell next to each wall empty.
+        species.load_particles_box_qs(
+            Vec3::new(0.01, 0.01, 0.01),
+            Vec3::new(0.09, 0.09, 0.09),
+            1e11,
+            (17, 17, 17),
+            &mesh,
+        );
+        species.compute_number_density(&mesh);
+
+        let bulk = species.average_density(&mesh, 2);
+        let full = species.average_density(&mesh, 0);
+
+        assert!((bulk / 1e11 - 1.0).abs() < 1e-4);
+        assert!(full < 0.9 * bulk);
+        assert_eq!(species.average_density(&mesh, 6), 0.0);
+    }
+
+    #[test]
+    fn test_weighting_conserves_deposited_total() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        species.load_particles_box_fixed_weight(mesh.origin(), mesh.max_bound(), 1e5, 5000, &mesh);
+        let expected = species.num_real_particles();
+
+        for weighting in [
+            Weighting::NearestGridPoint,
+            Weighting::CloudInCell,
+            Weighting::TriangularShapedCloud,
+        ] {
+            species.set_weighting(weighting);
+            species.compute_number_density(&mesh);
+
+            let total = species.deposited_total(&mesh);
+            assert!(
+                (total / expected - 1.0).abs() < 1e-9,
+                "{:?} deposited {} of {} particles.",
+                weighting,
+                total,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_add_drift() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        species.load_particles_box_fixed_weight(mesh.origin(), mesh.max_bound(), 1e5, 100, &mesh);
+        species.add_particle(
+            Vec3::new(0.05, 0.05, 0.05),
+            Vec3::new(1e3, -2e3, 0.0),
+            2e5,
+            &mesh,
+        );
+
+        let initial_velocities: Vec<Vec3> = species.particles.iter().map(|p| p.velocity).collect();
+        let initial_momentum = species.momentum();
+
+        let drift = Vec3::new(100.0, 0.0, -50.0);
+        species.add_drift(drift);
+
+        for (particle, initial) in species.particles.iter().zip(initial_velocities) {
+            assert_eq!(particle.velocity, initial + drift);
+        }
+
+        let expected = initial_momentum + drift * (species.mass * species.num_real_particles());
+        let error = species.momentum() - expected;
+        assert!(error.dot(error).sqrt() < 1e-9 * expected.dot(expected).sqrt());
+    }
+
+    #[test]
+    fn test_particles() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let positions = [
+            Vec3::new(0.01, 0.02, 0.03),
+            Vec3::new(0.05, 0.05, 0.05),
+            Vec3::new(0.09, 0.08, 0.07),
+        ];
+        for position in positions {
+            species.add_particle(position, Vec3::new(0.0, 0.0, 0.0), 1.0, &mesh);
+        }
+
+        assert_eq!(species.particles().len(), positions.len());
+        for (particle, position) in species.particles().iter().zip(positions) {
+            assert_eq!(particle.position, position);
+        }
+    }
+
+    #[test]
+    fn test_debye_length() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        use crate::constants::ELECTRON_MASS;
+
+        let mesh = test_mesh();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let mut load = |temperature: f64| {
+            let mut species = Species::new(
+                String::from("e-"),
+                ELECTRON_MASS,
+                -ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+            species.load_particles_box_qs_with_velocity(
+                mesh.origin(),
+                mesh.max_bound(),
+                1e11,
+                (21, 21, 21),
+                &mesh,
+                || maxwellian_velocity(&mut rng, ELECTRON_MASS, temperature),
+            );
+            species.compute_number_density(&mesh);
+            species
+        };
+
+        let cold = load(1.0);
+        let hot = load(4.0);
+
+        assert!((cold.temperature(&mesh) - 1.0).abs() < 0.05);
+        assert!((hot.temperature(&mesh) - 4.0).abs() < 0.2);
+
+        let cold_length = cold.debye_length(&mesh);
+        let hot_length = hot.debye_length(&mesh);
+        assert!((cold_length - plasma::debye_length(1.0, 1e11)).abs() < 0.05 * cold_length);
+
+        // At the same density, the Debye length scales with the square root of temperature.
+        assert!(hot_length > cold_length);
+        assert!((hot_length / cold_length - 2.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_load_particles_box_deterministic() {
+        let mesh = test_mesh();
+        let origin = Vec3::new(0.02, 0.03, 0.04);
+        let opposite = Vec3::new(0.08, 0.07, 0.06);
+
+        let load = |seed: u64| {
+            let mut species = Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+            species.load_particles_box_deterministic(origin, opposite, 1e11, 8000, seed, &mesh);
+            species
+        };
+
+        let first = load(11);
+        let second = load(11);
+
+        assert_eq!(first.particles().len(), 8000);
+        for (a, b) in first.particles().iter().zip(second.particles()) {
+            assert_eq!(a.position.x.to_bits(), b.position.x.to_bits());
+            assert_eq!(a.position.y.to_bits(), b.position.y.to_bits());
+            assert_eq!(a.position.z.to_bits(), b.position.z.to_bits());
+            assert_eq!(
+                a.macroparticle_weight.to_bits(),
+                b.macroparticle_weight.to_bits()
+            );
+        }
+
+        // A different seed gives different positions.
+        let other = load(12);
+        assert!(first.particles()[0].position.x != other.particles()[0].position.x);
+
+        // The particles fill the box, with about an eighth of them in each octant.
+        let center = (origin + opposite) * 0.5;
+        let mut octant_counts = [0usize; 8];
+        for particle in first.particles() {
+            let p = particle.position;
+            assert!(p.x >= origin.x && p.y >= origin.y && p.z >= origin.z);
+            assert!(p.x < opposite.x && p.y < opposite.y && p.z < opposite.z);
+
+            let octant = usize::from(p.x > center.x)
+                + 2 * usize::from(p.y > center.y)
+                + 4 * usize::from(p.z > center.z);
+            octant_counts[octant] += 1;
+        }
+        assert!(octant_counts
+            .iter()
+            .all(|&count| (900..1100).contains(&count)));
+
+        let total = first.num_real_particles();
+        let volume = 0.06 * 0.04 * 0.02;
+        assert!((total - 1e11 * volume).abs() < 1e-9 * total);
+    }
+
+    #[test]
+    fn test_max_cells_crossed_per_step() {
+        use crate::constants::ELECTRON_MASS;
+
+        // The spacing is smallest along z, at 5 mm.
+        let mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.05),
+            Dimensions::new(11, 11, 11),
+            1e-9,
+        );
+        let mut species = Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        assert_eq!(species.max_cells_crossed_per_step(&mesh), 0.0);
+
+        let position = Vec3::new(0.05, 0.05, 0.025);
+        species.add_particle(position, Vec3::new(1e5, 0.0, 0.0), 1.0, &mesh);
+        assert!(species.max_cells_crossed_per_step(&mesh) < 1.0);
+
+        // A speed of 1.3e7 m/s covers 13 mm, or 2.6 cells, in a nanosecond.
+        species.add_particle(position, Vec3::new(5e6, -12e6, 0.0), 1.0, &mesh);
+        assert!((species.max_cells_crossed_per_step(&mesh) - 2.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_periodic_particle_boundary() {
+        use crate::boundary::Axis;
+
+        let mut mesh = test_mesh();
+        mesh.set_periodic(Axis::X);
+
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let forward = Vec3::new(2e6, 0.0, 0.0);
+        let backward = Vec3::new(-3e6, 1e5, 0.0);
+        species.add_particle(Vec3::new(0.099, 0.05, 0.05), forward, 1.0, &mesh);
+        species.add_particle(Vec3::new(0.002, 0.05, 0.05), backward, 1.0, &mesh);
+        species.advance(&mesh);
+
+        // Both particles re-enter through the opposite face, keeping their velocities.
+        let particles = species.particles();
+        assert_eq!(particles.len(), 2);
+        assert!((particles[0].position.x - 0.001).abs() < 1e-12);
+        assert!((particles[1].position.x - 0.099).abs() < 1e-12);
+        assert_eq!(particles[0].velocity, forward);
+        assert_eq!(particles[1].velocity, backward);
+        assert_eq!(species.collected_charge(), [0.0; 6]);
+
+        // A particle beside the periodic faces deposits onto the coinciding end nodes equally.
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        species.add_particle(
+            Vec3::new(0.0995, 0.05, 0.05),
+            Vec3::new(0.0, 0.0, 0.0),
+            1e6,
+            &mesh,
+        );
+        species.compute_number_density(&mesh);
+
+        let density = species.number_density();
+        assert!(density[[0, 5, 5]] > 0.0);
+        assert_eq!(density[[0, 5, 5]], density[[10, 5, 5]]);
+        assert!((species.deposited_total(&mesh) - 1e6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_max_particles() {
+        let mesh = test_mesh();
+        let new_species = |policy: OverflowPolicy| {
+            let mut species = Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+            species.set_max_particles(Some(3), policy);
+            species
+        };
+        let position = |n: usize| Vec3::new(0.01 * (n + 1) as f64, 0.05, 0.05);
+        let velocity = Vec3::new(0.0, 0.0, 0.0);
+
+        let mut rejecting = new_species(OverflowPolicy::RejectNew);
+        for n in 0..5 {
+            rejecting.add_particle(position(n), velocity, 1.0, &mesh);
+        }
+        assert_eq!(rejecting.particles().len(), 3);
+        assert_eq!(rejecting.particles()[2].position, position(2));
+
+        // Merging keeps the count at the cap while conserving the total weight.
+        let mut merging = new_species(OverflowPolicy::MergeOldest);
+        for n in 0..5 {
+            merging.add_particle(position(n), velocity, 1.0, &mesh);
+        }
+        assert_eq!(merging.particles().len(), 3);
+        assert_eq!(merging.num_real_particles(), 5.0);
+        assert_eq!(merging.particles()[0].macroparticle_weight, 3.0);
+        assert!((merging.particles()[0].position.x - 0.02).abs() < 1e-12);
+
+        // Particles of different charge states or masses are never merged together.
+        let mut mixed = new_species(OverflowPolicy::MergeOldest);
+        mixed.add_particle(position(0), velocity, 1.0, &mesh);
+        mixed.add_particle_with_charge_state(position(1), velocity, 1.0, 2, &mesh);
+        mixed.add_particle_with_mass(position(2), velocity, 1.0, 18.0 * ATOMIC_MASS_UNIT, &mesh);
+        mixed.add_particle(position(3), velocity, 1.0, &mesh);
+        assert_eq!(mixed.particles().len(), 3);
+        assert_eq!(mixed.particles()[0].macroparticle_weight, 1.0);
+        assert_eq!(mixed.particles()[2].position, position(2));
+
+        let mut failing = new_species(OverflowPolicy::Error);
+        for n in 0..3 {
+            failing
+                .try_add_particle(position(n), velocity, 1.0, &mesh)
+                .unwrap();
+        }
+        assert_eq!(
+            failing.try_add_particle(position(3), velocity, 1.0, &mesh),
+            Err(ParticleError::LimitExceeded { max_particles: 3 })
+        );
+        assert_eq!(failing.particles().len(), 3);
+    }
+
+    #[test]
+    fn test_shuffle_particles() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        species.load_particles_box_qs(mesh.origin(), mesh.max_bound(), 1e11, (5, 5, 5), &mesh);
+
+        let key = |particle: &Particle| {
+            let p = particle.position;
+            [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]
+        };
+        let initial: Vec<[u64; 3]> = species.particles().iter().map(key).collect();
+
+        species.shuffle_particles(&mut StdRng::seed_from_u64(11));
+        let shuffled: Vec<[u64; 3]> = species.particles().iter().map(key).collect();
+        assert_ne!(shuffled, initial);
+
+        let mut initial_sorted = initial;
+        let mut shuffled_sorted = shuffled;
+        initial_sorted.sort_unstable();
+        shuffled_sorted.sort_unstable();
+        assert_eq!(shuffled_sorted, initial_sorted);
+    }
+
+    #[test]
+    fn test_bohm_criterion() {
+        let mesh = test_mesh();
+        let electron_temperature = 2.0;
+        let mass = 16.0 * ATOMIC_MASS_UNIT;
+        let bohm_speed = (electron_temperature * ELEMENTARY_CHARGE / mass).sqrt();
+
+        // Loading ions drifting towards the x-min wall around a sheath edge node.
+        let drifting_ions = |speed: f64| {
+            let mut species = Species::new(
+                String::from("O+"),
+                mass,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+            for position in [
+                Vec3::new(0.015, 0.05, 0.05),
+                Vec3::new(0.025, 0.052, 0.047),
+                Vec3::new(0.021, 0.043, 0.055),
+            ] {
+                species.add_particle(position, Vec3::new(-speed, 0.0, 0.0), 1e5, &mesh);
+            }
+            species
+        };
+        let node = (2, 5, 5);
+
+        let ions = drifting_ions(bohm_speed);
+        let drift = ions.drift_velocity_at(node, &mesh);
+        assert!((drift.x + bohm_speed).abs() < 1e-12 * bohm_speed);
+        assert_eq!(drift.y, 0.0);
+        assert!(ions.satisfies_bohm_criterion(node, Face::XMin, electron_temperature, &mesh));
+        assert!(!ions.satisfies_bohm_criterion(node, Face::XMax, electron_temperature, &mesh));
+
+        let ions = drifting_ions(0.9 * bohm_speed);
+        assert!(!ions.satisfies_bohm_criterion(node, Face::XMin, electron_temperature, &mesh));
+
+        // No particle contributes to a node far from the ions.
+        assert_eq!(
+            ions.drift_velocity_at((8, 8, 8), &mesh),
+            Vec3::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_rescale_weights() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        species.load_particles_box_qs(mesh.origin(), mesh.max_bound(), 1e11, (11, 11, 11), &mesh);
+        species.compute_number_density(&mesh);
+
+        let num_real_particles = species.num_real_particles();
+        let number_density = species.number_density();
+
+        species.rescale_weights(2.0);
+        species.compute_number_density(&mesh);
+
+        assert!(
+            (species.num_real_particles() - 2.0 * num_real_particles).abs()
+                < 1e-9 * num_real_particles
+        );
+        for i in 0..11 {
+            for j in 0..11 {
+                for k in 0..11 {
+                    let index = [i, j, k];
+                    let expected = 2.0 * number_density[index];
+                    assert!((species.number_density()[index] - expected).abs() <= 1e-12 * expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_potential_energy() {
+        use crate::boundary::BoundaryCondition;
+
+        // Imposing a linear potential rising by 10 V across the x-extent of the mesh.
+        let mut mesh = test_mesh();
+        for face in [Face::YMin, Face::YMax, Face::ZMin, Face::ZMax] {
+            mesh.set_boundary_condition(face, BoundaryCondition::Neumann);
+        }
+        mesh.set_boundary_condition(Face::XMax, BoundaryCondition::Dirichlet(10.0));
+        mesh.solve_potential(4000, 1e-6, None).unwrap();
+
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let velocity = Vec3::new(0.0, 0.0, 0.0);
+        species.add_particle(Vec3::new(0.037, 0.05, 0.05), velocity, 3.0, &mesh);
+
+        let expected = ELEMENTARY_CHARGE * 3.7 * 3.0;
+        assert!((species.potential_energy(&mesh) / expected - 1.0).abs() < 1e-3);
+
+        // Particles on the mesh boundaries gather the potential of the faces they lie on.
+        species.add_particle(mesh.origin(), velocity, 1.0, &mesh);
+        species.add_particle(mesh.max_bound(), velocity, 2.0, &mesh);
+
+        let expected = ELEMENTARY_CHARGE * (3.7 * 3.0 + 0.0 * 1.0 + 10.0 * 2.0);
+        assert!((species.potential_energy(&mesh) / expected - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_inject_rate() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let mut rng = StdRng::seed_from_u64(3);
+        let region = (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.01, 0.1, 0.1));
+        let rate = 3.7e8;
+        let num_steps = 1000;
+
+        let mut total_injected = 0;
+        for _ in 0..num_steps {
+            total_injected += species
+                .inject_rate(
+                    rate,
+                    region,
+                    1e5,
+                    |_| Vec3::new(1e3, 0.0, 0.0),
+                    mesh.timestep(),
+                    &mesh,
+                    &mut rng,
+                )
+                .unwrap();
+        }
+
+        // Fewer than one macroparticle per step, so the leftover fractions must accumulate.
+        assert!(rate * mesh.timestep() < 1.0);
+        let expected = rate * mesh.timestep() * num_steps as f64;
+        assert_eq!(species.particles.len(), total_injected);
+        assert!((total_injected as f64 - expected).abs() <= 1.0);
+
+        for particle in &species.particles {
+            assert!(particle.position.x >= 0.0 && particle.position.x <= 0.01);
+        }
+    }
+
+    #[test]
+    fn test_inject_rate_max_particles() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mesh = test_mesh();
+        let mut rng = StdRng::seed_from_u64(3);
+        let region = (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.01, 0.1, 0.1));
+        let rate = 10.0 / mesh.timestep();
+
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let mut inject = |species: &mut Species| {
+            let velocity_dist = |_: &mut StdRng| Vec3::new(0.0, 0.0, 0.0);
+            species.inject_rate(
+                rate,
+                region,
+                1.0,
+                velocity_dist,
+                mesh.timestep(),
+                &mesh,
+                &mut rng,
+            )
+        };
+
+        // Only the particles which fit under the cap are counted.
+        species.set_max_particles(Some(15), OverflowPolicy::RejectNew);
+        assert_eq!(inject(&mut species), Ok(10));
+        assert_eq!(inject(&mut species), Ok(5));
+        assert_eq!(inject(&mut species), Ok(0));
+        assert_eq!(species.particles().len(), 15);
+
+        species.set_max_particles(Some(15), OverflowPolicy::Error);
+        assert_eq!(
+            inject(&mut species),
+            Err(ParticleError::LimitExceeded { max_particles: 15 })
+        );
+        assert_eq!(species.particles().len(), 15);
+    }
+
+    #[test]
+    fn test_advance_without_field() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        species.add_particle(
+            Vec3::new(0.02, 0.05, 0.07),
+            Vec3::new(1e3, -2e3, 5e2),
+            1.0,
+            &mesh,
+        );
+        species.add_particle(
+            Vec3::new(0.06, 0.03, 0.01),
+            Vec3::new(-4e3, 0.0, 3e3),
+            1.0,
+            &mesh,
+        );
+
+        let mut expected = species.clone();
+        for particle in &mut expected.particles {
+            particle.position += particle.velocity * mesh.timestep();
+        }
+
+        // Without an electric field, particles drift at constant velocity.
+        species.advance(&mesh);
+        assert!(species.particles_approx_eq(&expected, 1e-15));
+
+        expected.particles[1].velocity.y += 1e-9;
+        assert!(!species.particles_approx_eq(&expected, 1e-15));
+    }
+
+    #[test]
+    fn test_synchronized_kinetic_energy() {
+        // Imposing a harmonic well along x, whose linear field is gathered exactly.
+        let mut mesh = test_mesh();
+        let curvature = 2e8;
+        let center = 0.05;
+        for i in 0..11 {
+            for j in 0..11 {
+                for k in 0..11 {
+                    let x = mesh.node_position(i, j, k).x - center;
+                    mesh.potential_mut()[[i, j, k]] = curvature * x * x;
+                }
+            }
+        }
+        mesh.compute_electric_field();
+
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let velocity = Vec3::new(0.0, 0.0, 0.0);
+        species.add_particle(Vec3::new(center + 0.02, 0.05, 0.05), velocity, 1.0, &mesh);
+
+        let potential_energy = |species: &Species| {
+            let x = species.particles[0].position.x - center;
+            ELEMENTARY_CHARGE * curvature * x * x
+        };
+        let initial_energy = potential_energy(&species);
+
+        // Following the particle over a few oscillations.
+        let mut raw_error: f64 = 0.0;
+        let mut synchronized_error: f64 = 0.0;
+        for _ in 0..300 {
+            species.advance(&mesh);
+
+            let raw = species.kinetic_energy() + potential_energy(&species);
+            let synchronized =
+                species.synchronized_kinetic_energy(&mesh) + potential_energy(&species);
+
+            raw_error = raw_error.max((raw / initial_energy - 1.0).abs());
+            synchronized_error =
+                synchronized_error.max((synchronized / initial_energy - 1.0).abs());
+        }
+
+        assert!(synchronized_error < 0.1 * raw_error);
+        assert!(synchronized_error < 1e-2);
+    }
+
+    #[test]
+    fn test_midpoint_energy_drift() {
+        // Imposing a harmonic well along x, whose linear field is gathered exactly.
+        let mut mesh = test_mesh();
+        let curvature = 2e8;
+        let center = 0.05;
+        for i in 0..11 {
+            for j in 0..11 {
+                for k in 0..11 {
+                    let x = mesh.node_position(i, j, k).x - center;
+                    mesh.potential_mut()[[i, j, k]] = curvature * x * x;
+                }
+            }
+        }
+        mesh.compute_electric_field();
+
+        let energy_drift = |integrator: Integrator| {
+            let mut species = Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+            species.set_integrator(integrator);
+            let velocity = Vec3::new(0.0, 0.0, 0.0);
+            species.add_particle(Vec3::new(center + 0.02, 0.05, 0.05), velocity, 1.0, &mesh);
+
+            let total_energy = |species: &Species| {
+                let x = species.particles[0].position.x - center;
+                species.synchronized_kinetic_energy(&mesh) + ELEMENTARY_CHARGE * curvature * x * x
+            };
+            let initial_energy = total_energy(&species);
+
+            // Following the particle over a few dozen oscillations.
+            let mut drift: f64 = 0.0;
+            for _ in 0..5000 {
+                species.advance(&mesh);
+                drift = drift.max((total_energy(&species) / initial_energy - 1.0).abs());
+            }
+
+            drift
+        };
+
+        let leapfrog = energy_drift(Integrator::Leapfrog);
+        let midpoint = energy_drift(Integrator::Midpoint);
+        assert!(midpoint < leapfrog);
+    }
+
+    #[test]
+    fn test_midpoint_add_particle() {
+        let mut mesh = test_mesh();
+        for i in 0..11 {
+            for j in 0..11 {
+                for k in 0..11 {
+                    mesh.potential_mut()[[i, j, k]] = 1e3 * mesh.node_position(i, j, k).x;
+                }
+            }
+        }
+        mesh.compute_electric_field();
+
+        let added_velocity = |integrator: Integrator| {
+            let mut species = Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+            species.set_integrator(integrator);
+            species.add_particle(
+                Vec3::new(0.05, 0.05, 0.05),
+                Vec3::new(1e3, 0.0, 0.0),
+                1.0,
+                &mesh,
+            );
+
+            species.particles[0].velocity
+        };
+
+        // Only leapfrog rewinds the velocity by half a timestep.
+        assert_eq!(
+            added_velocity(Integrator::Midpoint),
+            Vec3::new(1e3, 0.0, 0.0)
+        );
+        assert!(added_velocity(Integrator::Leapfrog).x > 1e3);
+    }
+
+    #[test]
+    fn test_load_particles_box_profile() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+
+        // Ramping the density linearly along x, from 5e10 to 1.5e11.
+        let ramp = |position: Vec3| 1e11 * (0.5 + 10.0 * position.x);
+        let mut rng = StdRng::seed_from_u64(3);
+        species.load_particles_box_profile(
+            mesh.origin(),
+            mesh.max_bound(),
+            ramp,
+            100000,
+            &mesh,
+            &mut rng,
+        );
+        species.compute_number_density(&mesh);
+
+        // The integral of the ramp is its mean density over the box volume.
+        let expected = 1e11 * 1e-3;
+        assert!((species.num_real_particles() - expected).abs() < 0.02 * expected);
+
+        // Averaging the interior density over planes of constant x.
+        let plane_density = |i: usize| {
+            let mut sum = 0.0;
+            for j in 1..10 {
+                for k in 1..10 {
+                    sum += species.number_density()[[i, j, k]];
+                }
+            }
+
+            sum / 81.0
+        };
+
+        for i in 1..9 {
+            assert!(plane_density(i + 1) > plane_density(i));
+        }
+
+        let x = mesh.node_position(5, 5, 5).x;
+        assert!(
+            (plane_density(5) - ramp(Vec3::new(x, 0.0, 0.0))).abs()
+                < 0.05 * ramp(Vec3::new(x, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_fixed_weight_load() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let weight = 2.5e5;
+        let num_macroparticles = 5000;
+
+        species.load_particles_box_fixed_weight(
+            Vec3::new(0.02, 0.02, 0.02),
+            Vec3::new(0.08, 0.07, 0.06),
+            weight,
+            num_macroparticles,
+            &mesh,
+        );
+        species.compute_number_density(&mesh);
+
+        let expected = weight * num_macroparticles as f64;
+        assert!((species.num_real_particles() - expected).abs() < 1e-9 * expected);
+
+        // The deposited density integrates back to the loaded number of real particles.
+        let node_volumes = mesh.node_volumes();
+        let mut deposited = 0.0;
+        for i in 0..11 {
+            for j in 0..11 {
+                for k in 0..11 {
+                    deposited += species.number_density()[[i, j, k]] * node_volumes[[i, j, k]];
+                }
+            }
+        }
+        assert!((deposited - expected).abs() < 1e-9 * expected);
+    }
+
+    #[test]
+    fn test_sort_by_cell() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        species.load_particles_box_fixed_weight(mesh.origin(), mesh.max_bound(), 1.0, 1000, &mesh);
+
+        let position_bits = |species: &Species| {
+            let mut bits: Vec<[u64; 3]> = species
+                .particles
+                .iter()
+                .map(|p| {
+                    [
+                        p.position.x.to_bits(),
+                        p.position.y.to_bits(),
+                        p.position.z.to_bits(),
+                    ]
+                })
+                .collect();
+            bits.sort();
+            bits
+        };
+
+        let unsorted = position_bits(&species);
+        species.sort_by_cell(&mesh);
+
+        for pair in species.particles.windows(2) {
+            assert!(mesh.cell_index(pair[0].position) <= mesh.cell_index(pair[1].position));
+        }
+        assert_eq!(position_bits(&species), unsorted);
+    }
+
+    #[test]
+    fn test_particles_per_cell_stats() {
+        let mesh = test_mesh();
+        let new_species = || {
+            Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            )
+        };
+
+        let mut uniform = new_species();
+        uniform.load_particles_box_fixed_weight(mesh.origin(), mesh.max_bound(), 1.0, 20000, &mesh);
+
+        let (mean, min, max) = uniform.particles_per_cell_stats(&mesh);
+        assert_eq!(mean, 20000.0 / 1000.0);
+        assert!(min > 0);
+        assert!(max < 50);
+
+        let mut clustered = new_species();
+        clustered.load_particles_box_fixed_weight(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.02, 0.02, 0.02),
+            1.0,
+            20000,
+            &mesh,
+        );
+
+        let (mean, min, max) = clustered.particles_per_cell_stats(&mesh);
+        assert_eq!(mean, 20.0);
+        assert_eq!(min, 0);
+        assert!(max as f64 > 100.0 * mean);
+    }
+
+    #[test]
+    fn test_centroid() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+
+        assert_eq!(species.centroid(), None);
+
+        species.load_particles_box_qs(
+            Vec3::new(0.02, 0.02, 0.02),
+            Vec3::new(0.06, 0.06, 0.06),
+            1e11,
+            (5, 5, 5),
+            &mesh,
+        );
+        let centroid = species.centroid().unwrap();
+
+        // The quiet start shifts particles on the maximum faces slightly inward.
+        assert!((centroid.x - 0.04).abs() < 1e-5);
+        assert!((centroid.y - 0.04).abs() < 1e-5);
+        assert!((centroid.z - 0.04).abs() < 1e-5);
+
+        // Adding a heavier particle pulls the centroid towards it.
+        let velocity = Vec3::new(0.0, 0.0, 0.0);
+        species.add_particle(Vec3::new(0.09, 0.04, 0.04), velocity, 1e11, &mesh);
+        let shifted = species.centroid().unwrap();
+
+        assert!(shifted.x > centroid.x + 1e-3);
+        assert!((shifted.y - centroid.y).abs() < 1e-5);
+        assert!((shifted.z - centroid.z).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_charge_state_scales_force() {
+        let mut mesh = test_mesh();
+
+        // Setting up a non-uniform electric field from a localized ion cloud.
+        let mut ions = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        ions.load_particles_box_qs(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.05, 0.05, 0.05),
+            1e12,
+            (6, 6, 6),
+            &mesh,
+        );
+        ions.compute_number_density(&mesh);
+        mesh.compute_charge_density(&[ions]);
+        mesh.solve_potential(4000, 1e-3, None).unwrap();
+        mesh.compute_electric_field();
+
+        let mut species = Species::new(
+            String::from("O"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let position = Vec3::new(0.07, 0.06, 0.05);
+        let velocity = Vec3::new(0.0, 0.0, 0.0);
+        species.add_particle_with_charge_state(position, velocity, 1.0, 1, &mesh);
+        species.add_particle_with_charge_state(position, velocity, 1.0, 2, &mesh);
+        species.advance(&mesh);
+
+        let single = species.particles[0].velocity;
+        let double = species.particles[1].velocity;
+
+        assert!(single.x != 0.0);
+        assert!((double.x - 2.0 * single.x).abs() <= 1e-12 * double.x.abs());
+        assert!((double.y - 2.0 * single.y).abs() <= 1e-12 * double.y.abs());
+        assert!((double.z - 2.0 * single.z).abs() <= 1e-12 * double.z.abs());
+    }
+
+    #[test]
+    fn test_particle_mass_override() {
+        use crate::boundary::BoundaryCondition;
+
+        // Imposing a uniform electric field along x.
+        let mut mesh = test_mesh();
+        for face in [Face::YMin, Face::YMax, Face::ZMin, Face::ZMax] {
+            mesh.set_boundary_condition(face, BoundaryCondition::Neumann);
+        }
+        mesh.set_boundary_condition(Face::XMax, BoundaryCondition::Dirichlet(10.0));
+        mesh.solve_potential(4000, 1e-6, None).unwrap();
+        mesh.compute_electric_field();
+
+        let mut species = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let position = Vec3::new(0.05, 0.05, 0.05);
+        let velocity = Vec3::new(0.0, 0.0, 0.0);
+        species.add_particle(position, velocity, 1.0, &mesh);
+        species.add_particle_with_mass(position, velocity, 1.0, 18.0 * ATOMIC_MASS_UNIT, &mesh);
+        species.advance(&mesh);
+
+        let light = species.particles[0].velocity;
+        let heavy = species.particles[1].velocity;
+
+        assert!(light.x < 0.0);
+        assert!((heavy.x * 18.0 - light.x * 16.0).abs() <= 1e-12 * light.x.abs());
+    }
+
+    #[test]
+    fn test_charge_state_scales_charge_density() {
+        let mesh = test_mesh();
+        let mut species = Species::new(
+            String::from("O"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let velocity = Vec3::new(0.0, 0.0, 0.0);
+        species.add_particle_with_charge_state(
+            Vec3::new(0.05, 0.05, 0.05),
+            velocity,
+            1.0,
+            2,
+            &mesh,
+        );
+        species.compute_number_density(&mesh);
+
+        let number_density = species.number_density()[[5, 5, 5]];
+        let charge_density = species.charge_density()[[5, 5, 5]];
+
+        assert!(number_density > 0.0);
+        assert!((charge_density - 2.0 * ELEMENTARY_CHARGE * number_density).abs() < 1e-30);
+    }
+
+    #[test]
+    fn test_load_neutral_plasma() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        use crate::constants::ELECTRON_MASS;
+
+        let mut mesh = test_mesh();
+        let mut ions = Species::new(
+            String::from("O+"),
+            16.0 * ATOMIC_MASS_UNIT,
+            ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let mut electrons = Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        );
+        let mut rng = StdRng::seed_from_u64(1);
+
+        load_neutral_plasma(
+            &mut ions,
+            &mut electrons,
+            mesh.origin(),
+            mesh.max_bound(),
+            1e11,
+            (21, 21, 21),
+            1.0,
+            &mesh,
+            &mut rng,
+        );
+
+        let mut species = vec![ions, electrons];
+        for s in &mut species {
+            s.compute_number_density(&mesh);
+        }
+        mesh.compute_charge_density(&species);
+
+        // The net charge density should be negligible relative to that of either species.
+        let species_charge_density = 1e11 * ELEMENTARY_CHARGE;
+        let (ni, nj, nk) = mesh.charge_density().shape();
+        for i in 0..ni {
+            for j in 0..nj {
+                for k in 0..nk {
+                    let rho = mesh.charge_density()[[i, j, k]];
+                    assert!(rho.abs() < 1e-9 * species_charge_density);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "em")]
+    #[test]
+    fn test_boris_push_conserves_speed() {
+        let velocity = Vec3::new(1e5, -2e5, 3e4);
+        let magnetic_field = Vec3::new(0.01, 0.02, -0.03);
+        let zero = Vec3::new(0.0, 0.0, 0.0);
+        let charge_to_mass_ratio = ELEMENTARY_CHARGE / (16.0 * ATOMIC_MASS_UNIT);
+
+        let rotated = boris_push(velocity, zero, magnetic_field, charge_to_mass_ratio, 1e-7);
+        assert!(rotated != velocity);
+        assert!((rotated.magnitude() - velocity.magnitude()).abs() < 1e-9 * velocity.magnitude());
+    }
+}
@@ -0,0 +1,644 @@
+use crate::mesh::BoxMesh;
+use crate::solver::{SolverError, SolverReport};
+use crate::species::Species;
+use crate::vector::Vec3;
+
+/// The total energy, in joules, below which the energy drift over a step is measured in
+/// absolute terms, as a fractional change of a vanishing energy is meaningless.
+const MIN_RELATIVE_DRIFT_ENERGY: f64 = 1e-30;
+
+/// Holds a mesh along with the species simulated on it, and advances them together.
+pub struct SimulationState {
+    /// The simulation mesh.
+    mesh: BoxMesh,
+    /// The species simulated on the mesh.
+    species: Vec<Species>,
+    /// The maximum number of iterations for each potential solve.
+    max_solver_iterations: usize,
+    /// The residual below which each potential solve is considered converged.
+    tolerance: f64,
+    /// The largest fractional change in total energy allowed over a step, if checked, or the
+    /// largest absolute change in joules when the energy is close to zero.
+    max_energy_drift: Option<f64>,
+}
+
+impl SimulationState {
+    /// Creates a new simulation state, depositing the species onto the mesh and solving for
+    /// the initial electric field.
+    pub fn new(
+        mesh: BoxMesh,
+        species: Vec<Species>,
+        max_solver_iterations: usize,
+        tolerance: f64,
+    ) -> Result<Self, SolverError> {
+        let mut state = SimulationState {
+            mesh,
+            species,
+            max_solver_iterations,
+            tolerance,
+            max_energy_drift: None,
+        };
+
+        for s in &mut state.species {
+            s.compute_number_density(&state.mesh);
+        }
+        state.solve_fields()?;
+
+        Ok(state)
+    }
+
+    /// Returns the simulation mesh.
+    pub fn mesh(&self) -> &BoxMesh {
+        &self.mesh
+    }
+
+    /// Returns the species simulated on the mesh.
+    pub fn species(&self) -> &[Species] {
+        &self.species
+    }
+
+    /// Returns the maximum number of iterations for each potential solve.
+    pub fn max_solver_iterations(&self) -> usize {
+        self.max_solver_iterations
+    }
+
+    /// Returns the residual below which each potential solve is considered converged.
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    /// Returns the largest fractional change in total energy allowed over a step, if checked.
+    pub fn max_energy_drift(&self) -> Option<f64> {
+        self.max_energy_drift
+    }
+
+    /// Sets the largest fractional change in total energy allowed over a step.
+    ///
+    /// When set, each step panics if the total energy changes by more than this fraction,
+    /// which catches integrator or field-coupling bugs. The check is disabled by default.
+    ///
+    /// If the total energy at the start of a step is close to zero, such as with no particles
+    /// or with particles at rest in a vanishing field, the change is instead compared in joules.
+    pub fn set_max_energy_drift(&mut self, max_energy_drift: Option<f64>) {
+        self.max_energy_drift = max_energy_drift;
+    }
+
+    /// Advances the particles by a timestep, then deposits them and solves for the fields at
+    /// their new positions.
+    pub fn step(&mut self) -> Result<SolverReport, SolverError> {
+        let advanced = vec![true; self.species.len()];
+
+        self.step_species(&advanced)
+    }
+
+    /// Advances only the species flagged in a mask, given in the order of the species, by a
+    /// timestep, then deposits them and solves for the fields at their new positions.
+    ///
+    /// This allows staged initialization, such as letting the ions settle in the field while
+    /// the electrons are held in place. Held species keep their positions and velocities but
+    /// still contribute to the charge density.
+    pub fn step_species(&mut self, advanced: &[bool]) -> Result<SolverReport, SolverError> {
+        assert_eq!(
+            advanced.len(),
+            self.species.len(),
+            "Species mask length doesn't match the number of species."
+        );
+
+        let initial_energy = self.total_energy();
+
+        for (s, &advance) in self.species.iter_mut().zip(advanced) {
+            if advance {
+                s.advance(&self.mesh);
+                s.compute_number_density(&self.mesh);
+            }
+        }
+
+        let report = self.solve_fields()?;
+        self.mesh.advance_time();
+
+        if let Some(max_energy_drift) = self.max_energy_drift {
+            let drift = energy_drift(initial_energy, self.total_energy());
+
+            assert!(
+                drift <= max_energy_drift,
+                "Total energy drifted by {} over a step, exceeding {}.",
+                drift,
+                max_energy_drift
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Returns the number of steps needed for the simulation time to reach a target time at
+    /// the current timestep, allowing for rounding like `run_until`.
+    pub fn steps_until(&self, target_time: f64) -> usize {
+        let remaining_steps = (target_time - self.mesh.current_time()) / self.mesh.timestep();
+
+        (remaining_steps - 1e-9).ceil().max(0.0) as usize
+    }
+
+    /// Steps the simulation until its time reaches a target time, returning the number of
+    /// steps taken.
+    ///
+    /// The time is checked before every step rather than deriving a fixed number of steps,
+    /// so this also works when the timestep changes along the way. The time accumulated over
+    /// steps is allowed to fall short of the target by a tiny fraction of a timestep, so
+    /// that rounding doesn't add a step.
+    pub fn run_until(&mut self, target_time: f64) -> Result<usize, SolverError> {
+        let mut steps = 0;
+
+        while self.mesh.current_time() < target_time - 1e-9 * self.mesh.timestep() {
+            self.step()?;
+            steps += 1;
+        }
+
+        Ok(steps)
+    }
+
+    /// Returns the kinetic energy of every species plus the energy of the electric field.
+    pub fn total_energy(&self) -> f64 {
+        let kinetic_energy: f64 = self.species.iter().map(Species::kinetic_energy).sum();
+
+        kinetic_energy + self.mesh.field_energy()
+    }
+
+    /// Returns the kinetic energy of the random motion of every species, about its mean
+    /// velocity.
+    pub fn thermal_energy(&self) -> f64 {
+        self.species
+            .iter()
+            .map(|s| s.thermal_energy(&self.mesh))
+            .sum()
+    }
+
+    /// Returns the total linear momentum of every species.
+    pub fn total_momentum(&self) -> Vec3 {
+        let mut momentum = Vec3::new(0.0, 0.0, 0.0);
+        for s in &self.species {
+            momentum += s.momentum();
+        }
+
+        momentum
+    }
+
+    /// Returns the force a single macroparticle of a species feels from its own charge at the
+    /// provided position, due to depositing the charge and gathering the field on the mesh.
+    ///
+    /// The force on a real particle is returned, which would vanish for a setup symmetric
+    /// about the particle if not for grid artifacts. It is computed on a copy of the mesh,
+    /// using the weighting of the species, and ignores the charge of every other particle.
+    pub fn self_force(
+        &self,
+        species_index: usize,
+        position: Vec3,
+        macroparticle_weight: f64,
+    ) -> Result<Vec3, SolverError> {
+        let source = &self.species[species_index];
+        let mut mesh = self.mesh.clone();
+
+        let mut species = Species::new(
+            source.name(),
+            source.mass(),
+            source.charge(),
+            mesh.dimensions(),
+        );
+        species.set_weighting(source.weighting());
+        species.add_particle(
+            position,
+            Vec3::new(0.0, 0.0, 0.0),
+            macroparticle_weight,
+            &mesh,
+        );
+        species.compute_number_density(&mesh);
+
+        mesh.compute_charge_density(&[species]);
+        mesh.solve_potential(self.max_solver_iterations, self.tolerance, None)?;
+        mesh.compute_electric_field();
+
+        let lc = mesh.position_to_logical_coordinate(position);
+
+        Ok(mesh.electric_field().gather(lc) * source.charge())
+    }
+
+    /// Computes the charge density, potential and electric field from the species densities.
+    fn solve_fields(&mut self) -> Result<SolverReport, SolverError> {
+        self.mesh.compute_charge_density(&self.species);
+        let report = self
+            .mesh
+            .solve_potential(self.max_solver_iterations, self.tolerance, None)?;
+        self.mesh.compute_electric_field();
+
+        Ok(report)
+    }
+}
+
+/// Records the thermal energy of a simulation over a run, to measure numerical grid heating.
+///
+/// Under-resolving the Debye length with the mesh makes the particles heat up steadily through
+/// aliasing, so a heating rate that isn't negligible over a run means the mesh is too coarse.
+#[derive(Clone, Debug, Default)]
+pub struct GridHeating {
+    /// The simulation time and total thermal energy at each recorded step.
+    samples: Vec<(f64, f64)>,
+}
+
+impl GridHeating {
+    /// Creates a diagnostic with no recorded steps.
+    pub fn new() -> Self {
+        GridHeating::default()
+    }
+
+    /// Records the current time and thermal energy of a simulation.
+    pub fn record(&mut self, state: &SimulationState) {
+        self.samples
+            .push((state.mesh().current_time(), state.thermal_energy()));
+    }
+
+    /// Returns the simulation time and total thermal energy at each recorded step.
+    pub fn samples(&self) -> &[(f64, f64)] {
+        &self.samples
+    }
+
+    /// Returns the rate at which the thermal energy grows, in watts, from a least squares fit
+    /// over the recorded steps, or zero if fewer than two steps were recorded.
+    pub fn heating_rate(&self) -> f64 {
+        if self.samples.len() < 2 {
+            return 0.0;
+        }
+
+        let count = self.samples.len() as f64;
+        let mean_time = self.samples.iter().map(|sample| sample.0).sum::<f64>() / count;
+        let mean_energy = self.samples.iter().map(|sample| sample.1).sum::<f64>() / count;
+
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for &(time, energy) in &self.samples {
+            covariance += (time - mean_time) * (energy - mean_energy);
+            variance += (time - mean_time) * (time - mean_time);
+        }
+
+        if variance == 0.0 {
+            return 0.0;
+        }
+
+        covariance / variance
+    }
+
+    /// Returns the heating rate as a fraction of the first recorded thermal energy per second,
+    /// or zero if no thermal energy was recorded.
+    pub fn relative_heating_rate(&self) -> f64 {
+        match self.samples.first() {
+            Some(&(_, energy)) if energy > 0.0 => self.heating_rate() / energy,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Returns the fractional change between two total energies, or the absolute change if the
+/// initial energy is too close to zero to divide by.
+fn energy_drift(initial_energy: f64, final_energy: f64) -> f64 {
+    let change = (final_energy - initial_energy).abs();
+
+    if initial_energy.abs() < MIN_RELATIVE_DRIFT_ENERGY {
+        change
+    } else {
+        change / initial_energy.abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::constants::{ATOMIC_MASS_UNIT, ELECTRON_MASS, ELEMENTARY_CHARGE};
+    use crate::mesh::Dimensions;
+    use crate::particle::Particle;
+
+    /// Creates a state with ions and electrons loaded in the boxes defined by the provided
+    /// opposite corners.
+    fn two_species_state(ions: (Vec3, Vec3), electrons: (Vec3, Vec3)) -> SimulationState {
+        let mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(11, 11, 11),
+            2e-9,
+        );
+        let mut species = vec![
+            Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            ),
+            Species::new(
+                String::from("e-"),
+                ELECTRON_MASS,
+                -ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            ),
+        ];
+        species[0].load_particles_box_qs(ions.0, ions.1, 1e11, (11, 11, 11), &mesh);
+        species[1].load_particles_box_qs(electrons.0, electrons.1, 1e11, (11, 11, 11), &mesh);
+
+        SimulationState::new(mesh, species, 4000, 1e-4).unwrap()
+    }
+
+    #[test]
+    fn test_reproducible() {
+        // Advancing is serial, so identical setups must produce bitwise identical states.
+        let mut first = two_species_state(
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.1, 0.1, 0.1)),
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.05, 0.05, 0.05)),
+        );
+        let mut second = two_species_state(
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.1, 0.1, 0.1)),
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.05, 0.05, 0.05)),
+        );
+
+        for _ in 0..10 {
+            first.step().unwrap();
+            second.step().unwrap();
+        }
+
+        let (ni, nj, nk) = first.mesh().potential().shape();
+        for i in 0..ni {
+            for j in 0..nj {
+                for k in 0..nk {
+                    let index = [i, j, k];
+                    assert_eq!(
+                        first.mesh().potential()[index].to_bits(),
+                        second.mesh().potential()[index].to_bits()
+                    );
+                    assert_eq!(
+                        first.mesh().electric_field()[index],
+                        second.mesh().electric_field()[index]
+                    );
+                }
+            }
+        }
+
+        for (a, b) in first.species().iter().zip(second.species()) {
+            assert_eq!(a.particles().len(), b.particles().len());
+
+            for (p, q) in a.particles().iter().zip(b.particles()) {
+                assert_eq!(p.position, q.position);
+                assert_eq!(p.velocity, q.velocity);
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_species() {
+        let mut state = two_species_state(
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.1, 0.1, 0.1)),
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.05, 0.05, 0.05)),
+        );
+        let ions = state.species()[0].particles().to_vec();
+        let electrons = state.species()[1].particles().to_vec();
+
+        for _ in 0..5 {
+            state.step_species(&[true, false]).unwrap();
+        }
+
+        let moved = |before: &[Particle], after: &[Particle]| {
+            before
+                .iter()
+                .zip(after)
+                .any(|(p, q)| p.position != q.position)
+        };
+        assert!(moved(&ions, state.species()[0].particles()));
+        assert!(!moved(&electrons, state.species()[1].particles()));
+
+        // The held electrons still contribute to the solved field.
+        let expected: f64 = state
+            .species()
+            .iter()
+            .map(|s| state.mesh().integrate(&state.mesh().charge_density_of(s)))
+            .sum();
+        let ion_charge = state
+            .mesh()
+            .integrate(&state.mesh().charge_density_of(&state.species()[0]));
+        assert!((state.mesh().total_charge() - expected).abs() < 1e-9 * expected.abs());
+        assert!(state.mesh().total_charge() < 0.9 * ion_charge);
+    }
+
+    #[test]
+    fn test_grid_heating() {
+        use crate::constants::PERMITTIVITY;
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        // Loads thermal electrons over immobile ions at a density giving a Debye length of the
+        // provided number of cells, timestepping at a tenth of the inverse plasma frequency.
+        let heating_over_run = |debye_length_in_cells: f64| {
+            let temperature = 1.0;
+            let cell_spacing = 0.01;
+            let debye_length = debye_length_in_cells * cell_spacing;
+            let density =
+                PERMITTIVITY * temperature / (ELEMENTARY_CHARGE * debye_length * debye_length);
+            let plasma_frequency = (density * ELEMENTARY_CHARGE * ELEMENTARY_CHARGE
+                / (PERMITTIVITY * ELECTRON_MASS))
+                .sqrt();
+            let timestep = 0.1 / plasma_frequency;
+
+            let mesh = BoxMesh::new(
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.1, 0.1, 0.1),
+                Dimensions::new(11, 11, 11),
+                timestep,
+            );
+            let mut ions = Species::new(
+                String::from("O+"),
+                16.0 * ATOMIC_MASS_UNIT,
+                ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+            ions.set_mobile(false);
+            let mut electrons = Species::new(
+                String::from("e-"),
+                ELECTRON_MASS,
+                -ELEMENTARY_CHARGE,
+                mesh.dimensions(),
+            );
+
+            let mut rng = StdRng::seed_from_u64(9);
+            crate::species::load_neutral_plasma(
+                &mut ions,
+                &mut electrons,
+                mesh.origin(),
+                mesh.max_bound(),
+                density,
+                (13, 13, 13),
+                temperature,
+                &mesh,
+                &mut rng,
+            );
+
+            let mut state = SimulationState::new(mesh, vec![ions, electrons], 4000, 1e-4).unwrap();
+            let mut heating = GridHeating::new();
+            heating.record(&state);
+
+            for _ in 0..100 {
+                state.step().unwrap();
+                heating.record(&state);
+            }
+
+            heating.relative_heating_rate() * state.mesh().current_time()
+        };
+
+        // Over the run, a resolved Debye length barely heats while a mesh ten times coarser
+        // than the Debye length heats noticeably.
+        let resolved = heating_over_run(2.0);
+        let under_resolved = heating_over_run(0.1);
+        assert!(resolved.abs() < 0.01, "Resolved heating {}.", resolved);
+        assert!(
+            under_resolved > 0.05,
+            "Under-resolved heating {}.",
+            under_resolved
+        );
+    }
+
+    #[test]
+    fn test_run_until() {
+        let mut state = two_species_state(
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.1, 0.1, 0.1)),
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.05, 0.05, 0.05)),
+        );
+        let timestep = state.mesh().timestep();
+
+        let target_time = 10.0 * timestep;
+        assert_eq!(state.steps_until(target_time), 10);
+        assert_eq!(state.run_until(target_time).unwrap(), 10);
+        assert!((state.mesh().current_time() - target_time).abs() < 1e-6 * timestep);
+        assert_eq!(state.run_until(target_time).unwrap(), 0);
+
+        // A target between steps is passed by less than a timestep.
+        let target_time = 13.5 * timestep;
+        assert_eq!(state.steps_until(target_time), 4);
+        assert_eq!(state.run_until(target_time).unwrap(), 4);
+        assert!(state.mesh().current_time() >= target_time);
+        assert!(state.mesh().current_time() < target_time + timestep);
+    }
+
+    #[test]
+    fn test_total_momentum_conserved() {
+        // Centering both species away from the walls, which would exchange momentum with
+        // reflected particles, so that the setup is symmetric.
+        let mut state = two_species_state(
+            (Vec3::new(0.02, 0.02, 0.02), Vec3::new(0.08, 0.08, 0.08)),
+            (Vec3::new(0.03, 0.03, 0.03), Vec3::new(0.07, 0.07, 0.07)),
+        );
+
+        let initial = state.total_momentum();
+        assert!(initial.dot(initial) == 0.0);
+
+        for _ in 0..20 {
+            state.step().unwrap();
+        }
+
+        // Comparing against the momentum each species would carry with its particles moving
+        // together.
+        let scale: f64 = state
+            .species()
+            .iter()
+            .map(|s| (2.0 * s.kinetic_energy() * s.mass() * s.num_real_particles()).sqrt())
+            .sum();
+        let momentum = state.total_momentum();
+
+        assert!(scale > 0.0);
+        assert!(momentum.dot(momentum).sqrt() < 1e-4 * scale);
+    }
+
+    #[test]
+    fn test_self_force() {
+        use crate::constants::PERMITTIVITY;
+        use crate::field::Weighting;
+        use std::f64::consts::PI;
+
+        // Placing the particle at the center of the central cell of a symmetric domain.
+        let mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.09, 0.09, 0.09),
+            Dimensions::new(10, 10, 10),
+            1e-9,
+        );
+        let species = vec![Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        )];
+        let mut state = SimulationState::new(mesh, species, 4000, 1e-8).unwrap();
+
+        let weight = 1e6;
+        let center = Vec3::new(0.045, 0.045, 0.045);
+        let magnitude = |force: Vec3| force.dot(force).sqrt();
+
+        // Comparing against the Coulomb force between the particle and its charge a cell away.
+        let reference = ELEMENTARY_CHARGE * ELEMENTARY_CHARGE * weight
+            / (4.0 * PI * PERMITTIVITY * 0.01 * 0.01);
+
+        let cic = state.self_force(0, center, weight).unwrap();
+        assert!(magnitude(cic) < 1e-6 * reference);
+
+        state.species[0].set_weighting(Weighting::TriangularShapedCloud);
+        let tsc = state.self_force(0, center, weight).unwrap();
+        assert!(magnitude(tsc) < 1e-6 * reference);
+
+        // Away from the cell center the particle pushes itself. Since fields are always gathered
+        // with cloud-in-cell weighting, the mismatched shape factors make this worse for TSC.
+        let off_center = Vec3::new(0.047, 0.045, 0.045);
+        let tsc = state.self_force(0, off_center, weight).unwrap();
+        state.species[0].set_weighting(Weighting::CloudInCell);
+        let cic = state.self_force(0, off_center, weight).unwrap();
+        assert!(magnitude(cic) > 1e-6 * reference);
+        assert!(magnitude(tsc) > 1e-6 * reference);
+    }
+
+    #[test]
+    fn test_total_energy_conserved() {
+        let mut state = two_species_state(
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.1, 0.1, 0.1)),
+            (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.05, 0.05, 0.05)),
+        );
+        state.set_max_energy_drift(Some(0.05));
+
+        let initial_energy = state.total_energy();
+        for _ in 0..100 {
+            state.step().unwrap();
+        }
+        let drift = (state.total_energy() - initial_energy).abs() / initial_energy;
+
+        assert!(drift < 0.05, "Total energy drifted by {}.", drift);
+    }
+
+    #[test]
+    fn test_energy_drift() {
+        assert_eq!(energy_drift(2.0, 3.0), 0.5);
+        assert_eq!(energy_drift(-2.0, -1.0), 0.5);
+
+        // Energies too close to zero fall back to the absolute change rather than NaN.
+        assert_eq!(energy_drift(0.0, 0.0), 0.0);
+        assert_eq!(energy_drift(0.0, 1e-20), 1e-20);
+
+        // Without particles the total energy stays at zero, which used to fail the check.
+        let mesh = BoxMesh::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.1, 0.1, 0.1),
+            Dimensions::new(5, 5, 5),
+            1e-9,
+        );
+        let species = vec![Species::new(
+            String::from("e-"),
+            ELECTRON_MASS,
+            -ELEMENTARY_CHARGE,
+            mesh.dimensions(),
+        )];
+        let mut state = SimulationState::new(mesh, species, 100, 1e-8).unwrap();
+        state.set_max_energy_drift(Some(0.05));
+
+        assert_eq!(state.total_energy(), 0.0);
+        state.step().unwrap();
+    }
+}
@@ -1,6 +1,6 @@
 use std::cmp::PartialEq;
 use std::fmt;
-use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, Neg, Sub, SubAssign};
 
 use num_traits::identities::Zero;
 
@@ -20,6 +20,73 @@ impl Vec3 {
     pub fn new(x: f64, y: f64, z: f64) -> Self {
         Vec3 { x: x, y: y, z: z }
     }
+
+    /// Computes the dot product with another vector.
+    pub fn dot(self, other: Vec3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Computes the squared magnitude, which avoids the square root when only comparing
+    /// magnitudes.
+    pub fn magnitude_squared(self) -> f64 {
+        self.dot(self)
+    }
+
+    /// Computes the magnitude.
+    pub fn magnitude(self) -> f64 {
+        self.magnitude_squared().sqrt()
+    }
+
+    /// Returns the unit vector in the same direction, or the zero vector if the magnitude is
+    /// zero, rather than NaN components.
+    pub fn normalize(self) -> Vec3 {
+        let magnitude = self.magnitude();
+
+        if magnitude == 0.0 {
+            return Vec3::zero();
+        }
+
+        self / magnitude
+    }
+
+    /// Returns whether every component lies within an absolute tolerance of that of another
+    /// vector.
+    ///
+    /// The exact comparison of `PartialEq` rarely holds after floating-point arithmetic, so
+    /// this suits assertions on simulated values.
+    pub fn approx_eq(self, other: Vec3, epsilon: f64) -> bool {
+        (0..3).all(|axis| (self[axis] - other[axis]).abs() <= epsilon)
+    }
+
+    /// Returns whether every component lies within a tolerance, relative to the larger of the
+    /// two magnitudes of the component, of that of another vector.
+    ///
+    /// Unlike `approx_eq`, the tolerance scales with the values, which suits quantities such
+    /// as velocities spanning many orders of magnitude.
+    pub fn approx_eq_rel(self, other: Vec3, relative_epsilon: f64) -> bool {
+        (0..3).all(|axis| {
+            let scale = self[axis].abs().max(other[axis].abs());
+            (self[axis] - other[axis]).abs() <= relative_epsilon * scale
+        })
+    }
+
+    /// Computes the cross product with another vector.
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    /// Rotates the vector by an angle, in radians, around an axis using Rodrigues' rotation
+    /// formula. The axis need not be normalized, but must be non-zero.
+    pub fn rotate_around(self, axis: Vec3, angle: f64) -> Vec3 {
+        let k = axis.normalize();
+        let (sin, cos) = angle.sin_cos();
+
+        self * cos + k.cross(self) * sin + k * (k.dot(self) * (1.0 - cos))
+    }
 }
 
 impl PartialEq for Vec3 {
@@ -57,6 +124,30 @@ impl Zero for Vec3 {
     }
 }
 
+impl Index<usize> for Vec3 {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 index {} is out of range.", index),
+        }
+    }
+}
+
+impl IndexMut<usize> for Vec3 {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("Vec3 index {} is out of range.", index),
+        }
+    }
+}
+
 impl Add<Vec3> for Vec3 {
     type Output = Vec3;
 
@@ -141,6 +232,34 @@ where
     }
 }
 
+impl<T> Div<T> for Vec3
+where
+    f64: From<T>,
+    T: Copy,
+{
+    type Output = Vec3;
+
+    fn div(self, other: T) -> Self {
+        Vec3 {
+            x: self.x / f64::from(other),
+            y: self.y / f64::from(other),
+            z: self.z / f64::from(other),
+        }
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3 {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,6 +276,117 @@ mod tests {
         assert_eq!(u, expected);
     }
 
+    #[test]
+    fn test_dot() {
+        let u = Vec3::new(3.0, -4.0, 5.5);
+        let v = Vec3::new(1.0, 8.0, -0.5);
+
+        assert_eq!(u.dot(v), -31.75);
+        assert_eq!(u.dot(v), v.dot(u));
+
+        // Orthogonal vectors have a vanishing dot product.
+        let x = Vec3::new(2.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, -3.0, 0.0);
+        assert_eq!(x.dot(y), 0.0);
+
+        // Parallel vectors have the product of their magnitudes, or its negation if opposed.
+        assert_eq!(x.dot(x * 2.5), 10.0);
+        assert_eq!(x.dot(-x), -4.0);
+    }
+
+    #[test]
+    fn test_cross() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let z = Vec3::new(0.0, 0.0, 1.0);
+
+        // The unit vectors of a right-handed basis follow cyclically.
+        assert_eq!(x.cross(y), z);
+        assert_eq!(y.cross(z), x);
+        assert_eq!(z.cross(x), y);
+        assert_eq!(y.cross(x), -z);
+
+        let u = Vec3::new(3.0, -4.0, 5.5);
+        let v = Vec3::new(1.0, 8.0, -0.5);
+        let w = u.cross(v);
+        assert_eq!(w, Vec3::new(-42.0, 7.0, 28.0));
+
+        // The cross product is orthogonal to both vectors.
+        assert_eq!(w.dot(u), 0.0);
+        assert_eq!(w.dot(v), 0.0);
+
+        // Parallel vectors have a vanishing cross product.
+        assert_eq!(u.cross(u * -2.0), Vec3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_magnitude() {
+        let u = Vec3::new(2.0, -3.0, 6.0);
+
+        assert_eq!(u.magnitude_squared(), 49.0);
+        assert_eq!(u.magnitude(), 7.0);
+        assert_eq!(Vec3::new(0.0, 0.0, 0.0).magnitude(), 0.0);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let u = Vec3::new(2.0, -3.0, 6.0);
+        let unit = u.normalize();
+
+        assert!((unit.magnitude() - 1.0).abs() < 1e-15);
+        assert_eq!(unit, Vec3::new(2.0 / 7.0, -3.0 / 7.0, 6.0 / 7.0));
+
+        // A unit vector is unchanged, while the zero vector stays zero rather than NaN.
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(x.normalize(), x);
+        assert_eq!(
+            Vec3::new(0.0, 0.0, 0.0).normalize(),
+            Vec3::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let u = Vec3::new(0.1, 0.2, 0.3);
+        let v = Vec3::new(0.1 + 0.2, 0.2, 0.3) - Vec3::new(0.2, 0.0, 0.0);
+
+        // Rounding leaves the vectors unequal, though well within a tolerance.
+        assert_ne!(u, v);
+        assert!(u.approx_eq(v, 1e-15));
+        assert!(!u.approx_eq(Vec3::new(0.1, 0.2, 0.3001), 1e-6));
+
+        // A relative tolerance scales with each component.
+        let w = Vec3::new(1e6, 1e-6, 0.0);
+        assert!(w.approx_eq_rel(Vec3::new(1e6 + 0.5, 1e-6 * (1.0 + 1e-7), 0.0), 1e-6));
+        assert!(!w.approx_eq_rel(Vec3::new(1e6, 2e-6, 0.0), 1e-6));
+        assert!(!w.approx_eq(Vec3::new(1e6 + 0.5, 1e-6, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn test_rotate_around() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let z = Vec3::new(0.0, 0.0, 2.0);
+
+        // Rotating a quarter turn around z takes x onto y.
+        let error = x.rotate_around(z, std::f64::consts::FRAC_PI_2) - Vec3::new(0.0, 1.0, 0.0);
+        assert!(error.dot(error) < 1e-24);
+
+        // Rotating around the vector's own axis leaves it unchanged.
+        let u = Vec3::new(1.0, -2.0, 3.0);
+        let error = u.rotate_around(u, 1.234) - u;
+        assert!(error.dot(error) < 1e-24);
+    }
+
+    #[test]
+    fn test_index() {
+        let mut u = Vec3::new(1.0, 2.0, 3.0);
+        u[1] = -2.0;
+
+        assert_eq!(u[0], 1.0);
+        assert_eq!(u[1], -2.0);
+        assert_eq!(u[2], 3.0);
+    }
+
     #[test]
     fn test_add() {
         let u = Vec3 {
@@ -257,4 +487,35 @@ mod tests {
 
         assert_eq!(u * c, expected_result);
     }
+
+    #[test]
+    fn test_scalar_divide() {
+        let u = Vec3 {
+            x: 3.0,
+            y: -4.0,
+            z: 5.5,
+        };
+        let expected_result = Vec3 {
+            x: 1.5,
+            y: -2.0,
+            z: 2.75,
+        };
+
+        assert_eq!(u / 2.0, expected_result);
+        assert_eq!(u / 2.0f32, expected_result);
+
+        // Dividing by zero gives infinite components, as for floats.
+        let infinite = u / 0.0;
+        assert_eq!(infinite.x, f64::INFINITY);
+        assert_eq!(infinite.y, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_negate() {
+        let u = Vec3::new(3.0, -4.0, 5.5);
+
+        assert_eq!(-u, Vec3::new(-3.0, 4.0, -5.5));
+        assert_eq!(-(-u), u);
+        assert_eq!(u + -u, Vec3::new(0.0, 0.0, 0.0));
+    }
 }